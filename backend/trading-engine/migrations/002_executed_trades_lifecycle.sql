@@ -0,0 +1,10 @@
+-- Link each follower copy back to the leader order and track its full lifecycle.
+-- order_hash is the leader fill hash, hl_oid the follower's own order id.
+ALTER TABLE executed_trades
+    ADD COLUMN leader_oid BIGINT,
+    ADD COLUMN status_reason TEXT,  -- exchange rejection / failure message
+    ADD COLUMN updated_at TIMESTAMP DEFAULT NOW();
+
+-- status: sent, resting, filled, rejected, cancelled, failed
+CREATE INDEX executed_trades_leader_order_idx ON executed_trades (trader_address, leader_oid);
+CREATE INDEX executed_trades_follower_idx ON executed_trades (follower_address, timestamp DESC);
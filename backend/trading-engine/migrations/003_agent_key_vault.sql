@@ -0,0 +1,16 @@
+-- Agent keys move out of followers.agent_signature into an envelope-encrypted store.
+-- Existing values are imported by the engine at startup and the column is cleared.
+CREATE TABLE agent_keys (
+    id SERIAL PRIMARY KEY,
+    follower_id INT NOT NULL REFERENCES followers(id) ON DELETE CASCADE,
+    master_key_id TEXT NOT NULL,     -- which master key wraps the data key
+    wrapped_data_key TEXT NOT NULL,  -- per-key data key, encrypted with the master key
+    ciphertext TEXT NOT NULL,        -- agent secret, encrypted with the data key
+    created_at TIMESTAMP DEFAULT NOW(),
+    revoked_at TIMESTAMP
+);
+
+-- at most one usable key per follower
+CREATE UNIQUE INDEX agent_keys_active_idx ON agent_keys (follower_id) WHERE revoked_at IS NULL;
+
+ALTER TABLE followers ALTER COLUMN agent_signature DROP NOT NULL;
@@ -0,0 +1,10 @@
+-- Per copy config sizing of opening orders:
+--   ratio          leader size * ratio (previous behaviour)
+--   equity         leader size * follower equity / leader equity * ratio
+--   fixed_notional fixed_notional USD per order
+ALTER TABLE copy_configs
+    ADD COLUMN sizing_mode TEXT NOT NULL DEFAULT 'ratio'
+        CHECK (sizing_mode IN ('ratio', 'equity', 'fixed_notional')),
+    ADD COLUMN fixed_notional DECIMAL(20,8) CHECK (fixed_notional > 0),
+    ADD CONSTRAINT copy_configs_fixed_notional_set
+        CHECK (sizing_mode <> 'fixed_notional' OR fixed_notional IS NOT NULL);
@@ -0,0 +1,13 @@
+-- How follower orders are placed:
+--   ioc        immediate-or-cancel within max_slippage_bps of the current mid
+--   aggressive immediate-or-cancel with a wide band, like a market order
+--   gtc        rest at the leader's price, cancelled after gtc_timeout_secs
+ALTER TABLE copy_configs
+    ADD COLUMN execution_style TEXT NOT NULL DEFAULT 'ioc'
+        CHECK (execution_style IN ('ioc', 'aggressive', 'gtc')),
+    ADD COLUMN max_slippage_bps INT NOT NULL DEFAULT 50
+        CHECK (max_slippage_bps BETWEEN 0 AND 10000),
+    ADD COLUMN gtc_timeout_secs INT NOT NULL DEFAULT 30
+        CHECK (gtc_timeout_secs > 0);
+
+-- status now also: skipped (no fill inside the band), cancelled (GTC timed out)
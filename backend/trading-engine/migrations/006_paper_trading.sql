@@ -0,0 +1,9 @@
+-- Paper copy configs run through the same pipeline as live ones, but their orders
+-- are filled at the leader's price by the engine and never sent to the exchange.
+ALTER TABLE copy_configs
+    ADD COLUMN mode TEXT NOT NULL DEFAULT 'live'
+        CHECK (mode IN ('live', 'paper'));
+
+-- status now also: paper_filled (simulated fill of a paper copy, hl_oid stays NULL)
+CREATE INDEX executed_trades_paper_fills_idx ON executed_trades (follower_address)
+    WHERE status = 'paper_filled';
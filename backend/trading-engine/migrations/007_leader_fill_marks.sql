@@ -0,0 +1,9 @@
+-- Latest fill forwarded per leader (lowercased address), used to pick the fills a
+-- userFills snapshot still has to deliver after a reconnect or restart.
+CREATE TABLE leader_fill_marks (
+    trader_address TEXT PRIMARY KEY,
+    last_time BIGINT NOT NULL,  -- fill time, unix ms
+    last_tid BIGINT NOT NULL,
+    last_hash TEXT NOT NULL,
+    updated_at TIMESTAMP DEFAULT NOW()
+);
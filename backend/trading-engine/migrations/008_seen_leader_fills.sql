@@ -0,0 +1,20 @@
+-- Leader fills and grouped orders already processed, so fills replayed by a
+-- reconnect snapshot or after a restart are never copied twice. Pruned daily.
+CREATE TABLE seen_leader_fills (
+    hash TEXT NOT NULL,
+    tid BIGINT NOT NULL,
+    trader_address TEXT NOT NULL,
+    seen_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (hash, tid)
+);
+
+CREATE TABLE seen_leader_orders (
+    trader_address TEXT NOT NULL,
+    oid BIGINT NOT NULL,
+    first_fill_time BIGINT NOT NULL,  -- unix ms
+    seen_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (trader_address, oid, first_fill_time)
+);
+
+CREATE INDEX seen_leader_fills_seen_at_idx ON seen_leader_fills (seen_at);
+CREATE INDEX seen_leader_orders_seen_at_idx ON seen_leader_orders (seen_at);
@@ -0,0 +1,8 @@
+-- Follower copies still queued when the engine shut down. Their leader orders
+-- are already marked seen, so they are re-dispatched from here on the next start.
+CREATE TABLE pending_copy_tasks (
+    id BIGSERIAL PRIMARY KEY,
+    follower_address TEXT NOT NULL,
+    leader_order JSONB NOT NULL,  -- the grouped FullOrder
+    saved_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
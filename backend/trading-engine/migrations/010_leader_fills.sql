@@ -0,0 +1,24 @@
+-- Every fill of a leader as Hyperliquid reported it, the input of the leaderboard.
+-- Fills without a trade id (older exports) of one transaction share a key and
+-- are stored once.
+CREATE TABLE leader_fills (
+    trader_address TEXT NOT NULL,
+    hash TEXT NOT NULL,
+    tid BIGINT NOT NULL,
+    oid BIGINT NOT NULL,
+    coin TEXT NOT NULL,
+    side TEXT NOT NULL,        -- "B" or "A"
+    px DECIMAL(30,10) NOT NULL,
+    sz DECIMAL(30,10) NOT NULL,
+    dir TEXT,                  -- "Open Long", "Close Short", "Long > Short", ...
+    start_position DECIMAL(30,10),
+    closed_pnl DECIMAL(30,10) NOT NULL DEFAULT 0,
+    fee DECIMAL(30,10) NOT NULL,  -- negative for maker rebates
+    fee_token TEXT NOT NULL,
+    crossed BOOLEAN NOT NULL,  -- taker
+    filled_at TIMESTAMP NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (trader_address, hash, tid)
+);
+
+CREATE INDEX leader_fills_trader_filled_at_idx ON leader_fills (trader_address, filled_at);
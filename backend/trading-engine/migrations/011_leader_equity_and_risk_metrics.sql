@@ -0,0 +1,17 @@
+-- Account value of each leader over time, the denominator of their returns.
+CREATE TABLE leader_equity (
+    trader_address TEXT NOT NULL,
+    account_value DECIMAL(30,10) NOT NULL,
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (trader_address, recorded_at)
+);
+
+-- Ratios are annualized now and outgrow the old precision
+ALTER TABLE leaderboard
+    ALTER COLUMN sharpe TYPE DECIMAL(12,3),
+    ADD COLUMN sortino DECIMAL(12,3),
+    ADD COLUMN calmar DECIMAL(12,3),
+    ADD COLUMN profit_factor DECIMAL(12,3),
+    ADD COLUMN avg_hold_secs BIGINT,
+    ADD COLUMN expectancy DECIMAL(20,8),
+    ADD COLUMN trades_30d INT NOT NULL DEFAULT 0;
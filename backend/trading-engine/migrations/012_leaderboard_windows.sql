@@ -0,0 +1,15 @@
+-- One leaderboard row per trader and time window (24h, 7d, 30d, 90d, all).
+-- The table is a cache rebuilt by the daily job, so old rows are dropped.
+DELETE FROM leaderboard;
+
+ALTER TABLE leaderboard DROP CONSTRAINT leaderboard_pkey;
+ALTER TABLE leaderboard ADD COLUMN time_window TEXT NOT NULL;
+ALTER TABLE leaderboard ADD PRIMARY KEY (trader_address, time_window);
+
+ALTER TABLE leaderboard RENAME COLUMN pnl_percent_30d TO return_pct;
+ALTER TABLE leaderboard RENAME COLUMN volume_7d TO volume;
+ALTER TABLE leaderboard RENAME COLUMN trades_30d TO trades;
+ALTER TABLE leaderboard ALTER COLUMN return_pct TYPE DECIMAL(12,4);
+ALTER TABLE leaderboard ALTER COLUMN volume TYPE DECIMAL(30,8);
+
+CREATE INDEX leaderboard_window_idx ON leaderboard (time_window);
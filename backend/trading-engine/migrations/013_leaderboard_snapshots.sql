@@ -0,0 +1,24 @@
+-- The leaderboard as each daily run left it, never updated afterwards, so a
+-- trader's metrics and rank can be followed over time.
+CREATE TABLE leaderboard_snapshots (
+    snapshot_date DATE NOT NULL,
+    time_window TEXT NOT NULL,
+    trader_address TEXT NOT NULL,
+    rank INT NOT NULL,             -- by return within the window, 1 is best
+    return_pct DECIMAL(12,4),
+    win_rate DECIMAL(5,2),
+    sharpe DECIMAL(12,3),
+    sortino DECIMAL(12,3),
+    calmar DECIMAL(12,3),
+    max_drawdown DECIMAL(10,4),
+    profit_factor DECIMAL(12,3),
+    avg_hold_secs BIGINT,
+    expectancy DECIMAL(20,8),
+    trades INT NOT NULL,
+    followers_count INT,
+    volume DECIMAL(30,8),
+    recorded_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (snapshot_date, time_window, trader_address)
+);
+
+CREATE INDEX leaderboard_snapshots_trader_idx ON leaderboard_snapshots (trader_address, time_window, snapshot_date);
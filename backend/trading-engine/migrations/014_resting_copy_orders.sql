@@ -0,0 +1,17 @@
+-- Live GTC copies with size left on the book and when to cancel it. Kept here
+-- rather than only in a timer so that a restart still cancels them.
+CREATE TABLE resting_copy_orders (
+    trade_id BIGINT PRIMARY KEY REFERENCES executed_trades(id) ON DELETE CASCADE,
+    follower_address TEXT NOT NULL,
+    coin TEXT NOT NULL,
+    is_buy BOOLEAN NOT NULL,
+    oid BIGINT NOT NULL,                          -- the follower's order
+    limit_px DECIMAL(20,8) NOT NULL,
+    sz DECIMAL(20,8) NOT NULL,                    -- as ordered
+    filled_sz DECIMAL(20,8) NOT NULL DEFAULT 0,   -- filled when placed
+    filled_px DECIMAL(20,8) NOT NULL DEFAULT 0,
+    cancel_at TIMESTAMP NOT NULL
+);
+
+-- status now also: partially_filled (the GTC timeout cancelled the unfilled
+-- rest; size and price are what filled)
@@ -0,0 +1,8 @@
+-- A mark keeps every fill seen at its last_time, identified by hash and tid,
+-- instead of assuming tids only increase within a millisecond.
+ALTER TABLE leader_fill_marks ADD COLUMN last_fills JSONB NOT NULL DEFAULT '[]';  -- [{"hash", "tid"}]
+
+UPDATE leader_fill_marks
+SET last_fills = jsonb_build_array(jsonb_build_object('hash', last_hash, 'tid', last_tid));
+
+ALTER TABLE leader_fill_marks DROP COLUMN last_tid, DROP COLUMN last_hash;
@@ -0,0 +1,9 @@
+-- Running all-time leaderboard totals of each leader, so the daily job reads
+-- only the fills since the last update instead of every fill ever recorded.
+CREATE TABLE leader_all_time_totals (
+    trader_address TEXT PRIMARY KEY,
+    anchor TIMESTAMP NOT NULL,        -- just before the first fill; days count from here
+    folded_until TIMESTAMP NOT NULL,  -- fills and days up to here are in `folded`
+    folded JSONB NOT NULL,            -- totals, daily returns and positions still open
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
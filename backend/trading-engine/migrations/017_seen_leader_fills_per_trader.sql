@@ -0,0 +1,4 @@
+-- Both sides of a trade share its hash and tid: when two monitored leaders
+-- trade with each other, each of them has a fill to process.
+ALTER TABLE seen_leader_fills DROP CONSTRAINT seen_leader_fills_pkey;
+ALTER TABLE seen_leader_fills ADD PRIMARY KEY (trader_address, hash, tid);
@@ -1,4 +1,11 @@
+use crate::config::{ApiConfig, DatabaseConfig};
+use crate::engine::backfill::Backfill;
+use crate::engine::subscriptions::SubscriptionHandle;
+use crate::hyperliquid::health::PoolHealth;
+use crate::shutdown::Shutdown;
+use crate::supervisor::Supervisor;
 use crate::routes;
+use crate::vault::KeyVault;
 use axum::{
     routing::{get},
     Router,
@@ -12,24 +19,74 @@ use std::sync::Arc;
 pub struct Server {
     pub port: u16,
     pub db_url: String,
+    pub db_max_connections: u32,
     pub pool: Option<PgPool>,
+    pub subscriptions: Option<SubscriptionHandle>,
+    pub ws_health: Option<Arc<PoolHealth>>,
+    pub vault: Option<Arc<KeyVault>>,
+    pub shutdown: Option<Shutdown>,
+    pub supervisor: Option<Arc<Supervisor>>,
+    pub backfill: Option<Arc<Backfill>>,
+    /// Backfill traders as they are registered
+    pub backfill_on_register: bool,
 }
 
 impl Server {
-    pub fn new(port: u16, db_url: String) -> Self {
+    pub fn new(api: &ApiConfig, database: &DatabaseConfig) -> Self {
         Self {
-            port,
-            db_url,
+            port: api.port,
+            db_url: database.url.clone(),
+            db_max_connections: database.max_connections,
             pool: None,
+            subscriptions: None,
+            ws_health: None,
+            vault: None,
+            shutdown: None,
+            supervisor: None,
+            backfill: None,
+            backfill_on_register: false,
         }
     }
 
+    pub fn with_subscriptions(mut self, subscriptions: SubscriptionHandle) -> Self {
+        self.subscriptions = Some(subscriptions);
+        self
+    }
+
+    pub fn with_ws_health(mut self, health: Arc<PoolHealth>) -> Self {
+        self.ws_health = Some(health);
+        self
+    }
+
+    pub fn with_vault(mut self, vault: Arc<KeyVault>) -> Self {
+        self.vault = Some(vault);
+        self
+    }
+
+    /// Stop accepting connections and finish in-flight requests on shutdown.
+    pub fn with_shutdown(mut self, shutdown: Shutdown) -> Self {
+        self.shutdown = Some(shutdown);
+        self
+    }
+
+    /// Report the supervised pipeline on `/health` and `/ready`.
+    pub fn with_supervisor(mut self, supervisor: Arc<Supervisor>) -> Self {
+        self.supervisor = Some(supervisor);
+        self
+    }
+
+    pub fn with_backfill(mut self, backfill: Arc<Backfill>, on_register: bool) -> Self {
+        self.backfill = Some(backfill);
+        self.backfill_on_register = on_register;
+        self
+    }
+
     pub async fn start(&self) -> anyhow::Result<()> {
         println!("Starting API server on port {}", self.port);
 
         // Create database pool
         let pool = PgPoolOptions::new()
-            .max_connections(5)
+            .max_connections(self.db_max_connections)
             .connect(&self.db_url)
             .await?;
 
@@ -38,7 +95,15 @@ impl Server {
 
         let app = server.router();
         let addr = SocketAddr::from(([0, 0, 0, 0], self.port));
-        axum::serve(tokio::net::TcpListener::bind(addr).await?, app).await?;
+        let shutdown = self.shutdown.clone();
+        axum::serve(tokio::net::TcpListener::bind(addr).await?, app)
+            .with_graceful_shutdown(async move {
+                match shutdown {
+                    Some(shutdown) => shutdown.wait().await,
+                    None => std::future::pending().await,
+                }
+            })
+            .await?;
         Ok(())
     }
 
@@ -46,12 +111,15 @@ impl Server {
         let state = Arc::new(self.clone());
         Router::new()
             .route("/", get(|| async { "Hyperliquid Copy Trading Engine API" }))
-            .route("/health", get(|| async { "OK" }))
+            .route("/health", get(routes::health::health))
+            .route("/ready", get(routes::health::ready))
             .nest("/traders", routes::traders::create_router().with_state(state.clone()))
             .nest("/followers", routes::followers::create_router().with_state(state.clone()))
             .nest("/copy_configs", routes::copy_configs::create_router().with_state(state.clone()))
             .nest("/leaderboard", routes::leaderboard::create_router().with_state(state.clone()))
-            .nest("/trades", routes::trades::create_router().with_state(state))
+            .nest("/trades", routes::trades::create_router().with_state(state.clone()))
+            .nest("/backtest", routes::backtest::create_router().with_state(state.clone()))
+            .with_state(state)
     }
 }
 
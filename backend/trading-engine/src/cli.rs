@@ -0,0 +1,126 @@
+use std::path::PathBuf;
+use chrono::{DateTime, NaiveDate};
+use clap::{Args, Parser, Subcommand};
+use rust_decimal::Decimal;
+
+use crate::engine::backtest::{self, BacktestConfig};
+use crate::engine::sizing::SizingMode;
+
+/// Without a subcommand the engine starts and copies leaders live.
+#[derive(Debug, Parser)]
+#[command(about = "Hyperliquid copy trading engine")]
+pub struct Cli {
+    /// TOML config file; `engine.toml` is read if present
+    #[arg(long, global = true)]
+    pub config: Option<PathBuf>,
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Replay a leader's exported fills through a copy config, offline
+    Backtest(BacktestArgs),
+}
+
+#[derive(Debug, Args)]
+pub struct BacktestArgs {
+    /// Leader address the fills belong to
+    #[arg(long)]
+    leader: String,
+    /// `userFills` export, as .json or .csv
+    #[arg(long)]
+    fills: PathBuf,
+    /// Start of the range: YYYY-MM-DD, RFC 3339 or unix ms
+    #[arg(long, value_parser = parse_start)]
+    from: Option<u64>,
+    /// End of the range, inclusive: YYYY-MM-DD, RFC 3339 or unix ms
+    #[arg(long, value_parser = parse_end)]
+    to: Option<u64>,
+    #[arg(long, default_value = "0.01")]
+    ratio: Decimal,
+    #[arg(long)]
+    max_risk_per_trade: Option<Decimal>,
+    /// Copy every opening order with this many USD instead of by ratio
+    #[arg(long)]
+    fixed_notional: Option<Decimal>,
+    /// Follower account value at the start, in USD
+    #[arg(long, default_value = "10000")]
+    initial_equity: Decimal,
+    /// Print the whole report as JSON
+    #[arg(long)]
+    json: bool,
+}
+
+pub async fn backtest(args: BacktestArgs) -> anyhow::Result<()> {
+    let fills = backtest::load_fills(&args.fills)?;
+    let config = BacktestConfig {
+        ratio: args.ratio,
+        max_risk_per_trade: args.max_risk_per_trade,
+        sizing_mode: if args.fixed_notional.is_some() { SizingMode::FixedNotional } else { SizingMode::Ratio },
+        fixed_notional: args.fixed_notional,
+        initial_equity: args.initial_equity,
+    };
+    let report = backtest::run(&args.leader, fills, args.from, args.to, &config).await?;
+
+    if args.json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+    println!("leader          {}", report.leader);
+    println!("range           {} .. {}", format_ms(report.from), format_ms(report.to));
+    println!("leader orders   {} ({} not copied)", report.leader_orders, report.skipped.len());
+    println!("follower trades {}", report.trades.len());
+    println!("equity          {} -> {}", report.initial_equity, report.final_equity.round_dp(2));
+    println!("pnl             {}", report.total_pnl.round_dp(2));
+    println!("fees            {}", report.total_fees.round_dp(2));
+    println!("max drawdown    {}%", (report.max_drawdown * Decimal::ONE_HUNDRED).round_dp(2));
+    for skipped in &report.skipped {
+        println!("  skipped {} {} at {}: {}", skipped.dir, skipped.coin, format_ms(skipped.time), skipped.reason);
+    }
+    Ok(())
+}
+
+fn parse_start(value: &str) -> Result<u64, String> {
+    parse_time(value, false)
+}
+
+fn parse_end(value: &str) -> Result<u64, String> {
+    parse_time(value, true)
+}
+
+/// Unix ms of `value`; a bare date is the start of that day, or its last
+/// millisecond for the end of a range.
+fn parse_time(value: &str, end_of_day: bool) -> Result<u64, String> {
+    if let Ok(ms) = value.parse::<u64>() {
+        return Ok(ms);
+    }
+    if let Ok(time) = DateTime::parse_from_rfc3339(value) {
+        return u64::try_from(time.timestamp_millis()).map_err(|_| format!("{value} is before 1970"));
+    }
+    let date = NaiveDate::parse_from_str(value, "%Y-%m-%d")
+        .map_err(|_| format!("{value} is not a date (YYYY-MM-DD), RFC 3339 time or unix ms"))?;
+    let start = date.and_hms_opt(0, 0, 0).unwrap_or_default().and_utc().timestamp_millis();
+    let ms = if end_of_day { start + 86_400_000 - 1 } else { start };
+    u64::try_from(ms).map_err(|_| format!("{value} is before 1970"))
+}
+
+fn format_ms(ms: u64) -> String {
+    DateTime::from_timestamp_millis(ms as i64)
+        .map(|t| t.to_rfc3339())
+        .unwrap_or_else(|| ms.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_time() {
+        assert_eq!(parse_time("1700000000000", false), Ok(1_700_000_000_000));
+        assert_eq!(parse_time("2024-01-02", false), Ok(1_704_153_600_000));
+        assert_eq!(parse_time("2024-01-02", true), Ok(1_704_239_999_999));
+        assert_eq!(parse_time("2024-01-02T00:00:01Z", true), Ok(1_704_153_601_000));
+        assert!(parse_time("yesterday", false).is_err());
+    }
+}
@@ -0,0 +1,418 @@
+use std::path::Path;
+use std::time::Duration;
+use serde::Deserialize;
+use thiserror::Error;
+use tokio_cron_scheduler::Job;
+
+use crate::hyperliquid::network::{Network, NetworkConfig, NetworkError};
+
+/// Used when `--config` is not given, if it exists.
+pub const DEFAULT_CONFIG_PATH: &str = "engine.toml";
+
+/// Prefix of the generic overrides, e.g. `ENGINE__EXECUTOR__WORKERS=16`.
+const ENV_PREFIX: &str = "ENGINE__";
+
+/// Variables the engine read before it had a config file, still honoured.
+const ENV_ALIASES: &[(&str, &str, &str)] = &[
+    ("DB_URL", "database", "url"),
+    ("HL_NETWORK", "network", "name"),
+    ("HL_API_URL", "network", "api_url"),
+    ("HL_WS_URL", "network", "ws_url"),
+    ("WS_POOL_SIZE", "ingest", "pool_size"),
+    ("SNAPSHOT_MAX_AGE_SECS", "ingest", "snapshot_max_age_secs"),
+];
+
+#[derive(Error, Debug)]
+pub enum ConfigError {
+    #[error("Failed to read config file {0}: {1}")]
+    Read(String, std::io::Error),
+    #[error("Invalid config: {0}")]
+    Parse(#[from] toml::de::Error),
+    #[error("{0} does not name a config setting; expected {ENV_PREFIX}<SECTION>__<KEY>")]
+    UnknownOverride(String),
+    #[error("Invalid config: {0}")]
+    Invalid(String),
+    #[error("Invalid network config: {0}")]
+    Network(#[from] NetworkError),
+}
+
+/// Every runtime knob of the engine. Loaded from a TOML file, then overridden
+/// from the environment; secrets (agent key master keys) stay env-only.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub database: DatabaseConfig,
+    pub api: ApiConfig,
+    pub network: NetworkSection,
+    pub ingest: IngestConfig,
+    pub grouper: GrouperConfig,
+    pub executor: ExecutorConfig,
+    pub backfill: BackfillConfig,
+    pub cron: CronConfig,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct DatabaseConfig {
+    pub url: String,
+    /// Connections of the API server's pool
+    pub max_connections: u32,
+}
+
+impl Default for DatabaseConfig {
+    fn default() -> Self {
+        Self { url: String::new(), max_connections: 5 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ApiConfig {
+    pub port: u16,
+}
+
+impl Default for ApiConfig {
+    fn default() -> Self {
+        Self { port: 3000 }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NetworkSection {
+    pub name: Network,
+    /// Only for the custom network
+    pub api_url: Option<String>,
+    pub ws_url: Option<String>,
+}
+
+impl Default for NetworkSection {
+    fn default() -> Self {
+        Self { name: Network::Testnet, api_url: None, ws_url: None }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct IngestConfig {
+    /// Sockets shared by all leader subscriptions
+    pub pool_size: usize,
+    /// Fills missed while disconnected are copied only if younger than this
+    pub snapshot_max_age_secs: Option<u64>,
+}
+
+impl Default for IngestConfig {
+    fn default() -> Self {
+        Self { pool_size: crate::hyperliquid::ws::DEFAULT_POOL_SIZE, snapshot_max_age_secs: None }
+    }
+}
+
+impl IngestConfig {
+    pub fn snapshot_max_age(&self) -> Option<Duration> {
+        self.snapshot_max_age_secs.map(Duration::from_secs)
+    }
+}
+
+/// How fills are debounced into whole leader orders.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct GrouperConfig {
+    /// How often an order's debouncer checks for more fills
+    pub debounce_ms: u64,
+    /// An order is complete once no fill arrived for this long
+    pub quiet_ms: u64,
+    /// How often orders whose debouncer missed them are swept up
+    pub sweep_interval_ms: u64,
+    /// Age of the last fill after which the sweeper flushes an order
+    pub sweep_after_ms: u64,
+}
+
+impl Default for GrouperConfig {
+    fn default() -> Self {
+        Self { debounce_ms: 420, quiet_ms: 400, sweep_interval_ms: 5_000, sweep_after_ms: 600 }
+    }
+}
+
+impl GrouperConfig {
+    pub fn debounce(&self) -> Duration {
+        Duration::from_millis(self.debounce_ms)
+    }
+
+    pub fn quiet(&self) -> Duration {
+        Duration::from_millis(self.quiet_ms)
+    }
+
+    pub fn sweep_interval(&self) -> Duration {
+        Duration::from_millis(self.sweep_interval_ms)
+    }
+
+    pub fn sweep_after(&self) -> Duration {
+        Duration::from_millis(self.sweep_after_ms)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ExecutorConfig {
+    pub workers: usize,
+    /// Pending tasks per worker before the dispatcher starts waiting
+    pub queue_capacity: usize,
+    /// How often copy configs are reloaded from the database
+    pub follower_refresh_secs: u64,
+    /// At shutdown, how long queued copies may still be placed before the rest
+    /// are saved for the next start
+    pub drain_timeout_secs: u64,
+    /// Copies saved at shutdown of leader orders older than this are dropped
+    pub recover_max_age_secs: u64,
+}
+
+impl Default for ExecutorConfig {
+    fn default() -> Self {
+        Self {
+            workers: 10,
+            queue_capacity: 1000,
+            follower_refresh_secs: 300,
+            drain_timeout_secs: 20,
+            recover_max_age_secs: 120,
+        }
+    }
+}
+
+impl ExecutorConfig {
+    pub fn follower_refresh(&self) -> Duration {
+        Duration::from_secs(self.follower_refresh_secs)
+    }
+
+    pub fn drain_timeout(&self) -> Duration {
+        Duration::from_secs(self.drain_timeout_secs)
+    }
+
+    pub fn recover_max_age(&self) -> Duration {
+        Duration::from_secs(self.recover_max_age_secs)
+    }
+}
+
+/// Loading leader history from the info API into `leader_fills`.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct BackfillConfig {
+    /// How far back a backfill reaches
+    pub window_days: u64,
+    /// Backfill leaders as they are registered
+    pub on_register: bool,
+}
+
+impl Default for BackfillConfig {
+    fn default() -> Self {
+        Self { window_days: 30, on_register: true }
+    }
+}
+
+impl BackfillConfig {
+    pub fn window(&self) -> Duration {
+        Duration::from_secs(self.window_days * 24 * 3600)
+    }
+}
+
+/// Schedules are cron expressions with a leading seconds field, in UTC.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct CronConfig {
+    pub leaderboard: String,
+    pub prune_seen: String,
+    /// Backfill of every active leader
+    pub backfill: String,
+    /// Snapshot of every active leader's account value
+    pub equity: String,
+    /// How long leader fills and orders are remembered for deduplication
+    pub seen_retention_days: u64,
+}
+
+impl Default for CronConfig {
+    fn default() -> Self {
+        Self {
+            leaderboard: "0 5 0 * * *".to_string(),
+            prune_seen: "0 15 3 * * *".to_string(),
+            backfill: "0 0 2 * * Sun".to_string(),
+            equity: "0 0 * * * *".to_string(),
+            seen_retention_days: 7,
+        }
+    }
+}
+
+impl CronConfig {
+    pub fn seen_retention(&self) -> Duration {
+        Duration::from_secs(self.seen_retention_days * 24 * 3600)
+    }
+}
+
+impl Config {
+    /// Read `path` (or [`DEFAULT_CONFIG_PATH`] if it exists), apply the process
+    /// environment and validate.
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        let path = path.unwrap_or(Path::new(DEFAULT_CONFIG_PATH));
+        let file = match std::fs::read_to_string(path) {
+            Ok(file) => Some(file),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound && path == Path::new(DEFAULT_CONFIG_PATH) => None,
+            Err(e) => return Err(ConfigError::Read(path.display().to_string(), e)),
+        };
+        Self::from_sources(file.as_deref(), std::env::vars())
+    }
+
+    fn from_sources(
+        file: Option<&str>,
+        env: impl IntoIterator<Item = (String, String)>,
+    ) -> Result<Self, ConfigError> {
+        let mut table: toml::Table = toml::from_str(file.unwrap_or_default())?;
+        for (var, value) in env {
+            let (section, key) = if let Some(path) = var.strip_prefix(ENV_PREFIX) {
+                let Some((section, key)) = path.split_once("__") else {
+                    return Err(ConfigError::UnknownOverride(var));
+                };
+                (section.to_lowercase(), key.to_lowercase())
+            } else if let Some((_, section, key)) = ENV_ALIASES.iter().find(|(alias, _, _)| *alias == var) {
+                (section.to_string(), key.to_string())
+            } else {
+                continue;
+            };
+
+            let section = table
+                .entry(section)
+                .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+                .as_table_mut()
+                .ok_or_else(|| ConfigError::UnknownOverride(var.clone()))?;
+            section.insert(key, env_value(&value));
+        }
+
+        let config: Config = table.try_into()?;
+        config.validate()?;
+        Ok(config)
+    }
+
+    pub fn network(&self) -> Result<NetworkConfig, NetworkError> {
+        NetworkConfig::resolve(self.network.name, self.network.api_url.clone(), self.network.ws_url.clone())
+    }
+
+    fn validate(&self) -> Result<(), ConfigError> {
+        let invalid = |reason: &str| Err(ConfigError::Invalid(reason.to_string()));
+        if self.database.url.is_empty() {
+            return invalid("database.url (or DB_URL) must be set");
+        }
+        if self.database.max_connections == 0 {
+            return invalid("database.max_connections must be at least 1");
+        }
+        if self.api.port == 0 {
+            return invalid("api.port must not be 0");
+        }
+        self.network()?;
+        if self.ingest.pool_size == 0 {
+            return invalid("ingest.pool_size must be at least 1");
+        }
+
+        let grouper = &self.grouper;
+        if grouper.debounce_ms == 0 || grouper.sweep_interval_ms == 0 {
+            return invalid("grouper.debounce_ms and grouper.sweep_interval_ms must be positive");
+        }
+        if grouper.sweep_after_ms < grouper.quiet_ms {
+            // the sweeper would cut orders the debouncer still considers open
+            return invalid("grouper.sweep_after_ms must not be shorter than grouper.quiet_ms");
+        }
+
+        if self.executor.workers == 0 || self.executor.queue_capacity == 0 {
+            return invalid("executor.workers and executor.queue_capacity must be at least 1");
+        }
+        if self.executor.follower_refresh_secs == 0 {
+            return invalid("executor.follower_refresh_secs must be positive");
+        }
+
+        if self.backfill.window_days == 0 {
+            return invalid("backfill.window_days must be at least 1");
+        }
+
+        let cron = &self.cron;
+        for (name, schedule) in [
+            ("cron.leaderboard", &cron.leaderboard),
+            ("cron.prune_seen", &cron.prune_seen),
+            ("cron.backfill", &cron.backfill),
+            ("cron.equity", &cron.equity),
+        ] {
+            if let Err(e) = Job::new(schedule.as_str(), |_, _| {}) {
+                return Err(ConfigError::Invalid(format!("{name} {schedule:?} is not a cron expression: {e}")));
+            }
+        }
+        if self.cron.seen_retention_days == 0 {
+            return invalid("cron.seen_retention_days must be at least 1");
+        }
+        Ok(())
+    }
+}
+
+/// An environment value as the TOML value it spells, or else as a string.
+fn env_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut t| t.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs.iter().map(|(k, v)| (k.to_string(), v.to_string())).collect()
+    }
+
+    #[test]
+    fn test_file_and_env_overrides() {
+        let file = r#"
+            [database]
+            url = "postgres://localhost/engine"
+
+            [api]
+            port = 8080
+
+            [executor]
+            workers = 4
+        "#;
+        let config = Config::from_sources(
+            Some(file),
+            vars(&[
+                ("ENGINE__EXECUTOR__WORKERS", "16"),
+                ("ENGINE__GROUPER__QUIET_MS", "250"),
+                ("WS_POOL_SIZE", "2"),
+                ("HL_NETWORK", "mainnet"),
+                ("PATH", "/usr/bin"),
+            ]),
+        )
+        .unwrap();
+
+        assert_eq!(config.api.port, 8080);
+        assert_eq!(config.executor.workers, 16);
+        assert_eq!(config.executor.queue_capacity, 1000);
+        assert_eq!(config.grouper.quiet(), Duration::from_millis(250));
+        assert_eq!(config.ingest.pool_size, 2);
+        assert_eq!(config.network().unwrap(), NetworkConfig::mainnet());
+    }
+
+    #[test]
+    fn test_invalid_configs_are_rejected() {
+        let db = ("DB_URL", "postgres://localhost/engine");
+        assert!(Config::from_sources(None, vars(&[db])).is_ok());
+
+        let errors = [
+            Config::from_sources(None, vec![]),
+            Config::from_sources(Some("[api]\nprot = 1"), vars(&[db])),
+            Config::from_sources(None, vars(&[db, ("ENGINE__API__PORT", "http")])),
+            Config::from_sources(None, vars(&[db, ("ENGINE__PORT", "1")])),
+            Config::from_sources(None, vars(&[db, ("ENGINE__EXECUTOR__WORKERS", "0")])),
+            Config::from_sources(None, vars(&[db, ("ENGINE__GROUPER__SWEEP_AFTER_MS", "100")])),
+            Config::from_sources(None, vars(&[db, ("ENGINE__CRON__LEADERBOARD", "daily")])),
+            Config::from_sources(None, vars(&[db, ("ENGINE__BACKFILL__WINDOW_DAYS", "0")])),
+            Config::from_sources(None, vars(&[db, ("HL_NETWORK", "custom")])),
+        ];
+        for (i, result) in errors.into_iter().enumerate() {
+            assert!(result.is_err(), "case {i} was accepted");
+        }
+    }
+}
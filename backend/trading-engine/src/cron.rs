@@ -1,13 +1,23 @@
+use std::sync::Arc;
 use sqlx::PgPool;
 use tokio_cron_scheduler::{JobScheduler, Job};
-use std::time::Duration;
 
-pub async fn start_scheduler(pool: PgPool) -> anyhow::Result<()> {
-    let mut sched = JobScheduler::new().await?;
+use crate::config::CronConfig;
+use crate::engine::backfill::Backfill;
+use crate::hyperliquid::info::InfoApi;
 
-    // Daily leaderboard update at 00:05 UTC
+/// Start the scheduled jobs; the returned scheduler runs them until shut down.
+pub async fn start_scheduler(
+    pool: PgPool,
+    config: CronConfig,
+    info: InfoApi,
+    backfill: Arc<Backfill>,
+) -> anyhow::Result<JobScheduler> {
+    let sched = JobScheduler::new().await?;
+
+    // Daily leaderboard update, 00:05 UTC by default
     let pool_clone = pool.clone();
-    sched.add(Job::new_async("5 0 * * *", move |_uuid, _l| {
+    sched.add(Job::new_async(config.leaderboard.as_str(), move |_uuid, _l| {
         let pool = pool_clone.clone();
         Box::pin(async move {
             if let Err(e) = crate::engine::leaderboard::update_all_leaderboards(&pool).await {
@@ -18,19 +28,49 @@ pub async fn start_scheduler(pool: PgPool) -> anyhow::Result<()> {
         })
     })?).await?;
 
-    // // Optional: Weekly deep sync from Allium (every Sunday at 02:00 UTC)
-    // let pool_clone = pool.clone();
-    // sched.add(Job::new_async("0 2 * * SUN", move |_uuid, _l| {
-    //     let pool = pool_clone.clone();
-    //     Box::pin(async move {
-    //         if let Err(e) = crate::engine::historical_sync::full_sync_from_allium(&pool).await {
-    //             log::error!("Weekly Allium sync failed: {}", e);
-    //         } else {
-    //             log::info!("Weekly Allium historical sync completed");
-    //         }
-    //     })
-    // })?).await?;
+    // Daily pruning of the leader fill/order seen-sets, 03:15 UTC by default
+    let pool_clone = pool.clone();
+    let retention = config.seen_retention();
+    sched.add(Job::new_async(config.prune_seen.as_str(), move |_uuid, _l| {
+        let pool = pool_clone.clone();
+        Box::pin(async move {
+            match crate::engine::dedup::prune(&pool, retention).await {
+                Ok(pruned) => log::info!("Pruned {} seen leader fills and orders", pruned),
+                Err(e) => log::error!("Seen-set pruning failed: {}", e),
+            }
+        })
+    })?).await?;
+
+    // Weekly backfill of leader history from the info API, Sundays 02:00 UTC by default
+    let pool_clone = pool.clone();
+    sched.add(Job::new_async(config.backfill.as_str(), move |_uuid, _l| {
+        let pool = pool_clone.clone();
+        let backfill = backfill.clone();
+        Box::pin(async move {
+            match backfill.all(&pool).await {
+                Ok(reports) => log::info!(
+                    "Weekly backfill stored {} new fills of {} leaders",
+                    reports.iter().map(|r| r.inserted).sum::<u64>(),
+                    reports.len()
+                ),
+                Err(e) => log::error!("Weekly backfill failed: {}", e),
+            }
+        })
+    })?).await?;
+
+    // Hourly snapshot of leader account values
+    let pool_clone = pool.clone();
+    sched.add(Job::new_async(config.equity.as_str(), move |_uuid, _l| {
+        let pool = pool_clone.clone();
+        let info = info.clone();
+        Box::pin(async move {
+            match crate::engine::equity::snapshot_all(&info, &pool).await {
+                Ok(recorded) => log::info!("Recorded equity of {} leaders", recorded),
+                Err(e) => log::error!("Equity snapshot failed: {}", e),
+            }
+        })
+    })?).await?;
 
     sched.start().await?;
-    Ok(())
+    Ok(sched)
 }
@@ -0,0 +1,155 @@
+use std::collections::HashSet;
+use std::time::Duration;
+use chrono::{NaiveDateTime, Utc};
+use serde::Serialize;
+use sqlx::PgPool;
+use thiserror::Error;
+
+use crate::engine::equity;
+use crate::engine::leader_fills::{self, LeaderFillError};
+use crate::hyperliquid::info::{InfoApi, InfoError};
+use crate::models::LeaderFill;
+
+/// The most fills `userFillsByTime` returns per request.
+const PAGE_LIMIT: usize = 2000;
+/// Between leaders of a full backfill, to stay clear of the info API rate limit.
+const LEADER_PAUSE: Duration = Duration::from_secs(1);
+
+#[derive(Error, Debug)]
+pub enum BackfillError {
+    #[error(transparent)]
+    Info(#[from] InfoError),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Invalid fill: {0}")]
+    Fill(#[from] LeaderFillError),
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BackfillReport {
+    pub trader_address: String,
+    pub from: NaiveDateTime,
+    pub fetched: usize,
+    /// Fills that were not stored yet
+    pub inserted: u64,
+}
+
+/// Pages a leader's history out of the info API's `userFillsByTime` into
+/// `leader_fills`. Safe to repeat: stored fills are skipped. Hyperliquid only
+/// serves the 10000 most recent fills of an account this way.
+#[derive(Debug)]
+pub struct Backfill {
+    info: InfoApi,
+    window: Duration,
+}
+
+impl Backfill {
+    pub fn new(info: InfoApi, window: Duration) -> Self {
+        Self { info, window }
+    }
+
+    /// Store the leader's fills of the configured window, and their current
+    /// equity so returns can be computed right away.
+    pub async fn leader(&self, pool: &PgPool, leader: &str) -> Result<BackfillReport, BackfillError> {
+        let now = Utc::now();
+        let from = now - self.window;
+        let fills = self.fetch(leader, from.timestamp_millis() as u64, now.timestamp_millis() as u64).await?;
+        let inserted = leader_fills::insert(pool, &fills).await?;
+        log::info!("Backfilled {} of {} fills of {}", inserted, fills.len(), leader);
+        if let Err(e) = equity::snapshot(&self.info, pool, leader).await {
+            log::error!("Failed to record equity of {}: {}", leader, e);
+        }
+        Ok(BackfillReport { trader_address: leader.to_string(), from: from.naive_utc(), fetched: fills.len(), inserted })
+    }
+
+    /// Backfill every active leader; one failing does not stop the others.
+    pub async fn all(&self, pool: &PgPool) -> Result<Vec<BackfillReport>, BackfillError> {
+        let leaders = sqlx::query_scalar::<_, String>("SELECT address FROM traders WHERE is_active = true")
+            .fetch_all(pool)
+            .await?;
+        let mut reports = Vec::with_capacity(leaders.len());
+        for (i, leader) in leaders.iter().enumerate() {
+            if i > 0 {
+                tokio::time::sleep(LEADER_PAUSE).await;
+            }
+            match self.leader(pool, leader).await {
+                Ok(report) => reports.push(report),
+                Err(e) => log::error!("Backfill of {} failed: {}", leader, e),
+            }
+        }
+        Ok(reports)
+    }
+
+    /// Every fill of `leader` from `start` to `end` (unix ms), oldest first.
+    pub async fn fetch(&self, leader: &str, mut start: u64, end: u64) -> Result<Vec<LeaderFill>, BackfillError> {
+        let mut fills = Vec::new();
+        let mut seen = HashSet::new();
+        loop {
+            let page = self.info.user_fills_by_time(leader, start, end).await?;
+            let full = page.len() >= PAGE_LIMIT;
+            let last_time = page.last().map(|f| f.time);
+            for fill in page {
+                // pages overlap on the millisecond they are split at
+                if seen.insert((fill.hash.clone(), fill.tid)) {
+                    fills.push(leader_fills::to_leader_fill(leader, &fill)?);
+                }
+            }
+
+            match last_time {
+                Some(last) if full => {
+                    // a full page within one millisecond cannot be split further
+                    start = if last > start { last } else { start + 1 };
+                }
+                _ => return Ok(fills),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::{Json, Router, routing::post};
+    use serde_json::json;
+
+    fn fill(time: u64, tid: u64) -> serde_json::Value {
+        json!({
+            "coin": "BTC", "px": "50000", "sz": "0.1", "side": "B", "time": time,
+            "hash": format!("0x{tid}"), "oid": tid, "tid": tid, "startPosition": "0",
+            "closedPnl": "0", "dir": "Open Long", "crossed": true, "fee": "0.5", "feeToken": "USDC",
+        })
+    }
+
+    #[tokio::test]
+    async fn test_pages_through_fills_by_time() {
+        // a stand-in info endpoint holding PAGE_LIMIT + 10 fills, 1ms apart
+        let app = Router::new().route(
+            "/info",
+            post(|Json(request): Json<serde_json::Value>| async move {
+                assert_eq!(request["type"], "userFillsByTime");
+                assert_eq!(request["user"], "0xleader");
+                let start = request["startTime"].as_u64().unwrap();
+                let end = request["endTime"].as_u64().unwrap();
+                let fills: Vec<_> = (1..=PAGE_LIMIT as u64 + 10)
+                    .map(|i| (1_000 + i, i))
+                    .filter(|(time, _)| *time >= start && *time <= end)
+                    .take(PAGE_LIMIT)
+                    .map(|(time, tid)| fill(time, tid))
+                    .collect();
+                Json(serde_json::Value::Array(fills))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let backfill = Backfill::new(InfoApi::new(&format!("http://{addr}")), Duration::from_secs(3600));
+        let fills = backfill.fetch("0xleader", 0, 10_000).await.unwrap();
+        assert_eq!(fills.len(), PAGE_LIMIT + 10);
+        assert_eq!(fills.iter().map(|f| f.tid).collect::<HashSet<_>>().len(), PAGE_LIMIT + 10);
+        assert_eq!(fills[0].trader_address, "0xleader");
+        assert!(fills.windows(2).all(|w| w[0].filled_at <= w[1].filled_at));
+
+        assert_eq!(backfill.fetch("0xleader", 5_000, 10_000).await.unwrap().len(), 0);
+    }
+}
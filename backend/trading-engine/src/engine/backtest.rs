@@ -0,0 +1,492 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::time::Duration;
+use log::info;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::broadcast;
+
+use crate::channel::WsFillChannel;
+use crate::config::GrouperConfig;
+use crate::engine::dedup::SeenStore;
+use crate::engine::execution::ExecutionStyle;
+use crate::engine::executor::{self, ExecutorContext, ExecutorError, FollowersCache};
+use crate::engine::grouper::{self, FullOrder};
+use crate::engine::paper::CopyMode;
+use crate::engine::parser::{parse_fee, parse_price, parse_size, ParseError};
+use crate::engine::positions::LeaderAction;
+use crate::engine::sizing::SizingMode;
+use crate::engine::trade_log::TradeLog;
+use crate::exchange::replay::ReplayExchange;
+use crate::exchange::{ExchangeError, OrderOutcome};
+use crate::hyperliquid::ws::WsFill;
+
+/// How long the grouper may take to emit every replayed order.
+const GROUPING_TIMEOUT: Duration = Duration::from_secs(30);
+/// Address the simulated follower trades under.
+const BACKTEST_FOLLOWER: &str = "backtest";
+
+#[derive(Error, Debug)]
+pub enum BacktestError {
+    #[error("Failed to read fills: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to parse JSON fills: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("Failed to parse CSV fills: {0}")]
+    Csv(#[from] csv::Error),
+    #[error("Unsupported fills file {0}: expected .json or .csv")]
+    UnsupportedFormat(String),
+    #[error("Invalid fill: {0}")]
+    Parse(#[from] ParseError),
+    #[error("Invalid backtest config: {0}")]
+    InvalidConfig(String),
+    #[error("No leader fills in the requested range")]
+    NoFills,
+    #[error("Grouper emitted {emitted} of {expected} orders before timing out")]
+    GroupingTimeout { emitted: usize, expected: usize },
+    #[error("Exchange error: {0}")]
+    Exchange(#[from] ExchangeError),
+    #[error("Executor error: {0}")]
+    Executor(#[from] ExecutorError),
+}
+
+/// The copy config under test.
+#[derive(Debug, Clone, Deserialize)]
+pub struct BacktestConfig {
+    pub ratio: Decimal,
+    pub max_risk_per_trade: Option<Decimal>,
+    #[serde(default)]
+    pub sizing_mode: SizingMode,
+    pub fixed_notional: Option<Decimal>,
+    /// Follower account value at the start, in USD
+    pub initial_equity: Decimal,
+}
+
+/// One simulated follower fill.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct BacktestTrade {
+    pub time: u64,
+    pub coin: String,
+    pub dir: String,
+    pub is_buy: bool,
+    pub size: Decimal,
+    pub price: Decimal,
+    pub fee: Decimal,
+    pub realized_pnl: Decimal,
+}
+
+/// A leader order the follower did not copy, and why.
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedOrder {
+    pub time: u64,
+    pub coin: String,
+    pub dir: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct EquityPoint {
+    pub time: u64,
+    pub equity: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct BacktestReport {
+    pub leader: String,
+    pub from: u64,
+    pub to: u64,
+    pub leader_orders: usize,
+    pub initial_equity: Decimal,
+    pub final_equity: Decimal,
+    pub total_pnl: Decimal,
+    pub total_fees: Decimal,
+    /// Largest peak-to-trough fall of the equity curve, as a fraction of the peak
+    pub max_drawdown: Decimal,
+    pub equity_curve: Vec<EquityPoint>,
+    pub trades: Vec<BacktestTrade>,
+    pub skipped: Vec<SkippedOrder>,
+}
+
+/// Load a `userFills` export: the API's JSON array, or a CSV with the same
+/// (camelCase) field names as headers.
+pub fn load_fills(path: &Path) -> Result<Vec<WsFill>, BacktestError> {
+    match path.extension().and_then(|e| e.to_str()) {
+        Some("json") => Ok(serde_json::from_slice(&std::fs::read(path)?)?),
+        Some("csv") => {
+            let mut reader = csv::Reader::from_path(path)?;
+            Ok(reader.deserialize().collect::<Result<_, _>>()?)
+        }
+        _ => Err(BacktestError::UnsupportedFormat(path.display().to_string())),
+    }
+}
+
+/// Replay `leader`'s fills between `from` and `to` (ms, inclusive) through the
+/// grouper and the executor's copy logic, as a paper follower on `config`.
+pub async fn run(
+    leader: &str,
+    mut fills: Vec<WsFill>,
+    from: Option<u64>,
+    to: Option<u64>,
+    config: &BacktestConfig,
+) -> Result<BacktestReport, BacktestError> {
+    let follower = backtest_follower(config)?;
+
+    fills.retain(|f| from.is_none_or(|from| f.time >= from) && to.is_none_or(|to| f.time <= to));
+    fills.sort_by_key(|f| f.time);
+    let (Some(first), Some(last)) = (fills.first(), fills.last()) else {
+        return Err(BacktestError::NoFills);
+    };
+    let (from, to) = (from.unwrap_or(first.time), to.unwrap_or(last.time));
+
+    let fee_rates = fee_rates(&fills)?;
+    let exchange = Arc::new(ReplayExchange::from_fills(&fills)?);
+    let ctx = ExecutorContext::new(exchange, TradeLog::disabled(), Arc::new(SeenStore::in_memory())).await?;
+    let orders = group_orders(leader, fills).await?;
+    info!("Backtesting {} leader orders of {}", orders.len(), leader);
+
+    let mut ledger = Ledger::new(config.initial_equity);
+    let mut trades = Vec::new();
+    let mut skipped = Vec::new();
+    for order in &orders {
+        // every leg of an order trades the same side
+        let is_buy = match LeaderAction::from_dir(&order.dir) {
+            Some(LeaderAction::Open { is_buy } | LeaderAction::Close { is_buy } | LeaderAction::Flip { is_buy }) => is_buy,
+            None => false,
+        };
+        match executor::handle_follower_order(&ctx, order, &follower).await {
+            Ok(outcomes) => {
+                for outcome in outcomes {
+                    let OrderOutcome::Filled { total_sz, avg_px, .. } = outcome else {
+                        continue;
+                    };
+                    let fee = total_sz * avg_px * fee_rates.get(&order.oid).copied().unwrap_or_default();
+                    let realized_pnl = ledger.apply_fill(&order.coin, is_buy, total_sz, avg_px, fee);
+                    trades.push(BacktestTrade {
+                        time: order.timestamp,
+                        coin: order.coin.clone(),
+                        dir: order.dir.clone(),
+                        is_buy,
+                        size: total_sz,
+                        price: avg_px,
+                        fee,
+                        realized_pnl,
+                    });
+                }
+            }
+            Err(e) => skipped.push(SkippedOrder {
+                time: order.timestamp,
+                coin: order.coin.clone(),
+                dir: order.dir.clone(),
+                reason: e.to_string(),
+            }),
+        }
+        ledger.mark(&order.coin, order.avg_px, order.timestamp);
+    }
+
+    let final_equity = ledger.equity();
+    Ok(BacktestReport {
+        leader: leader.to_string(),
+        from,
+        to,
+        leader_orders: orders.len(),
+        initial_equity: config.initial_equity,
+        final_equity,
+        total_pnl: final_equity - config.initial_equity,
+        total_fees: ledger.fees,
+        max_drawdown: max_drawdown(&ledger.curve),
+        equity_curve: ledger.curve,
+        trades,
+        skipped,
+    })
+}
+
+fn backtest_follower(config: &BacktestConfig) -> Result<FollowersCache, BacktestError> {
+    if config.initial_equity <= Decimal::ZERO {
+        return Err(BacktestError::InvalidConfig("initial_equity must be positive".to_string()));
+    }
+    match config.sizing_mode {
+        // would need the leader's historical account value
+        SizingMode::Equity => {
+            return Err(BacktestError::InvalidConfig("the equity sizing mode cannot be backtested".to_string()));
+        }
+        SizingMode::FixedNotional if config.fixed_notional.is_none_or(|n| n <= Decimal::ZERO) => {
+            return Err(BacktestError::InvalidConfig(
+                "fixed_notional must be positive for the fixed_notional sizing mode".to_string(),
+            ));
+        }
+        _ => {}
+    }
+
+    Ok(FollowersCache {
+        address: BACKTEST_FOLLOWER.to_string(),
+        agent_key_id: None,
+        ratio: config.ratio,
+        max_risk: config.max_risk_per_trade,
+        sizing_mode: config.sizing_mode,
+        fixed_notional: config.fixed_notional,
+        // at the leader's price: there is no historical book to cross
+        execution: ExecutionStyle::Gtc { timeout: Duration::ZERO },
+        mode: CopyMode::Paper,
+    })
+}
+
+/// Fee the leader paid per order, as a fraction of the order's notional.
+fn fee_rates(fills: &[WsFill]) -> Result<HashMap<u64, Decimal>, BacktestError> {
+    let mut totals: HashMap<u64, (Decimal, Decimal)> = HashMap::new();
+    for fill in fills {
+        let notional = parse_price(&fill.px)? * parse_size(&fill.sz)?;
+        let fee = parse_fee(&fill.fee)?;
+        let total = totals.entry(fill.oid).or_default();
+        total.0 += fee;
+        total.1 += notional;
+    }
+    Ok(totals
+        .into_iter()
+        .filter(|(_, (_, notional))| !notional.is_zero())
+        .map(|(oid, (fee, notional))| (oid, fee / notional))
+        .collect())
+}
+
+/// Run the fills through the live grouper and collect one order per leader oid,
+/// in the order the leader placed them.
+async fn group_orders(leader: &str, fills: Vec<WsFill>) -> Result<Vec<FullOrder>, BacktestError> {
+    let expected = fills.iter().map(|f| f.oid).collect::<HashSet<_>>().len();
+    let (fill_tx, fill_rx) = broadcast::channel(fills.len());
+    let (order_tx, mut order_rx) = broadcast::channel(expected);
+    let grouper = tokio::spawn(grouper::start(
+        fill_rx,
+        order_tx,
+        Arc::new(SeenStore::in_memory()),
+        Arc::default(),
+        GrouperConfig::default(),
+    ));
+
+    for fill in fills {
+        // the receiver is subscribed and the channel holds every fill
+        let _ = fill_tx.send(WsFillChannel { fill, user: leader.to_string() });
+    }
+
+    let mut orders = Vec::with_capacity(expected);
+    let collected = tokio::time::timeout(GROUPING_TIMEOUT, async {
+        while orders.len() < expected {
+            match order_rx.recv().await {
+                Ok(order) => orders.push(order),
+                Err(_) => break,
+            }
+        }
+    })
+    .await;
+    grouper.abort();
+    if collected.is_err() || orders.len() < expected {
+        return Err(BacktestError::GroupingTimeout { emitted: orders.len(), expected });
+    }
+
+    orders.sort_by_key(|o| (o.timestamp, o.oid));
+    Ok(orders)
+}
+
+#[derive(Debug, Default)]
+struct OpenPosition {
+    /// Signed size, positive long
+    szi: Decimal,
+    entry_px: Decimal,
+}
+
+/// The simulated follower account: realized PnL, fees and open positions marked
+/// at the latest leader price of each coin.
+struct Ledger {
+    initial_equity: Decimal,
+    realized: Decimal,
+    fees: Decimal,
+    positions: HashMap<String, OpenPosition>,
+    marks: HashMap<String, Decimal>,
+    curve: Vec<EquityPoint>,
+}
+
+impl Ledger {
+    fn new(initial_equity: Decimal) -> Self {
+        Self {
+            initial_equity,
+            realized: Decimal::ZERO,
+            fees: Decimal::ZERO,
+            positions: HashMap::new(),
+            marks: HashMap::new(),
+            curve: Vec::new(),
+        }
+    }
+
+    /// Apply a fill and return the PnL it realized, before fees.
+    fn apply_fill(&mut self, coin: &str, is_buy: bool, sz: Decimal, px: Decimal, fee: Decimal) -> Decimal {
+        let signed = if is_buy { sz } else { -sz };
+        let position = self.positions.entry(coin.to_string()).or_default();
+
+        let mut realized = Decimal::ZERO;
+        if position.szi.is_zero() || position.szi.is_sign_positive() == is_buy {
+            // opening or adding: average the entry
+            let total = position.szi + signed;
+            position.entry_px = (position.entry_px * position.szi.abs() + px * sz) / total.abs();
+            position.szi = total;
+        } else {
+            let closed = sz.min(position.szi.abs());
+            let direction = if position.szi.is_sign_positive() { dec!(1) } else { dec!(-1) };
+            realized = (px - position.entry_px) * closed * direction;
+            position.szi += signed;
+            if position.szi.is_zero() {
+                position.entry_px = Decimal::ZERO;
+            } else if position.szi.is_sign_positive() != (direction > Decimal::ZERO) {
+                // flipped through zero: the remainder opened at this price
+                position.entry_px = px;
+            }
+        }
+
+        self.realized += realized;
+        self.fees += fee;
+        realized
+    }
+
+    fn mark(&mut self, coin: &str, px: Decimal, time: u64) {
+        self.marks.insert(coin.to_string(), px);
+        let equity = self.equity();
+        self.curve.push(EquityPoint { time, equity });
+    }
+
+    fn equity(&self) -> Decimal {
+        let unrealized: Decimal = self
+            .positions
+            .iter()
+            .map(|(coin, p)| {
+                let mark = self.marks.get(coin).copied().unwrap_or(p.entry_px);
+                (mark - p.entry_px) * p.szi
+            })
+            .sum();
+        self.initial_equity + self.realized - self.fees + unrealized
+    }
+}
+
+fn max_drawdown(curve: &[EquityPoint]) -> Decimal {
+    let mut peak = Decimal::ZERO;
+    let mut worst = Decimal::ZERO;
+    for point in curve {
+        peak = peak.max(point.equity);
+        if peak > Decimal::ZERO {
+            worst = worst.max((peak - point.equity) / peak);
+        }
+    }
+    worst
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(oid: u64, time: u64, dir: &str, side: &str, px: &str, sz: &str, start_position: &str) -> WsFill {
+        WsFill {
+            coin: "ETH".to_string(),
+            px: px.to_string(),
+            sz: sz.to_string(),
+            side: side.to_string(),
+            time,
+            hash: format!("0xhash{oid}"),
+            oid,
+            tid: time,
+            start_position: Some(start_position.to_string()),
+            closed_pnl: None,
+            dir: Some(dir.to_string()),
+            crossed: true,
+            fee: "0".to_string(),
+            fee_token: "USDC".to_string(),
+        }
+    }
+
+    fn config() -> BacktestConfig {
+        BacktestConfig {
+            ratio: dec!(0.1),
+            max_risk_per_trade: None,
+            sizing_mode: SizingMode::Ratio,
+            fixed_notional: None,
+            initial_equity: dec!(10000),
+        }
+    }
+
+    #[test]
+    fn test_ledger() {
+        let mut ledger = Ledger::new(dec!(1000));
+        ledger.apply_fill("ETH", true, dec!(1), dec!(2000), dec!(1));
+        ledger.apply_fill("ETH", true, dec!(1), dec!(2200), dec!(1));
+        ledger.mark("ETH", dec!(2300), 1);
+        // entry 2100, 2 ETH up 200 each, less fees
+        assert_eq!(ledger.equity(), dec!(1398));
+
+        // long 2 -> short 1 at 2000
+        assert_eq!(ledger.apply_fill("ETH", false, dec!(3), dec!(2000), dec!(0)), dec!(-200));
+        ledger.mark("ETH", dec!(1900), 2);
+        assert_eq!(ledger.equity(), dec!(1000) - dec!(200) - dec!(2) + dec!(100));
+
+        assert_eq!(max_drawdown(&ledger.curve), (dec!(1398) - dec!(898)) / dec!(1398));
+    }
+
+    #[test]
+    fn test_load_fills() {
+        let dir = std::env::temp_dir().join(format!("backtest-fills-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+
+        let json = dir.join("fills.json");
+        std::fs::write(
+            &json,
+            r#"[{"coin":"ETH","px":"2000.5","sz":"0.5","side":"B","time":1,"hash":"0x1","oid":7,
+                 "startPosition":"0","closedPnl":"0","dir":"Open Long","crossed":true,"fee":"0.45",
+                 "feeToken":"USDC","tid":99}]"#,
+        )
+        .unwrap();
+        let fills = load_fills(&json).unwrap();
+        assert_eq!((fills[0].oid, fills[0].px.as_str()), (7, "2000.5"));
+
+        let csv = dir.join("fills.csv");
+        std::fs::write(
+            &csv,
+            "coin,px,sz,side,time,hash,oid,startPosition,closedPnl,dir,crossed,fee,feeToken\n\
+             ETH,2000.5,0.5,B,1,0x1,7,0,,Open Long,true,0.45,USDC\n",
+        )
+        .unwrap();
+        let fills = load_fills(&csv).unwrap();
+        assert_eq!((fills[0].oid, fills[0].closed_pnl.clone()), (7, None));
+
+        assert!(matches!(load_fills(&dir.join("fills.txt")), Err(BacktestError::UnsupportedFormat(_))));
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_run() {
+        let fills = vec![
+            // two fills of one order: long 2 ETH at 2000
+            fill(1, 1_000, "Open Long", "B", "1999", "1", "0"),
+            fill(1, 1_001, "Open Long", "B", "2001", "1", "1"),
+            // closes half at 2100
+            fill(2, 2_000, "Close Long", "A", "2100", "1", "2"),
+            // outside the range
+            fill(3, 9_000, "Close Long", "A", "1000", "1", "1"),
+        ];
+        let mut with_fees = fills.clone();
+        with_fees[2].fee = "0.945".to_string();
+
+        let report = run("0xleader", with_fees, None, Some(5_000), &config()).await.unwrap();
+        assert_eq!((report.from, report.to, report.leader_orders), (1_000, 5_000, 2));
+        assert!(report.skipped.is_empty(), "{:?}", report.skipped);
+
+        // the follower copies 0.2 ETH and closes half of it
+        let sizes: Vec<_> = report.trades.iter().map(|t| (t.is_buy, t.size, t.price)).collect();
+        assert_eq!(sizes, vec![(true, dec!(0.2), dec!(2000)), (false, dec!(0.1), dec!(2100))]);
+        // fee at the leader's rate of 0.045% of 210 USD
+        assert_eq!(report.total_fees, dec!(0.0945));
+        assert_eq!(report.trades[1].realized_pnl, dec!(10));
+        // 0.1 ETH still open, marked at 2100
+        assert_eq!(report.final_equity, dec!(10000) + dec!(10) + dec!(10) - dec!(0.0945));
+        assert_eq!(report.max_drawdown, dec!(0));
+
+        let equity = BacktestConfig { sizing_mode: SizingMode::Equity, ..config() };
+        assert!(matches!(run("0xleader", fills, None, None, &equity).await, Err(BacktestError::InvalidConfig(_))));
+    }
+}
@@ -0,0 +1,180 @@
+use std::collections::HashSet;
+use std::sync::Mutex;
+use std::time::Duration;
+use sqlx::PgPool;
+
+use crate::engine::grouper::FullOrder;
+use crate::hyperliquid::ws::WsFill;
+
+enum Backend {
+    Postgres(PgPool),
+    /// Backtests and tests: remembers for the life of the process only
+    Memory(Mutex<HashSet<String>>),
+}
+
+/// Leader fills and orders already processed, so a replayed fill (reconnect
+/// snapshot, restart) is never grouped or copied twice.
+pub struct SeenStore {
+    backend: Backend,
+}
+
+impl SeenStore {
+    pub fn new(pool: PgPool) -> Self {
+        Self { backend: Backend::Postgres(pool) }
+    }
+
+    pub fn in_memory() -> Self {
+        Self { backend: Backend::Memory(Mutex::new(HashSet::new())) }
+    }
+
+    /// Mark a leader fill as seen; `false` if it already was. Fills without a trade
+    /// id (older exports) cannot be told apart and always count as new.
+    pub async fn claim_fill(&self, leader: &str, fill: &WsFill) -> Result<bool, sqlx::Error> {
+        if fill.tid == 0 {
+            return Ok(true);
+        }
+        match &self.backend {
+            Backend::Postgres(pool) => {
+                let inserted = sqlx::query(
+                    "INSERT INTO seen_leader_fills (hash, tid, trader_address) VALUES ($1, $2, $3)
+                     ON CONFLICT DO NOTHING",
+                )
+                .bind(&fill.hash)
+                .bind(fill.tid as i64)
+                .bind(leader)
+                .execute(pool)
+                .await?;
+                Ok(inserted.rows_affected() == 1)
+            }
+            Backend::Memory(seen) => Ok(Self::insert(seen, format!("fill:{}:{}:{}", leader, fill.hash, fill.tid))),
+        }
+    }
+
+    /// Forget a fill claimed by `claim_fill` that was never passed on.
+    pub async fn release_fill(&self, leader: &str, fill: &WsFill) -> Result<(), sqlx::Error> {
+        if fill.tid == 0 {
+            return Ok(());
+        }
+        match &self.backend {
+            Backend::Postgres(pool) => {
+                sqlx::query("DELETE FROM seen_leader_fills WHERE hash = $1 AND tid = $2 AND trader_address = $3")
+                    .bind(&fill.hash)
+                    .bind(fill.tid as i64)
+                    .bind(leader)
+                    .execute(pool)
+                    .await?;
+            }
+            Backend::Memory(seen) => {
+                seen.lock().unwrap_or_else(|e| e.into_inner()).remove(&format!("fill:{}:{}:{}", leader, fill.hash, fill.tid));
+            }
+        }
+        Ok(())
+    }
+
+    /// Mark a grouped leader order as seen; `false` if it already was.
+    ///
+    /// Keyed on the time of its first fill as well as its oid: later partial fills of
+    /// a resting leader order group into another `FullOrder` with the same oid.
+    pub async fn claim_order(&self, order: &FullOrder) -> Result<bool, sqlx::Error> {
+        match &self.backend {
+            Backend::Postgres(pool) => {
+                let inserted = sqlx::query(
+                    "INSERT INTO seen_leader_orders (trader_address, oid, first_fill_time) VALUES ($1, $2, $3)
+                     ON CONFLICT DO NOTHING",
+                )
+                .bind(&order.user)
+                .bind(order.oid as i64)
+                .bind(order.timestamp as i64)
+                .execute(pool)
+                .await?;
+                Ok(inserted.rows_affected() == 1)
+            }
+            Backend::Memory(seen) => Ok(Self::insert(
+                seen,
+                format!("order:{}:{}:{}", order.user, order.oid, order.timestamp),
+            )),
+        }
+    }
+
+    fn insert(seen: &Mutex<HashSet<String>>, key: String) -> bool {
+        seen.lock().unwrap_or_else(|e| e.into_inner()).insert(key)
+    }
+}
+
+/// Forget fills and orders seen more than `retention` ago; returns how many rows went.
+pub async fn prune(pool: &PgPool, retention: Duration) -> Result<u64, sqlx::Error> {
+    let secs = retention.as_secs_f64();
+    let fills = sqlx::query("DELETE FROM seen_leader_fills WHERE seen_at < NOW() - make_interval(secs => $1)")
+        .bind(secs)
+        .execute(pool)
+        .await?;
+    let orders = sqlx::query("DELETE FROM seen_leader_orders WHERE seen_at < NOW() - make_interval(secs => $1)")
+        .bind(secs)
+        .execute(pool)
+        .await?;
+    Ok(fills.rows_affected() + orders.rows_affected())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn fill(hash: &str, tid: u64) -> WsFill {
+        WsFill {
+            coin: "ETH".to_string(),
+            px: "3000".to_string(),
+            sz: "1".to_string(),
+            side: "B".to_string(),
+            time: 1,
+            hash: hash.to_string(),
+            oid: 7,
+            tid,
+            start_position: None,
+            closed_pnl: None,
+            dir: Some("Open Long".to_string()),
+            crossed: true,
+            fee: "0".to_string(),
+            fee_token: "USDC".to_string(),
+        }
+    }
+
+    fn order(oid: u64, timestamp: u64) -> FullOrder {
+        FullOrder {
+            user: "0xleader".to_string(),
+            coin: "ETH".to_string(),
+            dir: "Open Long".to_string(),
+            total_sz: dec!(1),
+            avg_px: dec!(3000),
+            timestamp,
+            hash: "0xhash".to_string(),
+            oid,
+            start_position: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn test_claims() {
+        let seen = SeenStore::in_memory();
+
+        // fills of one transaction share a hash, not a tid
+        assert!(seen.claim_fill("0xleader", &fill("0xa", 1)).await.unwrap());
+        assert!(seen.claim_fill("0xleader", &fill("0xa", 2)).await.unwrap());
+        assert!(!seen.claim_fill("0xleader", &fill("0xa", 1)).await.unwrap());
+        assert!(seen.claim_fill("0xleader", &fill("0xa", 0)).await.unwrap());
+        assert!(seen.claim_fill("0xleader", &fill("0xa", 0)).await.unwrap());
+        // both sides of a trade between two leaders
+        assert!(seen.claim_fill("0xother", &fill("0xa", 1)).await.unwrap());
+        assert!(!seen.claim_fill("0xother", &fill("0xa", 1)).await.unwrap());
+        seen.release_fill("0xother", &fill("0xa", 1)).await.unwrap();
+        assert!(!seen.claim_fill("0xleader", &fill("0xa", 1)).await.unwrap());
+
+        seen.release_fill("0xleader", &fill("0xa", 2)).await.unwrap();
+        assert!(seen.claim_fill("0xleader", &fill("0xa", 2)).await.unwrap());
+
+        // a later partial fill of the same resting order is a new order
+        assert!(seen.claim_order(&order(7, 1_000)).await.unwrap());
+        assert!(!seen.claim_order(&order(7, 1_000)).await.unwrap());
+        assert!(seen.claim_order(&order(7, 60_000)).await.unwrap());
+    }
+}
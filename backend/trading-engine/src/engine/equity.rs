@@ -0,0 +1,55 @@
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use thiserror::Error;
+
+use crate::hyperliquid::info::{InfoApi, InfoError};
+use crate::models::EquitySnapshot;
+
+#[derive(Error, Debug)]
+pub enum EquityError {
+    #[error(transparent)]
+    Info(#[from] InfoError),
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Record the leader's current account value.
+pub async fn snapshot(info: &InfoApi, pool: &PgPool, leader: &str) -> Result<Decimal, EquityError> {
+    let value = info.account_value(leader).await?;
+    sqlx::query("INSERT INTO leader_equity (trader_address, account_value) VALUES ($1, $2) ON CONFLICT DO NOTHING")
+        .bind(leader)
+        .bind(value)
+        .execute(pool)
+        .await?;
+    Ok(value)
+}
+
+/// Record the account value of every active leader; returns how many were recorded.
+pub async fn snapshot_all(info: &InfoApi, pool: &PgPool) -> Result<usize, EquityError> {
+    let leaders = sqlx::query_scalar::<_, String>("SELECT address FROM traders WHERE is_active = true")
+        .fetch_all(pool)
+        .await?;
+    let mut recorded = 0;
+    for leader in &leaders {
+        match snapshot(info, pool, leader).await {
+            Ok(_) => recorded += 1,
+            Err(e) => log::error!("Failed to record equity of {}: {}", leader, e),
+        }
+    }
+    Ok(recorded)
+}
+
+/// The leader's snapshots since `since`, oldest first, led by the last one before it.
+pub async fn snapshots_since(pool: &PgPool, leader: &str, since: NaiveDateTime) -> Result<Vec<EquitySnapshot>, sqlx::Error> {
+    sqlx::query_as::<_, EquitySnapshot>(
+        "SELECT account_value, recorded_at FROM leader_equity
+         WHERE trader_address = $1 AND recorded_at >= COALESCE(
+             (SELECT MAX(recorded_at) FROM leader_equity WHERE trader_address = $1 AND recorded_at <= $2), $2)
+         ORDER BY recorded_at",
+    )
+    .bind(leader)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
@@ -0,0 +1,105 @@
+use std::time::Duration;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::exchange::TimeInForce;
+
+/// Band used by [`ExecutionStyle::Aggressive`], the same the SDK uses for market orders.
+const AGGRESSIVE_SLIPPAGE_BPS: u32 = 500;
+
+/// How a copy config places follower orders (`copy_configs.execution_style`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExecutionStyle {
+    /// Immediate-or-cancel within `max_slippage_bps` of the current mid
+    Ioc { max_slippage_bps: u32 },
+    /// Immediate-or-cancel with a wide band, filling like a market order
+    Aggressive,
+    /// Rest at the leader's price and cancel whatever is left after `timeout`
+    Gtc { timeout: Duration },
+}
+
+/// Name of an [`ExecutionStyle`] as stored and accepted by the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ExecutionStyleKind {
+    Ioc,
+    Aggressive,
+    Gtc,
+}
+
+impl ExecutionStyleKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ExecutionStyleKind::Ioc => "ioc",
+            ExecutionStyleKind::Aggressive => "aggressive",
+            ExecutionStyleKind::Gtc => "gtc",
+        }
+    }
+
+    pub fn parse(style: &str) -> Option<Self> {
+        match style {
+            "ioc" => Some(ExecutionStyleKind::Ioc),
+            "aggressive" => Some(ExecutionStyleKind::Aggressive),
+            "gtc" => Some(ExecutionStyleKind::Gtc),
+            _ => None,
+        }
+    }
+}
+
+impl ExecutionStyle {
+    pub fn new(kind: ExecutionStyleKind, max_slippage_bps: u32, gtc_timeout: Duration) -> Self {
+        match kind {
+            ExecutionStyleKind::Ioc => ExecutionStyle::Ioc { max_slippage_bps },
+            ExecutionStyleKind::Aggressive => ExecutionStyle::Aggressive,
+            ExecutionStyleKind::Gtc => ExecutionStyle::Gtc { timeout: gtc_timeout },
+        }
+    }
+
+    pub fn tif(&self) -> TimeInForce {
+        match self {
+            ExecutionStyle::Ioc { .. } | ExecutionStyle::Aggressive => TimeInForce::Ioc,
+            ExecutionStyle::Gtc { .. } => TimeInForce::Gtc,
+        }
+    }
+
+    /// Whether the limit is derived from the current mid rather than the leader's price.
+    pub fn needs_mid(&self) -> bool {
+        !matches!(self, ExecutionStyle::Gtc { .. })
+    }
+
+    /// Limit price for a follower order around `reference` (the mid, or the leader's
+    /// price for GTC), before rounding to the asset's tick.
+    pub fn limit_price(&self, is_buy: bool, reference: Decimal) -> Decimal {
+        let bps = match self {
+            ExecutionStyle::Ioc { max_slippage_bps } => *max_slippage_bps,
+            ExecutionStyle::Aggressive => AGGRESSIVE_SLIPPAGE_BPS,
+            ExecutionStyle::Gtc { .. } => 0,
+        };
+
+        let band = Decimal::from(bps) / dec!(10000);
+        if is_buy {
+            reference * (dec!(1) + band)
+        } else {
+            reference * (dec!(1) - band)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_limit_price() {
+        let ioc = ExecutionStyle::Ioc { max_slippage_bps: 50 };
+        // 50 bps around a 2000 mid
+        assert_eq!(ioc.limit_price(true, dec!(2000)), dec!(2010));
+        assert_eq!(ioc.limit_price(false, dec!(2000)), dec!(1990));
+
+        assert_eq!(ExecutionStyle::Aggressive.limit_price(true, dec!(100)), dec!(105));
+
+        let gtc = ExecutionStyle::Gtc { timeout: Duration::from_secs(30) };
+        assert_eq!(gtc.limit_price(true, dec!(50123.4)), dec!(50123.4));
+    }
+}
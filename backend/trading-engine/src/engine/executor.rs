@@ -1,44 +1,76 @@
 use log::{info, error};
-use sqlx::Row;
-use std::{collections::HashMap, str::FromStr};
-use hyperliquid_rust_sdk::{BaseUrl, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient};
-use rust_decimal::{Decimal, prelude::ToPrimitive};
+use std::collections::HashMap;
+use std::hash::{DefaultHasher, Hash, Hasher};
+use rust_decimal::Decimal;
 use rust_decimal_macros::dec;
-use sqlx::{PgPool, postgres::PgRow};
-use tokio::sync::{broadcast, RwLock};
+use sqlx::{FromRow, PgPool};
+use tokio::sync::{broadcast, mpsc, mpsc::error::TrySendError, RwLock};
+use tokio::task::JoinSet;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+use futures_util::future::join_all;
 use thiserror::Error;
 
 
+use crate::config::ExecutorConfig;
+use crate::engine::dedup::SeenStore;
+use crate::engine::execution::{ExecutionStyle, ExecutionStyleKind};
 use crate::engine::grouper::FullOrder;
+use crate::engine::metadata::{AssetMetadata, MetadataError, METADATA_REFRESH, MIN_NOTIONAL};
+use crate::engine::paper::{self, CopyMode};
+use crate::engine::positions::{self, LeaderAction, PositionBook, PositionError};
+use crate::engine::sizing::{EquityCache, SizingError, SizingMode};
+use crate::engine::trade_log::{RestingCopy, TradeLog, TradeStatus};
+use crate::exchange::{Exchange, ExchangeError, OrderOutcome, OrderRequest, OrderState, OrderStatus, Signer};
 
 #[derive(Error, Debug)]
 pub enum ExecutorError {
-    #[error("Invalid agent key: {0}")]
-    InvalidAgentKey(String),
-    #[error("Exchange client initialization failed: {0}")]
-    ClientInitialization(String),
-    #[error("Order placement failed: {0}")]
-    OrderPlacement(String),
+    #[error("Exchange error: {0}")]
+    Exchange(#[from] ExchangeError),
     #[error("Order size too small")]
     OrderSizeTooSmall,
-    #[error("No data in exchange response")]
-    NoDataInResponse,
-    #[error("Unexpected status from exchange: {0:?}")]
-    UnexpectedStatus(hyperliquid_rust_sdk::ExchangeDataStatus),
     #[error("Database error: {0}")]
     SqlxError(#[from] sqlx::Error),
-    #[error("Failed to convert decimal to f64")]
-    DecimalConversion,
+    #[error("Unsupported leader order direction: {0}")]
+    UnknownDirection(String),
+    #[error("Copy sizing failed: {0}")]
+    Sizing(#[from] SizingError),
+    #[error("Order skipped: {0}")]
+    Skipped(String),
+    #[error("Asset metadata unavailable: {0}")]
+    Metadata(#[from] MetadataError),
+    #[error("Position lookup failed: {0}")]
+    Position(#[from] PositionError),
+    #[error("Order value {notional} is below the exchange minimum of {min}")]
+    BelowMinNotional { notional: Decimal, min: Decimal },
+    #[error("Order value {notional} exceeds {max_leverage}x leverage on account value {equity}")]
+    ExceedsMaxLeverage { notional: Decimal, equity: Decimal, max_leverage: u32 },
 }
 
 
 #[derive(Debug, Clone)]
 pub struct FollowersCache {
     pub address: String,
-    pub signature: String,
+    /// Vault id of the agent key the follower's orders are signed with; paper
+    /// copies need none
+    pub agent_key_id: Option<i32>,
     pub ratio: Decimal,
     pub max_risk: Option<Decimal>,
+    pub sizing_mode: SizingMode,
+    /// USD per opening order in [`SizingMode::FixedNotional`]
+    pub fixed_notional: Option<Decimal>,
+    pub execution: ExecutionStyle,
+    pub mode: CopyMode,
+}
+
+impl FollowersCache {
+    pub fn signer(&self) -> Option<Signer<'_>> {
+        Some(Signer {
+            address: &self.address,
+            agent_key_id: self.agent_key_id?,
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -47,13 +79,64 @@ pub struct OrderTask {
     pub follower: FollowersCache,
 }
 
-const WORKER_COUNT: usize = 10;
-const CHANNEL_CAPACITY: usize = 1000;
-
 type SharedCache = Arc<RwLock<HashMap<String, Vec<FollowersCache>>>>;
 
-pub async fn start(mut rx: broadcast::Receiver<FullOrder>, pool: PgPool, agentkey: &str) -> Result<(), ExecutorError> {
+/// Everything an order worker needs to place and record follower orders.
+pub(crate) struct ExecutorContext {
+    exchange: Arc<dyn Exchange>,
+    trades: TradeLog,
+    /// Leader orders already dispatched
+    seen: Arc<SeenStore>,
+    positions: Arc<PositionBook>,
+    paper_positions: PositionBook,
+    equity: EquityCache,
+    metadata: AssetMetadata,
+    /// Timers cancelling resting GTC copies; their deadlines are also stored
+    cancels: std::sync::Mutex<JoinSet<()>>,
+}
+
+impl ExecutorContext {
+    pub(crate) async fn new(
+        exchange: Arc<dyn Exchange>,
+        trades: TradeLog,
+        seen: Arc<SeenStore>,
+    ) -> Result<Self, ExecutorError> {
+        Ok(Self {
+            positions: Arc::new(PositionBook::new(exchange.clone())),
+            paper_positions: PositionBook::paper(trades.clone()),
+            equity: EquityCache::new(exchange.clone()),
+            metadata: AssetMetadata::load(exchange.clone()).await?,
+            exchange,
+            trades,
+            seen,
+            cancels: std::sync::Mutex::new(JoinSet::new()),
+        })
+    }
+
+    /// Cancel what is left of `copy` at its deadline, in the background.
+    fn schedule_cancel(&self, copy: RestingCopy) {
+        let mut cancels = self.cancels.lock().unwrap_or_else(|e| e.into_inner());
+        while cancels.try_join_next().is_some() {}
+        cancels.spawn(expire_resting(self.exchange.clone(), self.trades.clone(), self.positions.clone(), copy));
+    }
+
+    fn positions_for(&self, follower: &FollowersCache) -> &PositionBook {
+        match follower.mode {
+            CopyMode::Live => &self.positions,
+            CopyMode::Paper => &self.paper_positions,
+        }
+    }
+}
+
+pub async fn start(
+    rx: broadcast::Receiver<FullOrder>,
+    pool: PgPool,
+    exchange: Arc<dyn Exchange>,
+    seen: Arc<SeenStore>,
+    config: ExecutorConfig,
+) -> Result<(), ExecutorError> {
     let cache: SharedCache = Arc::new(RwLock::new(HashMap::new()));
+    let ctx = Arc::new(ExecutorContext::new(exchange, TradeLog::new(pool.clone()), seen).await?);
 
     // Initial preload
     {
@@ -63,37 +146,91 @@ pub async fn start(mut rx: broadcast::Receiver<FullOrder>, pool: PgPool, agentke
 
     // Background refresher task
     let cache_clone = cache.clone();
+    let ctx_clone = ctx.clone();
     let pool_clone = pool.clone();
-    tokio::spawn(async move {
+    let follower_refresh = config.follower_refresh();
+    let follower_refresher = tokio::spawn(async move {
         loop {
-            tokio::time::sleep(tokio::time::Duration::from_secs(300)).await;
+            tokio::time::sleep(follower_refresh).await;
             let mut new_cache = HashMap::new();
             if let Err(e) = preload_followers(&pool_clone, &mut new_cache).await {
+                // keep serving the previous snapshot rather than an empty one
                 error!("Failed to refresh follower cache: {}", e);
+                continue;
             }
 
+            let active = new_cache.values().flatten().map(|f| f.address.clone()).collect();
+            ctx_clone.exchange.retain_accounts(&active).await;
+            ctx_clone.positions.clear().await;
+
             // Atomic replacement under write lock
             let mut write_lock = cache_clone.write().await;
             *write_lock = new_cache;
         }
     });
 
-    // Channel for distributing tasks to workers
-    let (tx, rx_orders) = broadcast::channel::<OrderTask>(CHANNEL_CAPACITY);
-
-    // Spawn worker pool
-    for worker_id in 0..WORKER_COUNT {
-        let rx_orders = rx_orders.resubscribe();
-        let agentkey = agentkey.to_string();
-        tokio::spawn(async move {
-            if let Err(e) = order_worker(worker_id, rx_orders, &agentkey).await {
-                error!("Worker {} failed: {}", worker_id, e);
+    // Universe refresher: new listings and changed size/leverage rules
+    let ctx_clone = ctx.clone();
+    let metadata_refresher = tokio::spawn(async move {
+        loop {
+            tokio::time::sleep(METADATA_REFRESH).await;
+            if let Err(e) = ctx_clone.metadata.refresh().await {
+                error!("Failed to refresh asset metadata: {}", e);
             }
-        });
-    }
+        }
+    });
+
+    let result = run(rx, cache, ctx.clone(), &config).await;
+    // a restarted executor brings its own
+    follower_refresher.abort();
+    metadata_refresher.abort();
+    // their deadlines are stored, the next start cancels those orders
+    ctx.cancels.lock().unwrap_or_else(|e| e.into_inner()).abort_all();
+    result
+}
+
+/// Fan leader orders out to the workers of the followers copying them, until the
+/// grouper stops; then let the workers drain their queues.
+async fn run(
+    mut rx: broadcast::Receiver<FullOrder>,
+    cache: SharedCache,
+    ctx: Arc<ExecutorContext>,
+    config: &ExecutorConfig,
+) -> Result<(), ExecutorError> {
+    // One queue per worker; a follower always lands on the same worker so their
+    // orders are placed exactly once and in the order the leader made them
+    let workers = Arc::new(std::sync::Mutex::new(Vec::with_capacity(config.workers)));
+    // set once draining takes too long: queued copies are saved instead of placed
+    let give_up = Arc::new(AtomicBool::new(false));
+    let spawn_worker = {
+        let (ctx, workers, give_up) = (ctx.clone(), workers.clone(), give_up.clone());
+        let queue_capacity = config.queue_capacity;
+        move |worker_id: usize| {
+            let (tx, rx_orders) = mpsc::channel::<OrderTask>(queue_capacity);
+            let (ctx, give_up) = (ctx.clone(), give_up.clone());
+            let worker = tokio::spawn(async move {
+                if let Err(e) = order_worker(worker_id, rx_orders, ctx, give_up).await {
+                    error!("Worker {} failed: {}", worker_id, e);
+                }
+            });
+            workers.lock().unwrap_or_else(|e| e.into_inner()).push(worker);
+            tx
+        }
+    };
+    let mut queue = OrderQueue::new(config.workers, Box::new(spawn_worker));
+    recover_pending(&ctx, &cache, &mut queue, config.recover_max_age()).await;
 
     // Main dispatcher loop
-    while let Ok(order) = rx.recv().await {
+    loop {
+        let order = match rx.recv().await {
+            Ok(order) => order,
+            Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                error!("Executor lagging behind grouper — {} leader orders were dropped", skipped);
+                continue;
+            }
+            Err(broadcast::error::RecvError::Closed) => break,
+        };
+
         let followers = {
             let read_lock = cache.read().await;
             match read_lock.get(&order.user) {
@@ -102,34 +239,174 @@ pub async fn start(mut rx: broadcast::Receiver<FullOrder>, pool: PgPool, agentke
             }
         };
 
+        match ctx.seen.claim_order(&order).await {
+            Ok(true) => {}
+            Ok(false) => {
+                info!("Leader order {} of {} was already dispatched", order.oid, order.user);
+                continue;
+            }
+            Err(e) => {
+                error!("Failed to check leader order {} of {} for duplicates, not copying it: {}", order.oid, order.user, e);
+                continue;
+            }
+        }
+
         for follower in followers {
             let task = OrderTask {
                 order: order.clone(),
                 follower,
             };
+            queue.dispatch(task).await;
+        }
+    }
+
+    drop(queue);
+    info!("Executor input closed, draining order workers");
+    // the queue, and with it every sender, is gone: no worker is added anymore
+    let mut workers = std::mem::take(&mut *workers.lock().unwrap_or_else(|e| e.into_inner()));
+    if tokio::time::timeout(config.drain_timeout(), join_all(workers.iter_mut())).await.is_err() {
+        log::warn!("Order workers still busy after {:?}, saving queued copies for the next start", config.drain_timeout());
+        give_up.store(true, Ordering::Relaxed);
+        join_all(workers).await;
+    }
+    Ok(())
+}
 
-            if tx.send(task).is_err() {
-                log::warn!("Order queue full — dropping task (backpressure)");
+/// Re-dispatch the copies saved at the last shutdown, unless their leader order
+/// is older than `max_age` or the follower no longer copies that leader, and
+/// restart the cancel timers of GTC copies still resting.
+async fn recover_pending(
+    ctx: &ExecutorContext,
+    cache: &SharedCache,
+    queue: &mut OrderQueue,
+    max_age: Duration,
+) {
+    match ctx.trades.resting_copies().await {
+        Ok(resting) => {
+            if !resting.is_empty() {
+                info!("Resuming the cancel timers of {} resting GTC copies", resting.len());
+            }
+            for copy in resting {
+                ctx.schedule_cancel(copy);
             }
         }
+        Err(e) => error!("Failed to load resting GTC copies: {}", e),
     }
-    Ok(())
+
+    let pending = match ctx.trades.take_pending().await {
+        Ok(pending) => pending,
+        Err(e) => {
+            error!("Failed to load copies saved at shutdown: {}", e);
+            return;
+        }
+    };
+    let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+
+    for (address, order) in pending {
+        if now_ms.saturating_sub(order.timestamp) > max_age.as_millis() as u64 {
+            log::warn!("Not copying leader order {} of {} for {}: saved at shutdown and now too old", order.oid, order.user, address);
+            continue;
+        }
+        let follower = {
+            let read_lock = cache.read().await;
+            read_lock.get(&order.user).and_then(|followers| followers.iter().find(|f| f.address == address).cloned())
+        };
+        let Some(follower) = follower else {
+            log::warn!("Not copying leader order {} of {} for {}: no longer copying that leader", order.oid, order.user, address);
+            continue;
+        };
+        info!("Re-dispatching leader order {} of {} for {}, saved at shutdown", order.oid, order.user, address);
+        queue.dispatch(OrderTask { order, follower }).await;
+    }
+}
+
+/// Starts the worker for a shard and returns the sender of its queue.
+type SpawnWorker = Box<dyn FnMut(usize) -> mpsc::Sender<OrderTask> + Send + Sync>;
+
+/// Per-follower sharded work queue in front of the order workers.
+struct OrderQueue {
+    shards: Vec<mpsc::Sender<OrderTask>>,
+    spawn_worker: SpawnWorker,
 }
 
-async fn order_worker(worker_id: usize, mut rx: broadcast::Receiver<OrderTask>, agentkey: &str) -> Result<(), ExecutorError> {
-    let wallet = agentkey.parse().unwrap();
-    let exchange_client = ExchangeClient::new(None, wallet, Some(BaseUrl::Testnet), None, None)
-        .await
-        .map_err(|e| ExecutorError::ClientInitialization(e.to_string()))?;
+impl OrderQueue {
+    fn new(workers: usize, mut spawn_worker: SpawnWorker) -> Self {
+        let shards = (0..workers).map(&mut spawn_worker).collect();
+        Self { shards, spawn_worker }
+    }
+
+    fn shard_for(&self, follower: &str) -> usize {
+        let mut hasher = DefaultHasher::new();
+        follower.hash(&mut hasher);
+        (hasher.finish() % self.shards.len() as u64) as usize
+    }
+
+    /// Hand a task to its follower's worker, waiting if that worker is backed up.
+    /// A worker that stopped is replaced, so one dead shard never stops the others.
+    async fn dispatch(&mut self, task: OrderTask) {
+        let shard = self.shard_for(&task.follower.address);
+        let Err(task) = self.send(shard, task).await else {
+            return;
+        };
+
+        error!("Worker {} is no longer running, starting a new one", shard);
+        self.shards[shard] = (self.spawn_worker)(shard);
+        if let Err(task) = self.send(shard, task).await {
+            error!(
+                "Worker {} stopped again, dropping leader order {} of {} for {}",
+                shard, task.order.oid, task.order.user, task.follower.address
+            );
+        }
+    }
 
+    /// Send to a shard; gives the task back if its worker is gone.
+    async fn send(&self, shard: usize, task: OrderTask) -> Result<(), OrderTask> {
+        let tx = &self.shards[shard];
+        let task = match tx.try_send(task) {
+            Ok(()) => return Ok(()),
+            Err(TrySendError::Full(task)) => {
+                log::warn!(
+                    "Worker {} queue full ({} tasks) — waiting to enqueue order for {}",
+                    shard, tx.max_capacity(), task.follower.address
+                );
+                task
+            }
+            Err(TrySendError::Closed(task)) => return Err(task),
+        };
+
+        tx.send(task).await.map_err(|e| e.0)
+    }
+}
+
+async fn order_worker(
+    worker_id: usize,
+    mut rx: mpsc::Receiver<OrderTask>,
+    ctx: Arc<ExecutorContext>,
+    give_up: Arc<AtomicBool>,
+) -> Result<(), ExecutorError> {
     info!("Worker {} started", worker_id);
 
-    while let Ok(task) = rx.recv().await {
-        let result = handle_follower_order(&exchange_client, &task.order, &task.follower).await;
+    while let Some(task) = rx.recv().await {
+        if give_up.load(Ordering::Relaxed) {
+            if let Err(e) = ctx.trades.save_pending(&task.follower.address, &task.order).await {
+                error!("Worker {}: Lost copy of leader order {} for {} — {}", worker_id, task.order.oid, task.follower.address, e);
+            }
+            continue;
+        }
+        let result = handle_follower_order(&ctx, &task.order, &task.follower).await;
 
         match result {
-            Ok(oid) => {
-                info!("Worker {}: Executed order for {} — OID: {}", worker_id, task.follower.address, oid);
+            Ok(outcomes) => {
+                let latency_ms = (chrono::Utc::now().timestamp_millis() as u64).saturating_sub(task.order.timestamp);
+                for outcome in outcomes {
+                    info!(
+                        "Worker {}: Executed {} order for {} — OID: {} ({}ms after leader fill)",
+                        worker_id, task.follower.mode.as_str(), task.follower.address, outcome.oid(), latency_ms
+                    );
+                }
+            }
+            Err(ExecutorError::Skipped(reason)) => {
+                info!("Worker {}: Skipped order for {} — {}", worker_id, task.follower.address, reason);
             }
             Err(e) => {
                 error!("Worker {}: Failed for {} — {}", worker_id, task.follower.address, e);
@@ -141,55 +418,333 @@ async fn order_worker(worker_id: usize, mut rx: broadcast::Receiver<OrderTask>,
     Ok(())
 }
 
-async fn handle_follower_order(
-    exchange_client: &ExchangeClient,
-    order: &FullOrder,
-    follower: &FollowersCache,
-) -> Result<u64, ExecutorError> {
-    let mut sz = order.total_sz * follower.ratio;
+/// Size of the follower copy for `leader_sz` opened by the leader at `avg_px`,
+/// after the per-trade risk cap. In equity mode `follower.ratio` must already
+/// include the equity scale.
+pub fn follower_order_size(leader_sz: Decimal, avg_px: Decimal, follower: &FollowersCache) -> Result<Decimal, ExecutorError> {
+    let mut sz = match follower.sizing_mode {
+        SizingMode::Ratio | SizingMode::Equity => leader_sz * follower.ratio,
+        SizingMode::FixedNotional => {
+            follower.fixed_notional.unwrap_or_default() / avg_px.max(dec!(0.0000001))
+        }
+    };
 
     if let Some(max_risk) = follower.max_risk {
-        let notional = sz * order.avg_px;
+        let notional = sz * avg_px;
         if notional > max_risk {
-            sz = max_risk / order.avg_px.max(dec!(0.0000001));
+            sz = max_risk / avg_px.max(dec!(0.0000001));
         }
     }
 
     if sz <= dec!(0.000001) {
         return Err(ExecutorError::OrderSizeTooSmall);
     }
+    Ok(sz.round_dp(8))
+}
+
+/// One order a follower sends to mirror a leader order.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CopyLeg {
+    pub is_buy: bool,
+    pub sz: Decimal,
+    pub reduce_only: bool,
+}
+
+/// Orders that mirror `order` for a follower currently holding `position` in its coin.
+///
+/// Opens are sized from the copy ratio; closes from the follower's own position and
+/// are reduce-only, so a close can never open the opposite side. An empty plan means
+/// the follower has nothing to close.
+pub fn plan_copy(order: &FullOrder, follower: &FollowersCache, position: Decimal) -> Result<Vec<CopyLeg>, ExecutorError> {
+    if LeaderAction::is_spot(&order.dir) {
+        return Err(ExecutorError::Skipped(format!("{} {} is a spot order, only perp orders are copied", order.dir, order.coin)));
+    }
+    let action = LeaderAction::from_dir(&order.dir).ok_or_else(|| ExecutorError::UnknownDirection(order.dir.clone()))?;
+
+    let close = |is_buy: bool| -> Result<Option<CopyLeg>, ExecutorError> {
+        match positions::close_size(order, is_buy, position) {
+            Some(sz) if sz <= dec!(0.000001) => Err(ExecutorError::OrderSizeTooSmall),
+            Some(sz) => Ok(Some(CopyLeg { is_buy, sz, reduce_only: true })),
+            None => Ok(None),
+        }
+    };
+
+    match action {
+        LeaderAction::Open { is_buy } => Ok(vec![CopyLeg {
+            is_buy,
+            sz: follower_order_size(order.total_sz, order.avg_px, follower)?,
+            reduce_only: false,
+        }]),
+        LeaderAction::Close { is_buy } => Ok(close(is_buy)?.into_iter().collect()),
+        LeaderAction::Flip { is_buy } => {
+            let mut legs: Vec<CopyLeg> = close(is_buy)?.into_iter().collect();
+            match follower_order_size(positions::flip_open_size(order), order.avg_px, follower) {
+                Ok(sz) => legs.push(CopyLeg { is_buy, sz, reduce_only: false }),
+                // the close alone still mirrors the leader leaving the old side
+                Err(ExecutorError::OrderSizeTooSmall) if !legs.is_empty() => {}
+                Err(e) => return Err(e),
+            }
+            Ok(legs)
+        }
+    }
+}
+
+pub(crate) async fn handle_follower_order(
+    ctx: &ExecutorContext,
+    order: &FullOrder,
+    follower: &FollowersCache,
+) -> Result<Vec<OrderOutcome>, ExecutorError> {
+    let mut follower = follower.clone();
+    // closes are sized from the follower's position, only opens need account values
+    let closes_only = matches!(LeaderAction::from_dir(&order.dir), Some(LeaderAction::Close { .. }));
+    if follower.sizing_mode == SizingMode::Equity && !closes_only {
+        follower.ratio *= ctx.equity.equity_ratio(&order.user, &follower.address).await?;
+    }
+    let follower = &follower;
+
+    let book = ctx.positions_for(follower);
+    let position = book.position(&follower.address, &order.coin).await?;
+    let legs = plan_copy(order, follower, position)?;
+    if legs.is_empty() {
+        info!(
+            "Skipping {} {} for {}: no position to close",
+            order.dir, order.coin, follower.address
+        );
+        return Ok(Vec::new());
+    }
+
+    let mut outcomes = Vec::with_capacity(legs.len());
+    for leg in legs {
+        let outcome = place_leg(ctx, order, follower, &leg).await?;
+        if let OrderOutcome::Filled { total_sz, .. } = &outcome {
+            book.apply_fill(&follower.address, &order.coin, leg.is_buy, *total_sz).await;
+        }
+        outcomes.push(outcome);
+    }
+    Ok(outcomes)
+}
+
+async fn place_leg(
+    ctx: &ExecutorContext,
+    order: &FullOrder,
+    follower: &FollowersCache,
+    leg: &CopyLeg,
+) -> Result<OrderOutcome, ExecutorError> {
+    let CopyLeg { is_buy, sz, reduce_only } = *leg;
+    let execution = follower.execution;
+
+    let reference = if execution.needs_mid() {
+        ctx.exchange.mid_price(&order.coin).await?
+    } else {
+        order.avg_px
+    };
+    let asset = ctx.metadata.asset(&order.coin).await?;
+    let limit_px = asset.round_price(execution.limit_price(is_buy, reference), is_buy);
+    let sz = asset.round_size(sz);
+    if sz.is_zero() {
+        return Err(ExecutorError::OrderSizeTooSmall);
+    }
 
-    let is_buy = matches!(order.dir.as_str(), "Open Long" | "Close Short");
+    let notional = sz * limit_px;
+    // reducing orders are exempt so small leftovers can still be closed
+    if !reduce_only {
+        if notional < MIN_NOTIONAL {
+            return Err(ExecutorError::BelowMinNotional { notional, min: MIN_NOTIONAL });
+        }
+        // paper copies use no margin
+        if let Some(max_leverage) = asset.max_leverage
+            && follower.mode == CopyMode::Live
+        {
+            let equity = ctx.equity.account_value(&follower.address).await?;
+            if notional > equity * Decimal::from(max_leverage) {
+                return Err(ExecutorError::ExceedsMaxLeverage { notional, equity, max_leverage });
+            }
+        }
+    }
 
-    let client_order = ClientOrderRequest {
-        asset: order.coin.clone(),
+    let request = OrderRequest {
+        coin: order.coin.clone(),
         is_buy,
-        reduce_only: false,
-        limit_px: order.avg_px.to_f64().ok_or(ExecutorError::DecimalConversion)?,
-        sz: sz.round_dp(8).to_f64().ok_or(ExecutorError::DecimalConversion)?,
-        cloid: None,
-        order_type: ClientOrder::Limit(ClientLimit {
-            tif: "Gtc".to_string(),
+        reduce_only,
+        limit_px,
+        sz,
+        tif: execution.tif(),
+    };
+
+    let trade_id = ctx.trades.record_sent(order, &follower.address, is_buy, sz, limit_px).await?;
+
+    let side = if is_buy { "buy" } else { "sell" };
+    let result = match follower.mode {
+        CopyMode::Paper => paper::match_order(&request, order.avg_px).ok_or_else(|| {
+            ExecutorError::Skipped(format!(
+                "leader price {} is outside the paper {} {} limit of {}",
+                order.avg_px, order.coin, side, limit_px
+            ))
         }),
+        CopyMode::Live => match follower.signer() {
+            Some(signer) => match ctx.exchange.place_order(signer, &request).await {
+                // nothing on the book inside the band: the copy is skipped, not failed
+                Err(ExchangeError::Unmatched(reason)) => Err(ExecutorError::Skipped(
+                    format!("no liquidity for {} {} at {} or better: {}", order.coin, side, limit_px, reason),
+                )),
+                result => result.map_err(ExecutorError::from),
+            },
+            None => Err(ExchangeError::InvalidAgentKey(format!("no agent key for {}", follower.address)).into()),
+        },
+    };
+
+    // a GTC copy keeps whatever did not fill at once on the book until its timeout
+    let resting = match (&result, execution, follower.agent_key_id) {
+        (Ok(outcome), ExecutionStyle::Gtc { timeout }, Some(agent_key_id)) if follower.mode == CopyMode::Live => {
+            let (filled_sz, filled_px) = match outcome {
+                OrderOutcome::Filled { total_sz, avg_px, .. } => (*total_sz, *avg_px),
+                OrderOutcome::Resting { .. } => (Decimal::ZERO, Decimal::ZERO),
+            };
+            (filled_sz < sz).then(|| RestingCopy {
+                trade_id,
+                follower_address: follower.address.clone(),
+                agent_key_id,
+                coin: order.coin.clone(),
+                is_buy,
+                oid: outcome.oid() as i64,
+                limit_px,
+                sz,
+                filled_sz,
+                filled_px,
+                cancel_at: (chrono::Utc::now() + chrono::Duration::from_std(timeout).unwrap_or_default()).naive_utc(),
+            })
+        }
+        _ => None,
     };
 
-    let response = exchange_client
-        .order(client_order, None)
-        .await
-        .map_err(|e| ExecutorError::OrderPlacement(e.to_string()))?;
+    let recorded = match (&result, &resting) {
+        (_, Some(copy)) => ctx.trades.record_resting(copy).await,
+        (Ok(OrderOutcome::Filled { total_sz, avg_px, .. }), _) if follower.mode == CopyMode::Paper => {
+            ctx.trades.record_paper_fill(trade_id, *total_sz, *avg_px).await
+        }
+        (Ok(OrderOutcome::Filled { oid, total_sz, avg_px }), _) => {
+            ctx.trades.record_filled(trade_id, *oid, *total_sz, *avg_px).await
+        }
+        (Ok(OrderOutcome::Resting { oid }), _) => {
+            ctx.trades.record_placed(trade_id, TradeStatus::Resting, *oid).await
+        }
+        (Err(ExecutorError::Skipped(reason)), _) => {
+            ctx.trades.record_failure(trade_id, TradeStatus::Skipped, reason).await
+        }
+        (Err(ExecutorError::Exchange(ExchangeError::Rejected(reason))), _) => {
+            ctx.trades.record_failure(trade_id, TradeStatus::Rejected, reason).await
+        }
+        (Err(e), _) => ctx.trades.record_failure(trade_id, TradeStatus::Failed, &e.to_string()).await,
+    };
+    if let Err(e) = recorded {
+        error!("Failed to record outcome of trade {}: {}", trade_id, e);
+    }
+    if let Some(copy) = resting {
+        ctx.schedule_cancel(copy);
+    }
+
+    result
+}
+
+/// At the deadline of a resting GTC copy, cancel what is still open, then record
+/// what filled while it rested and apply that to the follower's positions.
+///
+/// On failure the copy stays stored and the next start tries again.
+async fn expire_resting(exchange: Arc<dyn Exchange>, trades: TradeLog, positions: Arc<PositionBook>, copy: RestingCopy) {
+    let wait = (copy.cancel_at - chrono::Utc::now().naive_utc()).to_std().unwrap_or_default();
+    tokio::time::sleep(wait).await;
+
+    let signer = Signer { address: &copy.follower_address, agent_key_id: copy.agent_key_id };
+    let state = match cancel_open(exchange.as_ref(), signer, &copy.coin, copy.oid as u64).await {
+        Ok(state) => state,
+        Err(e) => {
+            error!("Failed to cancel resting order {} of {}: {}", copy.oid, copy.follower_address, e);
+            return;
+        }
+    };
+
+    // fills after the order started resting happened at its limit
+    let filled_sz = state.filled_sz.max(copy.filled_sz);
+    let later_sz = filled_sz - copy.filled_sz;
+    let (status, filled, reason) = if filled_sz.is_zero() {
+        (TradeStatus::Cancelled, None, Some("not filled before the GTC timeout".to_string()))
+    } else {
+        let avg_px = (copy.filled_sz * copy.filled_px + later_sz * copy.limit_px) / filled_sz;
+        if filled_sz >= copy.sz {
+            (TradeStatus::Filled, Some((filled_sz, avg_px)), None)
+        } else {
+            let reason = format!("{} of {} unfilled at the GTC timeout and cancelled", copy.sz - filled_sz, copy.sz);
+            (TradeStatus::PartiallyFilled, Some((filled_sz, avg_px)), Some(reason))
+        }
+    };
 
-    match response {
-        hyperliquid_rust_sdk::ExchangeResponseStatus::Ok(exchange_response) => {
-            let data = exchange_response.data.ok_or(ExecutorError::NoDataInResponse)?;
-            match &data.statuses[0] {
-                hyperliquid_rust_sdk::ExchangeDataStatus::Filled(o) => Ok(o.oid),
-                hyperliquid_rust_sdk::ExchangeDataStatus::Resting(o) => Ok(o.oid),
-                status => Err(ExecutorError::UnexpectedStatus(status.clone())),
+    match trades.finish_resting(copy.trade_id, status, filled, reason.as_deref()).await {
+        Ok(true) => {
+            if later_sz > Decimal::ZERO {
+                positions.apply_fill(&copy.follower_address, &copy.coin, copy.is_buy, later_sz).await;
             }
+            info!(
+                "Resting order {} of {} ended {}: {} of {} filled",
+                copy.oid, copy.follower_address, status.as_str(), filled_sz, copy.sz
+            );
         }
-        hyperliquid_rust_sdk::ExchangeResponseStatus::Err(e) => {
-            Err(ExecutorError::OrderPlacement(e.to_string()))
+        // a previous run already finished it
+        Ok(false) => {}
+        Err(e) => error!("Failed to record the end of resting trade {}: {}", copy.trade_id, e),
+    }
+}
+
+/// Cancel order `oid` if it is still open and return its final state.
+async fn cancel_open(exchange: &dyn Exchange, signer: Signer<'_>, coin: &str, oid: u64) -> Result<OrderState, ExchangeError> {
+    let state = exchange.order_status(signer.address, oid).await?;
+    if state.status != OrderStatus::Open {
+        return Ok(state);
+    }
+    exchange.cancel_order(signer, coin, oid).await?;
+    // it may have filled further before the cancel went through
+    exchange.order_status(signer.address, oid).await
+}
+
+/// An active copy config joined with its follower and agent key.
+#[derive(Debug, FromRow)]
+struct FollowerRow {
+    address: String,
+    agent_key_id: Option<i32>,
+    trader_address: String,
+    ratio: Decimal,
+    max_risk_per_trade: Option<Decimal>,
+    sizing_mode: String,
+    fixed_notional: Option<Decimal>,
+    execution_style: String,
+    max_slippage_bps: i32,
+    gtc_timeout_secs: i32,
+    mode: String,
+}
+
+impl FollowerRow {
+    /// The leader copied and how; `None` for a live config without an agent key,
+    /// which cannot sign orders.
+    fn into_follower(self) -> Option<(String, FollowersCache)> {
+        let mode = CopyMode::parse(&self.mode).unwrap_or_default();
+        if mode == CopyMode::Live && self.agent_key_id.is_none() {
+            return None;
         }
+        let execution = ExecutionStyle::new(
+            ExecutionStyleKind::parse(&self.execution_style).unwrap_or(ExecutionStyleKind::Ioc),
+            self.max_slippage_bps.max(0) as u32,
+            std::time::Duration::from_secs(self.gtc_timeout_secs.max(1) as u64),
+        );
+        let follower = FollowersCache {
+            address: self.address,
+            agent_key_id: self.agent_key_id,
+            ratio: self.ratio,
+            max_risk: self.max_risk_per_trade,
+            sizing_mode: SizingMode::parse(&self.sizing_mode).unwrap_or_default(),
+            fixed_notional: self.fixed_notional,
+            execution,
+            mode,
+        };
+        Some((self.trader_address, follower))
     }
 }
 
@@ -197,24 +752,21 @@ async fn preload_followers(
     pool: &PgPool,
     cache: &mut HashMap<String, Vec<FollowersCache>>,
 ) -> Result<(), ExecutorError> {
-    let rows: Vec<PgRow> = sqlx::query(
-        "SELECT f.address, f.agent_signature, c.trader_address, c.ratio, c.max_risk_per_trade
+    // paper copies run without an agent key
+    let rows: Vec<FollowerRow> = sqlx::query_as(
+        "SELECT f.address, k.id AS agent_key_id, c.trader_address, c.ratio, c.max_risk_per_trade,
+                c.sizing_mode, c.fixed_notional, c.execution_style, c.max_slippage_bps, c.gtc_timeout_secs,
+                c.mode
          FROM copy_configs c
          JOIN followers f ON c.follower_id = f.id
+         LEFT JOIN agent_keys k ON k.follower_id = f.id AND k.revoked_at IS NULL
          WHERE c.is_active = true",
     )
     .fetch_all(pool)
     .await?;
 
     cache.clear();
-    for row in rows {
-        let trader: String = row.get("trader_address");
-        let follower = FollowersCache {
-            address: row.get("address"),
-            signature: row.get("agent_signature"),
-            ratio: row.get("ratio"),
-            max_risk: row.get("max_risk_per_trade"),
-        };
+    for (trader, follower) in rows.into_iter().filter_map(FollowerRow::into_follower) {
         cache.entry(trader).or_default().push(follower);
     }
     Ok(())
@@ -223,7 +775,345 @@ async fn preload_followers(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::channel::WsFillChannel;
+    use crate::config::GrouperConfig;
+    use crate::engine::grouper;
+    use crate::engine::metadata::AssetInfo;
+    use crate::exchange::mock::MockExchange;
+    use crate::exchange::TimeInForce;
+    use crate::hyperliquid::ws::WsFill;
+    use std::time::Duration;
+
+    fn order(total_sz: Decimal, avg_px: Decimal) -> FullOrder {
+        FullOrder {
+            user: "0xleader".to_string(),
+            coin: "BTC".to_string(),
+            dir: "Open Long".to_string(),
+            total_sz,
+            avg_px,
+            timestamp: 0,
+            hash: "0xhash".to_string(),
+            oid: 1,
+            start_position: None,
+        }
+    }
+
+    fn follower(ratio: Decimal, max_risk: Option<Decimal>) -> FollowersCache {
+        FollowersCache {
+            address: "0xfollower".to_string(),
+            agent_key_id: Some(1),
+            ratio,
+            max_risk,
+            sizing_mode: SizingMode::Ratio,
+            fixed_notional: None,
+            execution: ExecutionStyle::Ioc { max_slippage_bps: 50 },
+            mode: CopyMode::Live,
+        }
+    }
+
+    fn follower_row(mode: &str, agent_key_id: Option<i32>) -> FollowerRow {
+        FollowerRow {
+            address: "0xfollower".to_string(),
+            agent_key_id,
+            trader_address: "0xleader".to_string(),
+            ratio: dec!(0.1),
+            max_risk_per_trade: Some(dec!(500)),
+            sizing_mode: "fixed_notional".to_string(),
+            fixed_notional: Some(dec!(100)),
+            execution_style: "gtc".to_string(),
+            max_slippage_bps: 50,
+            gtc_timeout_secs: 0,
+            mode: mode.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_preload() {
+        let (trader, live) = follower_row("live", Some(7)).into_follower().unwrap();
+        assert_eq!(trader, "0xleader");
+        assert_eq!(live.signer(), Some(Signer { address: "0xfollower", agent_key_id: 7 }));
+        assert_eq!((live.sizing_mode, live.fixed_notional, live.max_risk), (SizingMode::FixedNotional, Some(dec!(100)), Some(dec!(500))));
+        // a timeout below a second would cancel before the order could fill
+        assert_eq!(live.execution, ExecutionStyle::Gtc { timeout: Duration::from_secs(1) });
+
+        // paper copies need no agent key, live ones cannot sign without one
+        let (_, paper) = follower_row("paper", None).into_follower().unwrap();
+        assert_eq!((paper.mode, paper.signer()), (CopyMode::Paper, None));
+        assert!(follower_row("live", None).into_follower().is_none());
+    }
+
+    #[test]
+    fn test_follower_order_size() {
+        let sz = follower_order_size(dec!(2), dec!(50000), &follower(dec!(0.1), None)).unwrap();
+        assert_eq!(sz, dec!(0.2));
+
+        // 0.2 BTC @ 50k = 10k notional, capped to 1k
+        let sz = follower_order_size(dec!(2), dec!(50000), &follower(dec!(0.1), Some(dec!(1000)))).unwrap();
+        assert_eq!(sz, dec!(0.02));
+
+        assert!(matches!(
+            follower_order_size(dec!(0.00001), dec!(50000), &follower(dec!(0.01), None)),
+            Err(ExecutorError::OrderSizeTooSmall)
+        ));
+
+        // fixed notional ignores the leader's size: $500 @ 50k = 0.01 BTC
+        let mut fixed = follower(dec!(0.1), None);
+        fixed.sizing_mode = SizingMode::FixedNotional;
+        fixed.fixed_notional = Some(dec!(500));
+        assert_eq!(follower_order_size(dec!(2), dec!(50000), &fixed).unwrap(), dec!(0.01));
+        assert_eq!(follower_order_size(dec!(0.001), dec!(50000), &fixed).unwrap(), dec!(0.01));
+    }
 
     #[test]
-    fn test_preload() {}
+    fn test_plan_copy() {
+        let f = follower(dec!(0.1), None);
+
+        let open = order(dec!(2), dec!(50000));
+        assert_eq!(
+            plan_copy(&open, &f, dec!(0)).unwrap(),
+            vec![CopyLeg { is_buy: true, sz: dec!(0.2), reduce_only: false }]
+        );
+
+        // leader closes half its 4 BTC long; follower halves its own 0.3
+        let mut close = order(dec!(2), dec!(50000));
+        close.dir = "Close Long".to_string();
+        close.start_position = Some(dec!(4));
+        assert_eq!(
+            plan_copy(&close, &f, dec!(0.3)).unwrap(),
+            vec![CopyLeg { is_buy: false, sz: dec!(0.15), reduce_only: true }]
+        );
+
+        // a close never opens a position the follower doesn't have
+        assert!(plan_copy(&close, &f, dec!(0)).unwrap().is_empty());
+        assert!(plan_copy(&close, &f, dec!(-0.3)).unwrap().is_empty());
+
+        // long 1 -> short 2: close the follower's long, then open the short part
+        let mut flip = order(dec!(3), dec!(50000));
+        flip.dir = "Long > Short".to_string();
+        flip.start_position = Some(dec!(1));
+        assert_eq!(
+            plan_copy(&flip, &f, dec!(0.1)).unwrap(),
+            vec![
+                CopyLeg { is_buy: false, sz: dec!(0.1), reduce_only: true },
+                CopyLeg { is_buy: false, sz: dec!(0.2), reduce_only: false },
+            ]
+        );
+
+        let mut spot = order(dec!(1), dec!(10));
+        spot.dir = "Buy".to_string();
+        assert!(matches!(plan_copy(&spot, &f, dec!(0)), Err(ExecutorError::Skipped(_))));
+
+        let mut unknown = order(dec!(1), dec!(50000));
+        unknown.dir = "Liquidation".to_string();
+        assert!(matches!(plan_copy(&unknown, &f, dec!(0)), Err(ExecutorError::UnknownDirection(_))));
+    }
+
+    #[tokio::test]
+    async fn test_order_queue_delivers_each_task_once_in_order() {
+        let mut receivers = Vec::new();
+        let mut senders = Vec::new();
+        for _ in 0..4 {
+            let (tx, rx) = mpsc::channel(16);
+            senders.push(tx);
+            receivers.push(rx);
+        }
+        let mut queue = OrderQueue::new(4, Box::new(move |shard| senders[shard].clone()));
+
+        let followers = ["0xf1", "0xf2", "0xf3"];
+        for oid in 0..5 {
+            for address in followers {
+                let mut task_order = order(dec!(1), dec!(100));
+                task_order.oid = oid;
+                let mut task_follower = follower(dec!(0.1), None);
+                task_follower.address = address.to_string();
+                queue.dispatch(OrderTask { order: task_order, follower: task_follower }).await;
+            }
+        }
+
+        let mut received: HashMap<String, Vec<u64>> = HashMap::new();
+        for (shard, rx) in receivers.iter_mut().enumerate() {
+            while let Ok(task) = rx.try_recv() {
+                assert_eq!(queue.shard_for(&task.follower.address), shard);
+                received.entry(task.follower.address).or_default().push(task.order.oid);
+            }
+        }
+
+        assert_eq!(received.len(), followers.len());
+        for oids in received.values() {
+            assert_eq!(oids, &vec![0, 1, 2, 3, 4]);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_order_queue_replaces_dead_worker() {
+        let (dead_tx, dead_rx) = mpsc::channel(1);
+        drop(dead_rx);
+        let (tx, mut rx) = mpsc::channel(1);
+        let mut senders = vec![dead_tx, tx].into_iter();
+        let mut queue = OrderQueue::new(1, Box::new(move |_| senders.next().unwrap()));
+
+        let task = OrderTask { order: order(dec!(1), dec!(100)), follower: follower(dec!(0.1), None) };
+        queue.dispatch(task).await;
+        assert_eq!(rx.try_recv().unwrap().order.oid, 1);
+    }
+
+    fn leader_fill(oid: u64, tid: u64, dir: &str, side: &str, px: &str, sz: &str, start_position: &str) -> WsFillChannel {
+        WsFillChannel {
+            fill: WsFill {
+                coin: "BTC".to_string(),
+                px: px.to_string(),
+                sz: sz.to_string(),
+                side: side.to_string(),
+                time: 1,
+                hash: format!("0xhash{oid}"),
+                oid,
+                tid,
+                start_position: Some(start_position.to_string()),
+                closed_pnl: None,
+                dir: Some(dir.to_string()),
+                crossed: true,
+                fee: "0".to_string(),
+                fee_token: "USDC".to_string(),
+            },
+            user: "0xleader".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_run_drains_queued_copies_when_input_closes() {
+        let exchange = Arc::new(
+            MockExchange::new()
+                .with_asset("BTC", AssetInfo::perp(5, 40))
+                .with_book("BTC", &[(dec!(49990), dec!(10))], &[(dec!(50010), dec!(10))])
+                .with_account("0xfollower", dec!(100000)),
+        );
+        let ctx = Arc::new(ExecutorContext::new(exchange.clone(), TradeLog::disabled(), Arc::new(SeenStore::in_memory())).await.unwrap());
+        let cache: SharedCache = Arc::new(RwLock::new(HashMap::from([(
+            "0xleader".to_string(),
+            vec![follower(dec!(0.1), None)],
+        )])));
+
+        let (order_tx, order_rx) = broadcast::channel(16);
+        for oid in 1..=3 {
+            let mut leader_order = order(dec!(1), dec!(50000));
+            leader_order.oid = oid;
+            order_tx.send(leader_order).unwrap();
+        }
+        drop(order_tx);
+
+        // returns only after the workers placed everything already received
+        tokio::time::timeout(Duration::from_secs(5), run(order_rx, cache, ctx, &ExecutorConfig::default()))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(exchange.placed_orders().len(), 3);
+    }
+
+    // real time: the grouper debounces on std::time::Instant
+    #[tokio::test]
+    async fn test_leader_fills_to_follower_orders() {
+        let exchange = Arc::new(
+            MockExchange::new()
+                .with_asset("BTC", AssetInfo::perp(5, 40))
+                .with_book("BTC", &[(dec!(49990), dec!(10))], &[(dec!(50010), dec!(10))])
+                .with_account("0xfollower", dec!(100000)),
+        );
+        let ctx = Arc::new(ExecutorContext::new(exchange.clone(), TradeLog::disabled(), Arc::new(SeenStore::in_memory())).await.unwrap());
+        let cache: SharedCache = Arc::new(RwLock::new(HashMap::from([(
+            "0xleader".to_string(),
+            vec![follower(dec!(0.1), None)],
+        )])));
+
+        let (fill_tx, fill_rx) = broadcast::channel(16);
+        let (order_tx, order_rx) = broadcast::channel(16);
+        tokio::spawn(grouper::start(fill_rx, order_tx, Arc::new(SeenStore::in_memory()), Arc::default(), GrouperConfig::default()));
+        tokio::spawn(async move { run(order_rx, cache, ctx, &ExecutorConfig::default()).await });
+
+        // leader opens 2 BTC over two fills of one order...
+        fill_tx.send(leader_fill(1, 1, "Open Long", "B", "50000", "1", "0")).unwrap();
+        fill_tx.send(leader_fill(1, 2, "Open Long", "B", "50004", "1", "1")).unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        // ...then closes half of it
+        fill_tx.send(leader_fill(2, 3, "Close Long", "A", "50100", "1", "2")).unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        // a reconnect replays the close: it must not be copied again
+        fill_tx.send(leader_fill(2, 3, "Close Long", "A", "50100", "1", "2")).unwrap();
+        tokio::time::sleep(Duration::from_secs(1)).await;
+
+        let placed = exchange.placed_orders();
+        assert_eq!(placed.len(), 2, "{placed:?}");
+
+        // one copy of the grouped order, IOC inside 50 bps of the 50000 mid
+        let open = &placed[0].order;
+        assert_eq!((open.is_buy, open.sz, open.reduce_only, open.tif), (true, dec!(0.2), false, TimeInForce::Ioc));
+        assert_eq!(open.limit_px, dec!(50250));
+        assert_eq!(placed[0].outcome.as_ref().unwrap().oid(), 1);
+
+        // the close halves the follower's own position and can only reduce it
+        let close = &placed[1].order;
+        assert_eq!((close.is_buy, close.sz, close.reduce_only), (false, dec!(0.1), true));
+        assert!(placed[1].outcome.is_ok());
+        assert_eq!(exchange.position("0xfollower", "BTC"), dec!(0.1));
+    }
+
+    #[tokio::test]
+    async fn test_gtc_remainder_is_cancelled_and_later_fills_kept() {
+        // half of the copy fills at once, the rest rests at the leader's price
+        let exchange = Arc::new(
+            MockExchange::new()
+                .with_asset("BTC", AssetInfo::perp(5, 40))
+                .with_book("BTC", &[(dec!(49990), dec!(10))], &[(dec!(50000), dec!(1))])
+                .with_account("0xfollower", dec!(100000)),
+        );
+        let ctx = ExecutorContext::new(exchange.clone(), TradeLog::disabled(), Arc::new(SeenStore::in_memory())).await.unwrap();
+        let gtc = FollowersCache {
+            execution: ExecutionStyle::Gtc { timeout: Duration::from_millis(200) },
+            ..follower(dec!(0.1), None)
+        };
+
+        let outcomes = handle_follower_order(&ctx, &order(dec!(20), dec!(50000)), &gtc).await.unwrap();
+        assert_eq!(outcomes, vec![OrderOutcome::Filled { oid: 1, total_sz: dec!(1), avg_px: dec!(50000) }]);
+        assert_eq!(ctx.positions.position("0xfollower", "BTC").await.unwrap(), dec!(1));
+
+        // someone trades against the resting rest before the timeout
+        exchange.fill_resting(1, dec!(0.25));
+        tokio::time::sleep(Duration::from_millis(500)).await;
+
+        let state = exchange.order_status("0xfollower", 1).await.unwrap();
+        assert_eq!(state, OrderState { status: OrderStatus::Cancelled, filled_sz: dec!(1.25) });
+        assert_eq!(ctx.positions.position("0xfollower", "BTC").await.unwrap(), dec!(1.25));
+    }
+
+    #[tokio::test]
+    async fn test_paper_copies_never_reach_the_exchange() {
+        // the follower has no funds on the exchange at all
+        let exchange = Arc::new(
+            MockExchange::new()
+                .with_asset("BTC", AssetInfo::perp(5, 40))
+                .with_book("BTC", &[(dec!(49990), dec!(10))], &[(dec!(50010), dec!(10))]),
+        );
+        let ctx = ExecutorContext::new(exchange.clone(), TradeLog::disabled(), Arc::new(SeenStore::in_memory())).await.unwrap();
+        let paper = FollowersCache { mode: CopyMode::Paper, ..follower(dec!(0.1), None) };
+
+        let mut open = order(dec!(2), dec!(50002));
+        open.start_position = Some(dec!(0));
+        let outcomes = handle_follower_order(&ctx, &open, &paper).await.unwrap();
+        assert_eq!(outcomes, vec![OrderOutcome::Filled { oid: 0, total_sz: dec!(0.2), avg_px: dec!(50002) }]);
+
+        let mut close = order(dec!(1), dec!(50100));
+        close.dir = "Close Long".to_string();
+        close.start_position = Some(dec!(2));
+        let outcomes = handle_follower_order(&ctx, &close, &paper).await.unwrap();
+        assert_eq!(outcomes, vec![OrderOutcome::Filled { oid: 0, total_sz: dec!(0.1), avg_px: dec!(50100) }]);
+
+        assert_eq!(ctx.paper_positions.position("0xfollower", "BTC").await.unwrap(), dec!(0.1));
+        assert_eq!(ctx.positions.position("0xfollower", "BTC").await.unwrap(), dec!(0));
+        assert!(exchange.placed_orders().is_empty());
+
+        // the leader's price moved out of the band around the current mid
+        let far = order(dec!(2), dec!(51000));
+        assert!(matches!(handle_follower_order(&ctx, &far, &paper).await, Err(ExecutorError::Skipped(_))));
+    }
 }
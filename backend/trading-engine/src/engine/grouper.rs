@@ -1,18 +1,24 @@
 use std::collections::HashMap;
 use std::sync::Arc;
-use std::time::{Duration, Instant};
+use std::time::Instant;
 use tokio::sync::{
     broadcast,
     broadcast::error::{RecvError, SendError},
     Mutex,
 };
 use tokio::time::sleep;
+use log::{error, info};
 use rust_decimal::prelude::*;
 use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::channel::WsFillChannel;
-use crate::engine::parser::ParseError;
+use crate::config::GrouperConfig;
+use crate::engine::dedup::SeenStore;
+use crate::engine::parser::{parse_price, parse_size, ParseError};
+use crate::hyperliquid::ws::WsFill;
+use crate::supervisor::Liveness;
 
 #[derive(Error, Debug)]
 pub enum GrouperError {
@@ -22,12 +28,13 @@ pub enum GrouperError {
     ParseDecimalError(#[from] ParseError),
     #[error("Failed to send full order: {0}")]
     SendError(#[from] SendError<FullOrder>),
+    #[allow(dead_code)]
     #[error("Unknown fill direction")]
     UnknownFillDirection,
 }
 
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FullOrder {
     pub user:String,
     pub coin: String,
@@ -37,6 +44,17 @@ pub struct FullOrder {
     pub timestamp: u64,
     pub hash: String,
     pub oid: u64,
+    /// Leader's signed position in `coin` before this order, when the exchange reports it
+    pub start_position: Option<Decimal>,
+}
+
+/// A fill waiting in its order, parsed; not claimed as seen until the order is emitted.
+#[derive(Debug, Clone)]
+struct PendingFill {
+    fill: WsFill,
+    sz: Decimal,
+    px: Decimal,
+    start_position: Option<Decimal>,
 }
 
 #[derive(Debug, Clone)]
@@ -44,24 +62,114 @@ struct PendingOrder {
     user: String,
     coin: String,
     dir: String,
-    total_sz: Decimal,
-    weighted_px: Decimal,  // sum(px * sz)
-    timestamp: u64,
+    fills: Vec<PendingFill>,
     last_seen: Instant,
-    hash: String,
     oid: u64,
 }
 
-pub async fn start(mut rx: broadcast::Receiver<WsFillChannel>, tx: broadcast::Sender<FullOrder>) -> Result<(), GrouperError> {
-    let pending = Arc::new(Mutex::new(HashMap::new()));
+impl PendingOrder {
+    /// Whether `fill` is already part of this order; fills without a trade id never are.
+    fn contains(&self, fill: &WsFill) -> bool {
+        fill.tid != 0 && self.fills.iter().any(|p| p.fill.tid == fill.tid && p.fill.hash == fill.hash)
+    }
+
+    /// The order made of `fills`, which must be some of this order's.
+    fn to_full_order(&self, fills: &[PendingFill]) -> Option<FullOrder> {
+        let first = fills.first()?;
+        let total_sz: Decimal = fills.iter().map(|f| f.sz).sum();
+        let weighted_px: Decimal = fills.iter().map(|f| f.px * f.sz).sum();
+        let avg_px = if total_sz > dec!(0) { weighted_px / total_sz } else { dec!(0) };
+        Some(FullOrder {
+            user: self.user.clone(),
+            coin: self.coin.clone(),
+            dir: self.dir.clone(),
+            total_sz,
+            avg_px,
+            timestamp: first.fill.time,
+            hash: first.fill.hash.clone(),
+            oid: self.oid,
+            start_position: first.start_position,
+        })
+    }
+}
+
+/// Claim the fills of `order` as seen and send whatever was not seen before. The
+/// claims are released again if the order cannot be sent, so nothing is marked
+/// seen that was never handed on.
+async fn emit(order: PendingOrder, seen: &SeenStore, tx: &broadcast::Sender<FullOrder>) {
+    let mut claimed = Vec::with_capacity(order.fills.len());
+    for pending in &order.fills {
+        let fill = &pending.fill;
+        match seen.claim_fill(&order.user, fill).await {
+            Ok(true) => claimed.push(pending.clone()),
+            Ok(false) => info!("Dropping duplicate fill {} (tid {}) of {}", fill.hash, fill.tid, order.user),
+            // better to miss a fill than to copy it twice
+            Err(e) => error!("Failed to check fill {} of {} for duplicates, dropping it: {}", fill.hash, order.user, e),
+        }
+    }
+    let Some(full) = order.to_full_order(&claimed) else {
+        return;
+    };
+
+    info!("{:?}", full);
+    if let Err(e) = tx.send(full) {
+        error!("Failed to send full order: {}", e);
+        for pending in &claimed {
+            if let Err(e) = seen.release_fill(&order.user, &pending.fill).await {
+                error!("Failed to release fill {} of {}: {}", pending.fill.hash, order.user, e);
+            }
+        }
+    }
+}
+
+/// Size, price and starting position of a fill.
+fn parse_fill(fill: &WsFill) -> Result<(Decimal, Decimal, Option<Decimal>), ParseError> {
+    let start_position = fill.start_position.as_deref().map(parse_size).transpose()?;
+    Ok((parse_size(&fill.sz)?, parse_price(&fill.px)?, start_position))
+}
+
+/// Group fills into orders until the fill channel closes, then flush whatever
+/// is still pending and return.
+pub async fn start(
+    mut rx: broadcast::Receiver<WsFillChannel>,
+    tx: broadcast::Sender<FullOrder>,
+    seen: Arc<SeenStore>,
+    liveness: Arc<Liveness>,
+    config: GrouperConfig,
+) -> Result<(), GrouperError> {
+    let pending = Arc::new(Mutex::new(HashMap::<u64, PendingOrder>::new()));
 
     loop {
         tokio::select! {
-            Ok(wsfill) = rx.recv() => {
+            msg = rx.recv() => {
+                let wsfill = match msg {
+                    Ok(wsfill) => wsfill,
+                    Err(RecvError::Lagged(skipped)) => {
+                        error!("Grouper lagging behind ingest — {} fills were dropped", skipped);
+                        continue;
+                    }
+                    Err(RecvError::Closed) => {
+                        // ingest stopped: nothing more can arrive for the open orders
+                        let flushed: Vec<PendingOrder> = pending.lock().await.drain().map(|(_, p)| p).collect();
+                        let count = flushed.len();
+                        for p in flushed {
+                            emit(p, &seen, &tx).await;
+                        }
+                        info!("Grouper stopped, flushed {} pending orders", count);
+                        return Ok(());
+                    }
+                };
+
                 let oid = wsfill.fill.oid;
-                let sz = Decimal::from_str(&wsfill.fill.sz).map_err(|e| ParseError::Size(e.to_string()))?;
-                let px = Decimal::from_str(&wsfill.fill.px).map_err(|e| ParseError::Price(e.to_string()))?;
-                let weighted = px * sz;
+                let (sz, px, start_position) = match parse_fill(&wsfill.fill) {
+                    Ok(parsed) => parsed,
+                    Err(e) => {
+                        // one malformed fill must not take the open orders down with it
+                        error!("Skipping malformed fill {} (tid {}) of {}: {}", wsfill.fill.hash, wsfill.fill.tid, wsfill.user, e);
+                        continue;
+                    }
+                };
+                liveness.fill_processed();
 
                 let mut pending_guard = pending.lock().await;
 
@@ -69,81 +177,60 @@ pub async fn start(mut rx: broadcast::Receiver<WsFillChannel>, tx: broadcast::Se
                     user:wsfill.user.clone(),
                     coin: wsfill.fill.coin.clone(),
                     dir: wsfill.fill.dir.clone().unwrap_or("Unknown".to_string()), // This should be handled properly
-                    total_sz: dec!(0),
-                    weighted_px: dec!(0),  // sum(px * sz)
-                    timestamp: wsfill.fill.time,
+                    fills: Vec::new(),
                     last_seen: Instant::now(),
-                    hash: wsfill.fill.hash.clone(),
                     oid,
                 });
 
-                entry.total_sz += sz;
-                entry.weighted_px += weighted;
+                if entry.contains(&wsfill.fill) {
+                    info!("Dropping duplicate fill {} (tid {}) of {}", wsfill.fill.hash, wsfill.fill.tid, wsfill.user);
+                    continue;
+                }
+                entry.fills.push(PendingFill { fill: wsfill.fill, sz, px, start_position });
                 entry.last_seen = Instant::now();
 
                 // If this is the first fill for this oid, spawn a debouncer
-                if entry.total_sz == sz {
+                if entry.fills.len() == 1 {
                     let oid_copy = oid;
                     let tx_clone = tx.clone();
+                    let seen_clone = Arc::clone(&seen);
                     let pending_clone = Arc::clone(&pending);
+                    let (debounce, quiet) = (config.debounce(), config.quiet());
 
                     tokio::spawn(async move {
-                        sleep(Duration::from_millis(420)).await;
-
-                        let mut pending_guard = pending_clone.lock().await;
-                        if let Some(final_order) = pending_guard.remove(&oid_copy) {
-                            if final_order.last_seen.elapsed() >= Duration::from_millis(400) {
-                                let avg_px = if final_order.total_sz > dec!(0) {
-                                    final_order.weighted_px / final_order.total_sz
-                                } else {
-                                    dec!(0)
-                                };
-
-                                let full = FullOrder {
-                                    user:final_order.user,
-                                    coin: final_order.coin,
-                                    dir: final_order.dir,
-                                    total_sz: final_order.total_sz,
-                                    avg_px,
-                                    timestamp: final_order.timestamp,
-                                    hash: final_order.hash,
-                                    oid: final_order.oid,
-                                };
-                                println!("{:?}", full.clone());
-
-                                if let Err(e) = tx_clone.send(full) {
-                                    eprintln!("Failed to send full order: {}", e);
-                                }
+                        loop {
+                            sleep(debounce).await;
+
+                            let mut pending_guard = pending_clone.lock().await;
+                            // keep waiting while fills are still arriving for this oid
+                            match pending_guard.get(&oid_copy) {
+                                None => break, // already flushed by the sweeper
+                                Some(p) if p.last_seen.elapsed() < quiet => continue,
+                                Some(_) => {}
                             }
+                            let Some(final_order) = pending_guard.remove(&oid_copy) else {
+                                break;
+                            };
+                            drop(pending_guard);
+
+                            emit(final_order, &seen_clone, &tx_clone).await;
+                            break;
                         }
                     });
                 }
             }
 
-            _ = sleep(Duration::from_secs(5)) => {
-                let mut pending_guard = pending.lock().await;
+            _ = sleep(config.sweep_interval()) => {
                 let now = Instant::now();
-                pending_guard.retain(|_, p| {
-                    if now.duration_since(p.last_seen) > Duration::from_millis(600) {
-                        let avg_px = if p.total_sz > dec!(0) { p.weighted_px / p.total_sz } else { dec!(0) };
-                        let full = FullOrder {
-                            user: p.user.clone(),
-                            coin: p.coin.clone(),
-                            dir: p.dir.clone(),
-                            total_sz: p.total_sz,
-                            avg_px,
-                            timestamp: p.timestamp,
-                            hash: p.hash.clone(),
-                            oid: p.oid,
-                        };
-                        if let Err(e) = tx.send(full.clone()) {
-                             eprintln!("Failed to send full order: {}", e);
-                        }
-                        false // remove
-                    } else {
-                        true // keep
-                    }
-                });
+                let expired: Vec<PendingOrder> = pending
+                    .lock()
+                    .await
+                    .extract_if(|_, p| now.duration_since(p.last_seen) > config.sweep_after())
+                    .map(|(_, p)| p)
+                    .collect();
+                for p in expired {
+                    emit(p, &seen, &tx).await;
+                }
             }
         }
     }
@@ -165,7 +252,7 @@ mod tests {
         let (fill_tx, fill_rx) = broadcast::channel::<WsFillChannel>(16);
         let (order_tx, mut order_rx) = broadcast::channel::<FullOrder>(16);
 
-        tokio::spawn(start(fill_rx, order_tx.clone()));
+        tokio::spawn(start(fill_rx, order_tx.clone(), Arc::new(SeenStore::in_memory()), Arc::default(), GrouperConfig::default()));
 
         let oid = 123;
         let user = "test_user".to_string();
@@ -178,12 +265,13 @@ mod tests {
             time: 1,
             hash: "hash1".to_string(),
             oid,
-            startPosition: None,
-            closedPnl: None,
+            tid: 1,
+            start_position: Some("0.5".to_string()),
+            closed_pnl: None,
             dir: Some("Open Long".to_string()),
             crossed: false,
             fee: "10.0".to_string(),
-            feeToken: "USDC".to_string(),
+            fee_token: "USDC".to_string(),
         };
 
         let fill2 = WsFill {
@@ -194,12 +282,13 @@ mod tests {
             time: 2,
             hash: "hash2".to_string(),
             oid,
-            startPosition: None,
-            closedPnl: None,
+            tid: 2,
+            start_position: Some("1.5".to_string()),
+            closed_pnl: None,
             dir: Some("Open Long".to_string()),
             crossed: false,
             fee: "20.0".to_string(),
-            feeToken: "USDC".to_string(),
+            fee_token: "USDC".to_string(),
         };
 
         fill_tx.send(WsFillChannel { fill: fill1, user: user.clone() }).unwrap();
@@ -218,5 +307,121 @@ mod tests {
         assert_eq!(full_order.oid, oid);
         assert_eq!(full_order.total_sz, expected_total_sz);
         assert_eq!(full_order.avg_px.round_dp(2), expected_avg_px.round_dp(2));
+        // the leader's position before the order, not before its last fill
+        assert_eq!(full_order.start_position, Some(dec!(0.5)));
+    }
+
+    #[tokio::test]
+    async fn test_closing_input_flushes_pending_orders() {
+        let (fill_tx, fill_rx) = broadcast::channel::<WsFillChannel>(16);
+        let (order_tx, mut order_rx) = broadcast::channel::<FullOrder>(16);
+        let grouper = tokio::spawn(start(fill_rx, order_tx, Arc::new(SeenStore::in_memory()), Arc::default(), GrouperConfig::default()));
+
+        let fill = WsFill {
+            coin: "ETH".to_string(),
+            px: "3000".to_string(),
+            sz: "2".to_string(),
+            side: "A".to_string(),
+            time: 1,
+            hash: "hash".to_string(),
+            oid: 9,
+            tid: 1,
+            start_position: None,
+            closed_pnl: None,
+            dir: Some("Open Short".to_string()),
+            crossed: true,
+            fee: "0".to_string(),
+            fee_token: "USDC".to_string(),
+        };
+        fill_tx.send(WsFillChannel { fill, user: "test_user".to_string() }).unwrap();
+        drop(fill_tx);
+
+        // well before the debounce would have emitted it
+        let order = time::timeout(Duration::from_millis(200), order_rx.recv()).await.unwrap().unwrap();
+        assert_eq!((order.oid, order.total_sz), (9, dec!(2)));
+        assert!(time::timeout(Duration::from_secs(1), grouper).await.unwrap().unwrap().is_ok());
+    }
+
+    fn eth_fill(tid: u64) -> WsFill {
+        WsFill {
+            coin: "ETH".to_string(),
+            px: "3000".to_string(),
+            sz: "1".to_string(),
+            side: "B".to_string(),
+            time: 1,
+            hash: "hash".to_string(),
+            oid: 9,
+            tid,
+            start_position: None,
+            closed_pnl: None,
+            dir: Some("Open Long".to_string()),
+            crossed: true,
+            fee: "0".to_string(),
+            fee_token: "USDC".to_string(),
+        }
+    }
+
+    #[tokio::test]
+    async fn test_fills_are_claimed_once_the_order_is_sent() {
+        let seen = Arc::new(SeenStore::in_memory());
+        // already passed on before a restart
+        assert!(seen.claim_fill("test_user", &eth_fill(3)).await.unwrap());
+
+        let (fill_tx, fill_rx) = broadcast::channel::<WsFillChannel>(16);
+        let (order_tx, mut order_rx) = broadcast::channel::<FullOrder>(16);
+        let grouper = tokio::spawn(start(fill_rx, order_tx, Arc::clone(&seen), Arc::default(), GrouperConfig::default()));
+        for tid in [1, 2, 1, 3] {
+            fill_tx.send(WsFillChannel { fill: eth_fill(tid), user: "test_user".to_string() }).unwrap();
+        }
+        drop(fill_tx);
+
+        let order = time::timeout(Duration::from_secs(1), order_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(order.total_sz, dec!(2));
+        grouper.await.unwrap().unwrap();
+        assert!(!seen.claim_fill("test_user", &eth_fill(1)).await.unwrap());
+
+        // nobody to send to: the fills stay unclaimed
+        let (fill_tx, fill_rx) = broadcast::channel::<WsFillChannel>(16);
+        let (order_tx, _) = broadcast::channel::<FullOrder>(16);
+        let grouper = tokio::spawn(start(fill_rx, order_tx, Arc::clone(&seen), Arc::default(), GrouperConfig::default()));
+        fill_tx.send(WsFillChannel { fill: eth_fill(4), user: "test_user".to_string() }).unwrap();
+        drop(fill_tx);
+        grouper.await.unwrap().unwrap();
+        assert!(seen.claim_fill("test_user", &eth_fill(4)).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_both_leaders_of_one_trade_are_grouped() {
+        let (fill_tx, fill_rx) = broadcast::channel::<WsFillChannel>(16);
+        let (order_tx, mut order_rx) = broadcast::channel::<FullOrder>(16);
+        let grouper = tokio::spawn(start(fill_rx, order_tx, Arc::new(SeenStore::in_memory()), Arc::default(), GrouperConfig::default()));
+
+        // the taker and the maker side share the trade's hash and tid
+        let maker = WsFill { oid: 10, side: "A".to_string(), dir: Some("Open Short".to_string()), ..eth_fill(5) };
+        fill_tx.send(WsFillChannel { fill: eth_fill(5), user: "0xtaker".to_string() }).unwrap();
+        fill_tx.send(WsFillChannel { fill: maker, user: "0xmaker".to_string() }).unwrap();
+        drop(fill_tx);
+        grouper.await.unwrap().unwrap();
+
+        let mut users = vec![order_rx.recv().await.unwrap().user, order_rx.recv().await.unwrap().user];
+        users.sort();
+        assert_eq!(users, vec!["0xmaker", "0xtaker"]);
+    }
+
+    #[tokio::test]
+    async fn test_malformed_fill_is_skipped() {
+        let (fill_tx, fill_rx) = broadcast::channel::<WsFillChannel>(16);
+        let (order_tx, mut order_rx) = broadcast::channel::<FullOrder>(16);
+        let grouper = tokio::spawn(start(fill_rx, order_tx, Arc::new(SeenStore::in_memory()), Arc::default(), GrouperConfig::default()));
+
+        let broken = WsFill { sz: "lots".to_string(), ..eth_fill(2) };
+        fill_tx.send(WsFillChannel { fill: eth_fill(1), user: "test_user".to_string() }).unwrap();
+        fill_tx.send(WsFillChannel { fill: broken, user: "test_user".to_string() }).unwrap();
+        fill_tx.send(WsFillChannel { fill: eth_fill(3), user: "test_user".to_string() }).unwrap();
+        drop(fill_tx);
+
+        assert!(grouper.await.unwrap().is_ok());
+        let order = order_rx.recv().await.unwrap();
+        assert_eq!(order.total_sz, dec!(2));
     }
 }
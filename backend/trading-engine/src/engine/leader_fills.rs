@@ -0,0 +1,168 @@
+use chrono::{DateTime, NaiveDateTime};
+use log::error;
+use sqlx::{PgPool, Postgres, QueryBuilder};
+use thiserror::Error;
+use tokio::sync::broadcast::{self, error::{RecvError, TryRecvError}};
+
+use crate::channel::WsFillChannel;
+use crate::engine::parser::{parse_fee, parse_price, parse_size, ParseError};
+use crate::hyperliquid::ws::WsFill;
+use crate::models::LeaderFill;
+
+/// Fills written per insert; 15 binds each stays far below Postgres' limit.
+const RECORD_BATCH: usize = 500;
+
+#[derive(Error, Debug)]
+pub enum LeaderFillError {
+    #[error("Database error: {0}")]
+    Database(#[from] sqlx::Error),
+    #[error("Parse error: {0}")]
+    Parse(#[from] ParseError),
+    #[error("Invalid fill time {0}")]
+    Time(u64),
+}
+
+pub fn to_leader_fill(leader: &str, fill: &WsFill) -> Result<LeaderFill, LeaderFillError> {
+    let filled_at = DateTime::from_timestamp_millis(fill.time as i64)
+        .ok_or(LeaderFillError::Time(fill.time))?
+        .naive_utc();
+    Ok(LeaderFill {
+        trader_address: leader.to_string(),
+        hash: fill.hash.clone(),
+        tid: fill.tid as i64,
+        oid: fill.oid as i64,
+        coin: fill.coin.clone(),
+        side: fill.side.clone(),
+        px: parse_price(&fill.px)?,
+        sz: parse_size(&fill.sz)?,
+        dir: fill.dir.clone(),
+        start_position: fill.start_position.as_deref().map(parse_size).transpose()?,
+        // realized PnL is signed like a fee
+        closed_pnl: fill.closed_pnl.as_deref().map(parse_fee).transpose()?.unwrap_or_default(),
+        fee: parse_fee(&fill.fee)?,
+        fee_token: fill.fee_token.clone(),
+        crossed: fill.crossed,
+        filled_at,
+    })
+}
+
+/// Store leader fills, skipping ones already stored; returns how many were new.
+pub async fn insert(pool: &PgPool, fills: &[LeaderFill]) -> Result<u64, sqlx::Error> {
+    let mut inserted = 0;
+    for batch in fills.chunks(RECORD_BATCH) {
+        let mut query = QueryBuilder::<Postgres>::new(
+            "INSERT INTO leader_fills (trader_address, hash, tid, oid, coin, side, px, sz, dir, \
+             start_position, closed_pnl, fee, fee_token, crossed, filled_at) ",
+        );
+        query.push_values(batch, |mut row, f| {
+            row.push_bind(&f.trader_address)
+                .push_bind(&f.hash)
+                .push_bind(f.tid)
+                .push_bind(f.oid)
+                .push_bind(&f.coin)
+                .push_bind(&f.side)
+                .push_bind(f.px)
+                .push_bind(f.sz)
+                .push_bind(&f.dir)
+                .push_bind(f.start_position)
+                .push_bind(f.closed_pnl)
+                .push_bind(f.fee)
+                .push_bind(&f.fee_token)
+                .push_bind(f.crossed)
+                .push_bind(f.filled_at);
+        });
+        query.push(" ON CONFLICT DO NOTHING");
+        inserted += query.build().execute(pool).await?.rows_affected();
+    }
+    Ok(inserted)
+}
+
+/// A leader's fills since `since`, oldest first.
+pub async fn fills_since(pool: &PgPool, leader: &str, since: NaiveDateTime) -> Result<Vec<LeaderFill>, sqlx::Error> {
+    sqlx::query_as::<_, LeaderFill>(
+        "SELECT trader_address, hash, tid, oid, coin, side, px, sz, dir, start_position,
+                closed_pnl, fee, fee_token, crossed, filled_at
+         FROM leader_fills WHERE trader_address = $1 AND filled_at > $2
+         ORDER BY filled_at, tid",
+    )
+    .bind(leader)
+    .bind(since)
+    .fetch_all(pool)
+    .await
+}
+
+/// Record every leader fill from ingest until the fill channel closes. Fills that
+/// arrive together are written in one insert.
+pub async fn record(mut rx: broadcast::Receiver<WsFillChannel>, pool: PgPool) -> Result<(), LeaderFillError> {
+    loop {
+        let mut batch = match rx.recv().await {
+            Ok(fill) => vec![fill],
+            Err(RecvError::Lagged(skipped)) => {
+                error!("Fill recorder lagging behind ingest — {} fills were not recorded", skipped);
+                continue;
+            }
+            Err(RecvError::Closed) => return Ok(()),
+        };
+        while batch.len() < RECORD_BATCH {
+            match rx.try_recv() {
+                Ok(fill) => batch.push(fill),
+                Err(TryRecvError::Lagged(skipped)) => {
+                    error!("Fill recorder lagging behind ingest — {} fills were not recorded", skipped);
+                }
+                Err(_) => break,
+            }
+        }
+
+        let fills: Vec<LeaderFill> = batch
+            .iter()
+            .filter_map(|f| match to_leader_fill(&f.user, &f.fill) {
+                Ok(fill) => Some(fill),
+                Err(e) => {
+                    error!("Not recording fill {} of {}: {}", f.fill.hash, f.user, e);
+                    None
+                }
+            })
+            .collect();
+        insert(&pool, &fills).await?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn ws_fill() -> WsFill {
+        WsFill {
+            coin: "BTC".to_string(),
+            px: "50000.5".to_string(),
+            sz: "0.2".to_string(),
+            side: "A".to_string(),
+            time: 1_700_000_000_123,
+            hash: "0xabc".to_string(),
+            oid: 7,
+            tid: 11,
+            start_position: Some("0.5".to_string()),
+            closed_pnl: Some("-12.5".to_string()),
+            dir: Some("Close Long".to_string()),
+            crossed: true,
+            fee: "-0.01".to_string(),
+            fee_token: "USDC".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_to_leader_fill() {
+        let fill = to_leader_fill("0xleader", &ws_fill()).unwrap();
+        assert_eq!((fill.px, fill.sz, fill.start_position), (dec!(50000.5), dec!(0.2), Some(dec!(0.5))));
+        assert_eq!((fill.closed_pnl, fill.fee), (dec!(-12.5), dec!(-0.01)));
+        assert_eq!(fill.filled_at.and_utc().timestamp_millis(), 1_700_000_000_123);
+        assert_eq!(fill.net_pnl(), dec!(-12.49));
+
+        let open = WsFill { closed_pnl: None, dir: Some("Open Short".to_string()), ..ws_fill() };
+        assert_eq!(to_leader_fill("0xleader", &open).unwrap().closed_pnl, dec!(0));
+
+        let broken = WsFill { sz: "lots".to_string(), ..ws_fill() };
+        assert!(matches!(to_leader_fill("0xleader", &broken), Err(LeaderFillError::Parse(ParseError::Size(_)))));
+    }
+}
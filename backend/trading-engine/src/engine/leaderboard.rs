@@ -1,378 +1,373 @@
+use chrono::{DateTime, Duration, NaiveDate, NaiveDateTime, Utc};
+use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
-use rust_decimal::{Decimal, MathematicalOps};
-use rust_decimal_macros::dec;
-use std::collections::HashMap;
-
-#[derive(Debug, sqlx::FromRow)]
-struct TradeRecord {
-    trader_address: String,
-    closed_pnl: Option<Decimal>,
-    entry_px: Decimal,
-    exit_px: Decimal,
-    sz: Decimal,
-    is_long: bool,
-    timestamp: chrono::DateTime<chrono::Utc>,
+use sqlx::types::Json;
+
+use crate::engine::metrics::{self, RoundTrips, Totals};
+use crate::engine::{equity, leader_fills};
+use crate::models::{EquitySnapshot, LeaderFill};
+
+/// Fills this old are taken to be all recorded, and their days are folded into
+/// the all-time totals.
+const SETTLE: Duration = Duration::hours(1);
+
+/// The periods every leader is ranked over (`leaderboard.time_window`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Window {
+    Day,
+    Week,
+    #[default]
+    Month,
+    Quarter,
+    /// Since the leader's first recorded fill
+    All,
 }
 
-pub async fn update_all_leaderboards(pool: &PgPool) -> anyhow::Result<()> {
-    let traders = sqlx::query_scalar::<_, String>(
-        "SELECT DISTINCT trader_address FROM executed_trades WHERE timestamp > NOW() - INTERVAL '90 days'"
-    )
-    .fetch_all(pool)
-    .await?;
+impl Window {
+    pub const EVERY: [Window; 5] = [Window::Day, Window::Week, Window::Month, Window::Quarter, Window::All];
 
-    for trader in traders {
-        let metrics = calculate_trader_metrics(pool, &trader).await?;
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Window::Day => "24h",
+            Window::Week => "7d",
+            Window::Month => "30d",
+            Window::Quarter => "90d",
+            Window::All => "all",
+        }
+    }
 
-        sqlx::query(
-            r#"
-            INSERT INTO leaderboard (
-                trader_address, pnl_percent_30d, win_rate, sharpe, max_drawdown,
-                followers_count, volume_7d, updated_at
-            ) VALUES ($1, $2, $3, $4, $5, $6, $7, NOW())
-            ON CONFLICT (trader_address) DO UPDATE SET
-                pnl_percent_30d = EXCLUDED.pnl_percent_30d,
-                win_rate = EXCLUDED.win_rate,
-                sharpe = EXCLUDED.sharpe,
-                max_drawdown = EXCLUDED.max_drawdown,
-                followers_count = EXCLUDED.followers_count,
-                volume_7d = EXCLUDED.volume_7d,
-                updated_at = NOW()
-            "#
-        )
-        .bind(&trader)
-        .bind(metrics.pnl_percent_30d)
-        .bind(metrics.win_rate)
-        .bind(metrics.sharpe)
-        .bind(metrics.max_drawdown)
-        .bind(metrics.followers_count as i32)
-        .bind(metrics.volume_7d)
-        .execute(pool)
-        .await?;
+    pub fn parse(window: &str) -> Option<Self> {
+        Self::EVERY.into_iter().find(|w| w.as_str() == window)
     }
 
-    Ok(())
+    fn duration(&self) -> Option<Duration> {
+        match self {
+            Window::Day => Some(Duration::days(1)),
+            Window::Week => Some(Duration::days(7)),
+            Window::Month => Some(Duration::days(30)),
+            Window::Quarter => Some(Duration::days(90)),
+            Window::All => None,
+        }
+    }
 }
 
-struct TraderMetrics {
-    pnl_percent_30d: Decimal,
-    win_rate: Decimal,
-    sharpe: Decimal,
-    max_drawdown: Decimal,
-    followers_count: i64,
-    volume_7d: Decimal,
+/// All-time totals of a leader folded up to `folded_until`, a whole number of
+/// days after `anchor`, with the positions still open then. An update reads
+/// only the fills after it.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+struct Folded {
+    totals: Totals,
+    open: RoundTrips,
 }
 
-async fn calculate_trader_metrics(pool: &PgPool, trader: &str) -> anyhow::Result<TraderMetrics> {
-    // 30-day PnL %
-    let pnl_30d: Decimal = sqlx::query_scalar(
-        "SELECT COALESCE(SUM(closed_pnl), 0) FROM executed_trades
-         WHERE trader_address = $1 AND timestamp > NOW() - INTERVAL '30 days'"
-    )
-    .bind(trader)
-    .fetch_one(pool)
-    .await?;
-
-    // Approximate initial capital from average position size (or use known deposit data)
-    let avg_position: Decimal = sqlx::query_scalar(
-        "SELECT COALESCE(AVG(sz * entry_px), 100000) FROM executed_trades
-         WHERE trader_address = $1 AND timestamp > NOW() - INTERVAL '90 days'"
-    )
-    .bind(trader)
-    .fetch_one(pool)
-    .await?;
-
-    let pnl_percent_30d = if avg_position > dec!(0) {
-        (pnl_30d / avg_position) * dec!(100)
-    } else {
-        dec!(0)
-    };
-
-    // Win rate
-    let wins: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM executed_trades
-         WHERE trader_address = $1 AND closed_pnl > 0 AND timestamp > NOW() - INTERVAL '30 days'"
-    )
-    .bind(trader)
-    .fetch_one(pool)
-    .await?;
-
-    let total: i64 = sqlx::query_scalar(
-        "SELECT COUNT(*) FROM executed_trades
-         WHERE trader_address = $1 AND closed_pnl IS NOT NULL AND timestamp > NOW() - INTERVAL '30 days'"
-    )
-    .bind(trader)
-    .fetch_one(pool)
-    .await?;
-
-    let win_rate = if total > 0 {
-        Decimal::from(wins) / Decimal::from(total) * dec!(100)
-    } else {
-        dec!(0)
-    };
-
-    // Simplified Sharpe (daily returns std dev approximation)
-    let daily_returns: Vec<Decimal> = sqlx::query_scalar(
-        "SELECT SUM(closed_pnl) FROM executed_trades
-         WHERE trader_address = $1
-         GROUP BY DATE(timestamp)
-         ORDER BY DATE(timestamp) DESC
-         LIMIT 30"
-    )
-    .fetch_all(pool)
-    .await?;
-
-    let mean: Decimal = daily_returns.iter().sum::<Decimal>() / Decimal::from(daily_returns.len().max(1));
-    let variance: Decimal = daily_returns.iter()
-        .map(|r| (*r - mean).powi(2))
-        .sum::<Decimal>() / Decimal::from(daily_returns.len().max(1));
-    let sharpe = if variance > dec!(0) {
-        if let Some(variance_sqrt) = variance.sqrt(){
-            mean / variance_sqrt
-        }else{
-            dec!(0)
-        }
-    }else{
-        dec!(0)
-    };
-
-
-    // Max drawdown (simplified from equity curve)
-    let mut equity_curve = vec![dec!(0)];
-    let mut current = dec!(0);
-    let pnls: Vec<Decimal> = sqlx::query_scalar(
-        "SELECT closed_pnl FROM executed_trades
-         WHERE trader_address = $1 AND timestamp > NOW() - INTERVAL '90 days'
-         ORDER BY timestamp"
-    )
-    .fetch_all(pool)
-    .await?;
-
-    let mut peak = dec!(0);
-    let mut max_dd = dec!(0);
-    for pnl in pnls {
-        current += pnl;
-        equity_curve.push(current);
-        if current > peak {
-            peak = current;
-        }
-        let dd = (peak - current) / peak.max(dec!(1));
-        if dd > max_dd {
-            max_dd = dd;
+impl Folded {
+    /// Add the fills and days of `from..=to`, `from` being where the last fold ended.
+    fn fold(&mut self, fills: &[LeaderFill], equity: &[EquitySnapshot], from: NaiveDateTime, to: NaiveDateTime) {
+        for fill in fills.iter().filter(|f| f.filled_at > from && f.filled_at <= to) {
+            self.totals.volume += fill.notional();
+            if let Some(trade) = self.open.push(fill) {
+                self.totals.add_trade(&trade);
+            }
         }
+        self.totals.returns.extend(metrics::daily_returns(fills, equity, from, to));
     }
-
-    // Followers count
-    let followers_count: i64 = sqlx::query_scalar(
-        "SELECT COUNT(DISTINCT follower_id) FROM copy_configs
-         WHERE trader_address = $1 AND is_active = true"
-    )
-    .bind(trader)
-    .fetch_one(pool)
-    .await?;
-
-    // 7-day volume
-    let volume_7d: Decimal = sqlx::query_scalar(
-        "SELECT COALESCE(SUM(sz * exit_px), 0) FROM executed_trades
-         WHERE trader_address = $1 AND timestamp > NOW() - INTERVAL '7 days'"
-    )
-    .bind(trader)
-    .fetch_one(pool)
-    .await?;
-
-    Ok(TraderMetrics {
-        pnl_percent_30d: pnl_percent_30d.round_dp(2),
-        win_rate: win_rate.round_dp(2),
-        sharpe: sharpe.round_dp(3),
-        max_drawdown: (max_dd * dec!(100)).round_dp(2),
-        followers_count,
-        volume_7d,
-    })
 }
 
+#[derive(Debug, Clone)]
+struct AllTime {
+    /// Just before the leader's first fill; days are counted from here
+    anchor: NaiveDateTime,
+    folded_until: NaiveDateTime,
+    folded: Folded,
+}
 
-
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use sqlx::sqlite::SqlitePoolOptions;
-    use chrono::{Utc, Duration};
-    use rust_decimal_macros::dec;
-
-    async fn setup_test_db() -> sqlx::Pool<sqlx::Sqlite> {
-        let pool = SqlitePoolOptions::new()
-            .connect("sqlite::memory:")
-            .await
-            .unwrap();
-
-        // Create tables (SQLite-compatible schema)
-        pool.execute(
-            r#"
-            CREATE TABLE executed_trades (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            trader_address TEXT NOT NULL,
-            closed_pnl TEXT,  -- SQLite has no DECIMAL; store as TEXT and parse
-            entry_px TEXT NOT NULL,
-            exit_px TEXT NOT NULL,
-            sz TEXT NOT NULL,
-            is_long INTEGER NOT NULL,
-            timestamp TEXT NOT NULL
-            );
-            CREATE TABLE copy_configs (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            follower_id INTEGER,
-            trader_address TEXT NOT NULL,
-            is_active INTEGER DEFAULT 1
-            );
-            CREATE TABLE followers (
-            id INTEGER PRIMARY KEY AUTOINCREMENT,
-            address TEXT NOT NULL
-            );
-            CREATE TABLE leaderboard (
-            trader_address TEXT PRIMARY KEY,
-            pnl_percent_30d TEXT,
-            win_rate TEXT,
-            sharpe TEXT,
-            max_drawdown TEXT,
-            followers_count INTEGER,
-            volume_7d TEXT,
-            updated_at TEXT
-            );
-            "#
-        ).await.unwrap();
-
-        pool
+impl AllTime {
+    fn new(anchor: NaiveDateTime) -> Self {
+        Self { anchor, folded_until: anchor, folded: Folded::default() }
     }
 
-    #[tokio::test]
-    async fn test_leaderboard_basic_pnl_calculation() {
-        let pool = setup_test_db().await;
-        let trader = "0x123";
+    /// Fold in the whole days that ended before `now - SETTLE`; false if there were
+    /// none. Nothing is folded while the leader has no equity snapshot, as the
+    /// returns of those days are not known yet.
+    fn settle(&mut self, fills: &[LeaderFill], equity: &[EquitySnapshot], now: NaiveDateTime) -> bool {
+        let days = (now - SETTLE - self.folded_until).num_days();
+        if equity.is_empty() || days <= 0 {
+            return false;
+        }
+        let until = self.folded_until + Duration::days(days);
+        self.folded.fold(fills, equity, self.folded_until, until);
+        self.folded_until = until;
+        true
+    }
 
-        // Insert profitable trades
-        sqlx::query(
-            r#"
-            INSERT INTO executed_trades
-            (trader_address, closed_pnl, entry_px, exit_px, sz, is_long, timestamp)
-            VALUES
-            (?, 1000.0, 50000.0, 51000.0, 1.0, 1, ?),
-            (?, 500.0, 49000.0, 49500.0, 2.0, 1, ?)
-            "#
-        )
-        .bind(trader)
-        .bind(Utc::now())
-        .bind(trader)
-        .bind(Utc::now() - Duration::days(10))
-        .execute(&pool)
-        .await
-        .unwrap();
-
-        let metrics = calculate_trader_metrics(&pool, trader).await.unwrap();
-
-        assert!(metrics.pnl_percent_30d > dec!(0));
-        assert!(metrics.win_rate == dec!(100.0)); // both trades profitable
-        assert!(metrics.sharpe > dec!(0));
-        assert!(metrics.max_drawdown >= dec!(0));
+    /// The totals up to `now`: the folded ones and whatever came after.
+    fn totals(&self, fills: &[LeaderFill], equity: &[EquitySnapshot], now: NaiveDateTime) -> Totals {
+        let mut current = self.folded.clone();
+        current.fold(fills, equity, self.folded_until, now);
+        current.totals
     }
 
-    #[tokio::test]
-    async fn test_leaderboard_with_losses_and_drawdown() {
-        let pool = setup_test_db().await;
-        let trader = "0x456";
+    /// The stored totals of `leader`, unless fills were recorded since into days
+    /// already folded (a backfill), in which case they are rebuilt.
+    async fn load(pool: &PgPool, leader: &str) -> Result<Option<Self>, sqlx::Error> {
+        let row: Option<(NaiveDateTime, NaiveDateTime, Json<Folded>, bool)> = sqlx::query_as(
+            "SELECT anchor, folded_until, folded, EXISTS (
+                 SELECT 1 FROM leader_fills f
+                 WHERE f.trader_address = t.trader_address AND f.filled_at <= t.folded_until
+                   AND f.recorded_at > t.updated_at)
+             FROM leader_all_time_totals t WHERE trader_address = $1",
+        )
+        .bind(leader)
+        .fetch_optional(pool)
+        .await?;
 
-        let now = Utc::now();
+        Ok(match row {
+            Some((anchor, folded_until, folded, false)) => Some(Self { anchor, folded_until, folded: folded.0 }),
+            Some(_) => {
+                log::info!("Fills of {} were recorded into folded days, rebuilding its all-time totals", leader);
+                None
+            }
+            None => None,
+        })
+    }
 
-        // Simulate equity curve: +1000, +500, -800, +200 → peak 1500, trough 700 → drawdown ~53%
+    async fn save(&self, pool: &PgPool, leader: &str) -> Result<(), sqlx::Error> {
         sqlx::query(
-            r#"
-            INSERT INTO executed_trades
-            (trader_address, closed_pnl, entry_px, exit_px, sz, is_long, timestamp)
-            VALUES
-            (?, 1000.0, 100.0, 110.0, 10.0, 1, ?),
-            (?, 500.0, 110.0, 115.0, 10.0, 1, ?),
-            (?, -800.0, 115.0, 107.0, 10.0, 0, ?),
-            (?, 200.0, 107.0, 109.0, 10.0, 1, ?)
-            "#
+            "INSERT INTO leader_all_time_totals (trader_address, anchor, folded_until, folded, updated_at)
+             VALUES ($1, $2, $3, $4, NOW())
+             ON CONFLICT (trader_address) DO UPDATE SET
+                 anchor = EXCLUDED.anchor, folded_until = EXCLUDED.folded_until,
+                 folded = EXCLUDED.folded, updated_at = NOW()",
         )
-        .bind(trader).bind(now - Duration::days(20))
-        .bind(trader).bind(now - Duration::days(15))
-        .bind(trader).bind(now - Duration::days(10))
-        .bind(trader).bind(now - Duration::days(5))
-        .execute(&pool)
-        .await
-        .unwrap();
-
-        let metrics = calculate_trader_metrics(&pool, trader).await.unwrap();
-
-        assert!(metrics.pnl_percent_30d > dec!(0)); // net positive
-        assert!(metrics.win_rate == dec!(75.0)); // 3 wins, 1 loss
-        assert!(metrics.max_drawdown > dec!(40.0)); // should detect ~53%
+        .bind(leader)
+        .bind(self.anchor)
+        .bind(self.folded_until)
+        .bind(Json(&self.folded))
+        .execute(pool)
+        .await?;
+        Ok(())
     }
+}
 
-    #[tokio::test]
-    async fn test_no_trades_returns_zeros() {
-        let pool = setup_test_db().await;
-        let trader = "0x789";
+/// The earliest fill the metrics from `at` on depend on: the opening of every
+/// position still open at `at`, and the fills since the equity snapshot before it.
+async fn reading_start(pool: &PgPool, leader: &str, at: NaiveDateTime) -> Result<NaiveDateTime, sqlx::Error> {
+    let start: Option<NaiveDateTime> = sqlx::query_scalar(
+        r#"
+        WITH positions AS (
+            SELECT DISTINCT ON (coin) coin,
+                start_position + CASE WHEN side = 'B' THEN sz ELSE -sz END AS position
+            FROM leader_fills
+            WHERE trader_address = $1 AND filled_at <= $2 AND start_position IS NOT NULL
+            ORDER BY coin, filled_at DESC, tid DESC
+        ),
+        openings AS (
+            -- from flat, or a flip
+            SELECT MAX(f.filled_at) AS opened_at
+            FROM leader_fills f JOIN positions p ON p.coin = f.coin
+            WHERE f.trader_address = $1 AND f.filled_at <= $2 AND p.position <> 0
+              AND (f.start_position = 0
+                   OR f.start_position * (f.start_position + CASE WHEN f.side = 'B' THEN f.sz ELSE -f.sz END) < 0)
+            GROUP BY f.coin
+        )
+        SELECT LEAST(
+            $2,
+            (SELECT MIN(opened_at) FROM openings),
+            (SELECT MAX(recorded_at) FROM leader_equity WHERE trader_address = $1 AND recorded_at <= $2))
+        "#,
+    )
+    .bind(leader)
+    .bind(at)
+    .fetch_one(pool)
+    .await?;
+    Ok(start.unwrap_or(at))
+}
 
-        let metrics = calculate_trader_metrics(&pool, trader).await.unwrap();
+/// Rank every active leader with recorded fills over every window. Only the fills
+/// the longest bounded window depends on are read; the all-time window adds the
+/// ones since its stored totals.
+pub async fn update_all_leaderboards(pool: &PgPool) -> anyhow::Result<()> {
+    let now = Utc::now().naive_utc();
+    let horizon = now - Window::EVERY.iter().filter_map(Window::duration).max().unwrap_or_default();
 
-        assert_eq!(metrics.pnl_percent_30d, dec!(0));
-        assert_eq!(metrics.win_rate, dec!(0));
-        assert_eq!(metrics.sharpe, dec!(0));
-        assert_eq!(metrics.max_drawdown, dec!(0));
-        assert_eq!(metrics.followers_count, 0);
-        assert_eq!(metrics.volume_7d, dec!(0));
+    let removed = sqlx::query(
+        "DELETE FROM leaderboard WHERE trader_address NOT IN (SELECT address FROM traders WHERE is_active = true)",
+    )
+    .execute(pool)
+    .await?
+    .rows_affected();
+    if removed > 0 {
+        log::info!("Removed {} leaderboard rows of inactive traders", removed);
     }
 
-    #[tokio::test]
-    async fn test_followers_count() {
-        let pool = setup_test_db().await;
-        let trader = "0xfollowed";
+    let traders = sqlx::query_scalar::<_, String>(
+        "SELECT address FROM traders t
+         WHERE is_active = true AND EXISTS (SELECT 1 FROM leader_fills f WHERE f.trader_address = t.address)",
+    )
+    .fetch_all(pool)
+    .await?;
 
-        // Insert followers
-        sqlx::query(
-            "INSERT INTO followers (address) VALUES ('0xf1'), ('0xf2')"
-        ).execute(&pool).await.unwrap();
+    for trader in traders {
+        let stored = AllTime::load(pool, &trader).await?;
+        let read_from = match &stored {
+            // trades closed in a window may have opened long before it
+            Some(all_time) => reading_start(pool, &trader, horizon.min(all_time.folded_until)).await? - Duration::milliseconds(1),
+            None => DateTime::UNIX_EPOCH.naive_utc(),
+        };
+        let fills = leader_fills::fills_since(pool, &trader, read_from).await?;
+        let mut all_time = match stored {
+            Some(all_time) => all_time,
+            None => match fills.first() {
+                Some(first) => AllTime::new(first.filled_at - Duration::milliseconds(1)),
+                None => continue,
+            },
+        };
+        let snapshots = equity::snapshots_since(pool, &trader, read_from).await?;
+        if all_time.settle(&fills, &snapshots, now) {
+            all_time.save(pool, &trader).await?;
+        }
 
-        sqlx::query(
-            "INSERT INTO copy_configs (follower_id, trader_address, is_active)
-            VALUES (1, ?, 1), (2, ?, 1)"
+        let followers_count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(DISTINCT follower_id) FROM copy_configs
+             WHERE trader_address = $1 AND is_active = true"
         )
-        .bind(trader)
-        .bind(trader)
-        .execute(&pool)
-        .await
-        .unwrap();
-
-        let metrics = calculate_trader_metrics(&pool, trader).await.unwrap();
+        .bind(&trader)
+        .fetch_one(pool)
+        .await?;
 
-        assert_eq!(metrics.followers_count, 2);
+        for window in Window::EVERY {
+            let metrics = match window.duration() {
+                Some(d) => metrics::compute(&fills, &snapshots, now - d, now),
+                None => all_time.totals(&fills, &snapshots, now).metrics(),
+            };
+
+            sqlx::query(
+                r#"
+                INSERT INTO leaderboard (
+                    trader_address, time_window, return_pct, win_rate, sharpe, max_drawdown,
+                    followers_count, volume, sortino, calmar, profit_factor,
+                    avg_hold_secs, expectancy, trades, updated_at
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, NOW())
+                ON CONFLICT (trader_address, time_window) DO UPDATE SET
+                    return_pct = EXCLUDED.return_pct,
+                    win_rate = EXCLUDED.win_rate,
+                    sharpe = EXCLUDED.sharpe,
+                    max_drawdown = EXCLUDED.max_drawdown,
+                    followers_count = EXCLUDED.followers_count,
+                    volume = EXCLUDED.volume,
+                    sortino = EXCLUDED.sortino,
+                    calmar = EXCLUDED.calmar,
+                    profit_factor = EXCLUDED.profit_factor,
+                    avg_hold_secs = EXCLUDED.avg_hold_secs,
+                    expectancy = EXCLUDED.expectancy,
+                    trades = EXCLUDED.trades,
+                    updated_at = NOW()
+                "#
+            )
+            .bind(&trader)
+            .bind(window.as_str())
+            .bind(metrics.return_pct)
+            .bind(metrics.win_rate)
+            .bind(metrics.sharpe)
+            .bind(metrics.max_drawdown)
+            .bind(followers_count as i32)
+            .bind(metrics.volume)
+            .bind(metrics.sortino)
+            .bind(metrics.calmar)
+            .bind(metrics.profit_factor)
+            .bind(metrics.avg_hold_secs)
+            .bind(metrics.expectancy)
+            .bind(metrics.trades as i32)
+            .execute(pool)
+            .await?;
+        }
     }
 
-    #[tokio::test]
-    async fn test_update_all_leaderboards() {
-        let pool = setup_test_db().await;
-        let trader1 = "0xt1";
-        let trader2 = "0xt2";
+    let snapshotted = snapshot(pool, now.date()).await?;
+    log::info!("Snapshotted {} leaderboard rows", snapshotted);
+    Ok(())
+}
 
-        // Insert some trades for trader1
-        sqlx::query(
-            "INSERT INTO executed_trades
-            (trader_address, closed_pnl, entry_px, exit_px, sz, is_long, timestamp)
-            VALUES (?, 1000.0, 100.0, 110.0, 10.0, 1, DATETIME('now'))"
+/// Copy the leaderboard, ranked by return within each window, into the history
+/// for `date`. A day already snapshotted is left as it was.
+async fn snapshot(pool: &PgPool, date: NaiveDate) -> Result<u64, sqlx::Error> {
+    let inserted = sqlx::query(
+        r#"
+        INSERT INTO leaderboard_snapshots (
+            snapshot_date, time_window, trader_address, rank, return_pct, win_rate, sharpe,
+            sortino, calmar, max_drawdown, profit_factor, avg_hold_secs, expectancy, trades,
+            followers_count, volume
         )
-        .bind(trader1)
-        .execute(&pool)
-        .await
-        .unwrap();
+        SELECT $1, time_window, trader_address,
+            ROW_NUMBER() OVER (PARTITION BY time_window ORDER BY return_pct DESC NULLS LAST, trader_address),
+            return_pct, win_rate, sharpe, sortino, calmar, max_drawdown, profit_factor,
+            avg_hold_secs, expectancy, trades, followers_count, volume
+        FROM leaderboard
+        ON CONFLICT DO NOTHING
+        "#
+    )
+    .bind(date)
+    .execute(pool)
+    .await?;
+    Ok(inserted.rows_affected())
+}
 
-        update_all_leaderboards(&pool).await.unwrap();
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal::Decimal;
+    use rust_decimal_macros::dec;
 
-        let count: (i64,) = sqlx::query_as("SELECT COUNT(*) FROM leaderboard")
-            .fetch_one(&pool)
-            .await
-            .unwrap();
+    fn fill(side: &str, start: Decimal, closed_pnl: Decimal, filled_at: NaiveDateTime) -> LeaderFill {
+        LeaderFill {
+            trader_address: "0x123".to_string(),
+            hash: "0xhash".to_string(),
+            tid: 1,
+            oid: 1,
+            coin: "BTC".to_string(),
+            side: side.to_string(),
+            px: dec!(100),
+            sz: dec!(1),
+            dir: None,
+            start_position: Some(start),
+            closed_pnl,
+            fee: dec!(1),
+            fee_token: "USDC".to_string(),
+            crossed: true,
+            filled_at,
+        }
+    }
 
-        assert!(count.0 >= 1); // at least one entry
+    #[test]
+    fn test_folded_all_time_matches_a_full_recompute() {
+        let now = Utc::now().naive_utc();
+        let first = now - Duration::days(5);
+        let fills = [
+            fill("B", dec!(0), dec!(0), first),
+            fill("A", dec!(1), dec!(50), first + Duration::days(1)),
+            // opened before the first fold, closed after it
+            fill("A", dec!(0), dec!(0), first + Duration::hours(40)),
+            fill("B", dec!(-1), dec!(-20), now - Duration::hours(2)),
+            // not settled yet
+            fill("B", dec!(0), dec!(0), now - Duration::minutes(10)),
+        ];
+        let equity = [EquitySnapshot { account_value: dec!(1000), recorded_at: first + Duration::hours(12) }];
+
+        let mut all_time = AllTime::new(first - Duration::milliseconds(1));
+        let midway = now - Duration::days(3);
+        assert!(all_time.settle(&fills, &equity, midway));
+        assert_eq!(all_time.folded_until, all_time.anchor + Duration::days(1));
+        assert_eq!(all_time.folded.totals.trades, 0);
+        assert!(all_time.settle(&fills, &equity, now));
+        assert!(!all_time.settle(&fills, &equity, now));
+        assert_eq!(all_time.folded.totals.trades, 1);
+
+        let expected = metrics::compute(&fills, &equity, all_time.anchor, now);
+        assert_eq!(expected.trades, 2);
+        assert_eq!(all_time.totals(&fills, &equity, now).metrics(), expected);
+
+        // no equity yet: nothing can be folded
+        let mut unknown = AllTime::new(first - Duration::milliseconds(1));
+        assert!(!unknown.settle(&fills, &[], now));
     }
 }
@@ -0,0 +1,181 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use log::{info, warn};
+use rust_decimal::{Decimal, RoundingStrategy};
+use rust_decimal_macros::dec;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::exchange::{Exchange, ExchangeError};
+
+/// How often the universe is reloaded to pick up listings and parameter changes.
+pub const METADATA_REFRESH: Duration = Duration::from_secs(3600);
+/// Least time between reloads for coins not in the universe, so fills of an
+/// unlisted or delisted coin cannot flood the info endpoint.
+const UNKNOWN_RELOAD_INTERVAL: Duration = Duration::from_secs(60);
+/// Hyperliquid accepts at most five significant figures in a price.
+const PRICE_SIG_FIGS: u32 = 5;
+/// Smallest order value the exchange accepts, in USD.
+pub const MIN_NOTIONAL: Decimal = dec!(10);
+
+#[derive(Error, Debug)]
+pub enum MetadataError {
+    #[error("Failed to load asset metadata: {0}")]
+    Exchange(#[from] ExchangeError),
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+}
+
+/// Trading rules of one perp or spot asset.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AssetInfo {
+    pub sz_decimals: u32,
+    /// Most decimals a price may have: 6 - szDecimals for perps, 8 - szDecimals for spot
+    pub max_price_decimals: u32,
+    /// `None` for spot
+    pub max_leverage: Option<u32>,
+}
+
+impl AssetInfo {
+    pub fn perp(sz_decimals: u32, max_leverage: u32) -> Self {
+        Self {
+            sz_decimals,
+            max_price_decimals: 6u32.saturating_sub(sz_decimals),
+            max_leverage: Some(max_leverage),
+        }
+    }
+
+    pub fn spot(sz_decimals: u32) -> Self {
+        Self {
+            sz_decimals,
+            max_price_decimals: 8u32.saturating_sub(sz_decimals),
+            max_leverage: None,
+        }
+    }
+
+    /// Round a size down to the asset's lot size.
+    pub fn round_size(&self, sz: Decimal) -> Decimal {
+        sz.round_dp_with_strategy(self.sz_decimals, RoundingStrategy::ToZero).normalize()
+    }
+
+    /// Round a price to five significant figures and the asset's tick, towards the
+    /// side that never pays more (buys) or receives less (sells) than `px`.
+    /// Integer prices are always valid, whatever their number of digits.
+    pub fn round_price(&self, px: Decimal, is_buy: bool) -> Decimal {
+        let strategy = if is_buy { RoundingStrategy::ToZero } else { RoundingStrategy::AwayFromZero };
+        let px = if px.abs() >= dec!(100000) {
+            px.round_dp_with_strategy(0, strategy)
+        } else {
+            px.round_sf_with_strategy(PRICE_SIG_FIGS, strategy).unwrap_or(px)
+        };
+        px.round_dp_with_strategy(self.max_price_decimals, strategy).normalize()
+    }
+}
+
+/// Perp and spot universe of the exchange, keyed by the coin names fills and orders use.
+pub struct AssetMetadata {
+    exchange: Arc<dyn Exchange>,
+    assets: RwLock<HashMap<String, AssetInfo>>,
+    /// Last reload for a coin that was not known
+    unknown_reloaded_at: Mutex<Option<Instant>>,
+}
+
+impl AssetMetadata {
+    /// Load the universe; the engine cannot size orders without it.
+    pub async fn load(exchange: Arc<dyn Exchange>) -> Result<Self, MetadataError> {
+        let metadata = Self {
+            exchange,
+            assets: RwLock::new(HashMap::new()),
+            unknown_reloaded_at: Mutex::new(None),
+        };
+        metadata.refresh().await?;
+        Ok(metadata)
+    }
+
+    pub async fn refresh(&self) -> Result<usize, MetadataError> {
+        let assets = self.exchange.universe().await?;
+        let count = assets.len();
+        *self.assets.write().await = assets;
+        info!("Loaded metadata for {} assets", count);
+        Ok(count)
+    }
+
+    /// Rules for `coin`, reloading if it is not known yet (e.g. a new listing) and
+    /// no such reload happened in the last [`UNKNOWN_RELOAD_INTERVAL`].
+    pub async fn asset(&self, coin: &str) -> Result<AssetInfo, MetadataError> {
+        if let Some(asset) = self.assets.read().await.get(coin) {
+            return Ok(*asset);
+        }
+
+        let reload = {
+            let mut reloaded_at = self.unknown_reloaded_at.lock().unwrap_or_else(|e| e.into_inner());
+            let due = reloaded_at.is_none_or(|at| at.elapsed() >= UNKNOWN_RELOAD_INTERVAL);
+            if due {
+                *reloaded_at = Some(Instant::now());
+            }
+            due
+        };
+        if !reload {
+            return Err(MetadataError::UnknownAsset(coin.to_string()));
+        }
+
+        warn!("No metadata for {} — reloading universe", coin);
+        self.refresh().await?;
+        self.assets
+            .read()
+            .await
+            .get(coin)
+            .copied()
+            .ok_or_else(|| MetadataError::UnknownAsset(coin.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::exchange::mock::MockExchange;
+
+    #[tokio::test]
+    async fn test_unknown_coins_reload_at_most_once_per_interval() {
+        let exchange = Arc::new(MockExchange::new().with_asset("BTC", AssetInfo::perp(5, 40)));
+        let metadata = AssetMetadata::load(exchange.clone()).await.unwrap();
+        assert!(matches!(metadata.asset("NEW").await, Err(MetadataError::UnknownAsset(_))));
+
+        // listed right after the reload: not seen until the next one is due
+        exchange.add_asset("NEW", AssetInfo::perp(1, 3));
+        assert!(matches!(metadata.asset("NEW").await, Err(MetadataError::UnknownAsset(_))));
+
+        metadata.refresh().await.unwrap();
+        assert_eq!(metadata.asset("NEW").await.unwrap(), AssetInfo::perp(1, 3));
+    }
+
+    #[test]
+    fn test_round_size() {
+        let btc = AssetInfo::perp(5, 40);
+        assert_eq!(btc.round_size(dec!(0.123456789)), dec!(0.12345));
+        assert_eq!(btc.round_size(dec!(0.000004)), dec!(0));
+
+        let doge = AssetInfo::perp(0, 10);
+        assert_eq!(doge.round_size(dec!(152.9)), dec!(152));
+    }
+
+    #[test]
+    fn test_round_price() {
+        // BTC: 1 price decimal, five significant figures
+        let btc = AssetInfo::perp(5, 40);
+        assert_eq!(btc.round_price(dec!(50123.47), true), dec!(50123));
+        assert_eq!(btc.round_price(dec!(50123.47), false), dec!(50124));
+        assert_eq!(btc.round_price(dec!(123456.7), true), dec!(123456));
+
+        // sz 2 perp: at most 4 decimals even when 5 significant figures would allow more
+        let sol = AssetInfo::perp(2, 20);
+        assert_eq!(sol.round_price(dec!(0.0123456), true), dec!(0.0123));
+        assert_eq!(sol.round_price(dec!(0.0123456), false), dec!(0.0124));
+        assert_eq!(sol.round_price(dec!(1.234567), true), dec!(1.2345));
+
+        // spot allows two more decimals than a perp with the same szDecimals
+        let purr = AssetInfo::spot(0);
+        assert_eq!(purr.round_price(dec!(0.000123456), true), dec!(0.00012345));
+    }
+}
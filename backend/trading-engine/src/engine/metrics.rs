@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+use chrono::{Duration, NaiveDateTime};
+use rust_decimal::{Decimal, MathematicalOps};
+use rust_decimal_macros::dec;
+use serde::{Deserialize, Serialize};
+
+use crate::models::{EquitySnapshot, LeaderFill};
+
+/// Crypto trades every day of the year.
+const DAYS_PER_YEAR: Decimal = dec!(365);
+/// Ratios over a near-zero denominator are capped so they fit the leaderboard.
+const RATIO_CAP: Decimal = dec!(999999);
+
+/// Performance of a leader over one window. Return-based figures need at least
+/// one equity snapshot and are `None` without.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metrics {
+    /// Time-weighted return, percent
+    pub return_pct: Option<Decimal>,
+    /// Annualized, from daily returns
+    pub sharpe: Option<Decimal>,
+    /// Annualized, against downside deviation only
+    pub sortino: Option<Decimal>,
+    /// Annualized return over max drawdown
+    pub calmar: Option<Decimal>,
+    /// Deepest fall of the return index from its peak, percent
+    pub max_drawdown: Option<Decimal>,
+    /// Round trips closed in the window
+    pub trades: i64,
+    /// Share of winning trades, percent
+    pub win_rate: Decimal,
+    /// Gross profit over gross loss of the trades
+    pub profit_factor: Option<Decimal>,
+    pub avg_hold_secs: Option<i64>,
+    /// Mean PnL per trade
+    pub expectancy: Option<Decimal>,
+    /// Notional traded
+    pub volume: Decimal,
+}
+
+/// A position from flat (or a flip) back to flat (or the next flip).
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Trade {
+    pub opened_at: NaiveDateTime,
+    pub closed_at: NaiveDateTime,
+    /// Realized PnL net of every fee paid in it
+    pub pnl: Decimal,
+}
+
+/// What a window's metrics are made of. Totals can be extended trade by trade
+/// and day by day, so all-time figures need not be rebuilt from every fill.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Totals {
+    /// Notional traded
+    pub volume: Decimal,
+    pub trades: i64,
+    pub wins: i64,
+    pub gross_profit: Decimal,
+    pub gross_loss: Decimal,
+    pub held_secs: i64,
+    pub pnl: Decimal,
+    /// Realized return of each day, oldest first
+    pub returns: Vec<Decimal>,
+}
+
+impl Totals {
+    pub fn add_trade(&mut self, trade: &Trade) {
+        self.trades += 1;
+        if trade.pnl > dec!(0) {
+            self.wins += 1;
+            self.gross_profit += trade.pnl;
+        } else if trade.pnl < dec!(0) {
+            self.gross_loss -= trade.pnl;
+        }
+        self.held_secs += (trade.closed_at - trade.opened_at).num_seconds();
+        self.pnl += trade.pnl;
+    }
+
+    pub fn metrics(&self) -> Metrics {
+        let mut metrics = Metrics { volume: self.volume, ..Metrics::default() };
+
+        if self.trades > 0 {
+            let count = Decimal::from(self.trades);
+            metrics.trades = self.trades;
+            metrics.win_rate = (Decimal::from(self.wins) / count * dec!(100)).round_dp(2);
+            metrics.profit_factor = (self.gross_loss > dec!(0)).then(|| cap(self.gross_profit / self.gross_loss).round_dp(3));
+            metrics.avg_hold_secs = Some(self.held_secs / self.trades);
+            metrics.expectancy = Some((self.pnl / count).round_dp(8));
+        }
+
+        let returns = &self.returns;
+        if returns.is_empty() {
+            return metrics;
+        }
+
+        let mut index = dec!(1);
+        let mut peak = dec!(1);
+        let mut max_dd = dec!(0);
+        for r in returns {
+            index *= dec!(1) + r;
+            peak = peak.max(index);
+            max_dd = max_dd.max((peak - index) / peak);
+        }
+        let twr = index - dec!(1);
+        metrics.return_pct = Some(cap(twr * dec!(100)).round_dp(2));
+        metrics.max_drawdown = Some((max_dd * dec!(100)).round_dp(2));
+
+        let n = Decimal::from(returns.len());
+        let mean = returns.iter().sum::<Decimal>() / n;
+        let std_dev = (returns.iter().map(|r| (*r - mean).powi(2)).sum::<Decimal>() / n).sqrt();
+        let downside = (returns.iter().map(|r| (*r).min(dec!(0)).powi(2)).sum::<Decimal>() / n).sqrt();
+        let annualize = DAYS_PER_YEAR.sqrt().unwrap_or_default();
+        metrics.sharpe = std_dev.filter(|s| *s > dec!(0)).map(|s| cap(mean / s * annualize).round_dp(3));
+        metrics.sortino = downside.filter(|d| *d > dec!(0)).map(|d| cap(mean / d * annualize).round_dp(3));
+
+        if max_dd > dec!(0) {
+            // compounded to a year; a wiped-out account stays at -100%
+            let annual_return = if index > dec!(0) {
+                index.checked_powd(DAYS_PER_YEAR / n).map(|g| g - dec!(1)).unwrap_or(RATIO_CAP)
+            } else {
+                dec!(-1)
+            };
+            metrics.calmar = Some(cap(annual_return / max_dd).round_dp(3));
+        }
+        metrics
+    }
+}
+
+/// Metrics over `from..=to` from a leader's fills, oldest first, and equity
+/// snapshots, oldest first. Fills before `from` only serve to find where the
+/// trades closed in the window were opened.
+///
+/// A day's return is its realized PnL net of fees over the equity at its start,
+/// so deposits and withdrawals do not count as performance. Equity between
+/// snapshots is carried forward (or, before the first one, backward) by PnL.
+pub fn compute(fills: &[LeaderFill], equity: &[EquitySnapshot], from: NaiveDateTime, to: NaiveDateTime) -> Metrics {
+    let window = |at: NaiveDateTime| at > from && at <= to;
+    let mut totals = Totals {
+        volume: fills.iter().filter(|f| window(f.filled_at)).map(LeaderFill::notional).sum(),
+        returns: daily_returns(fills, equity, from, to),
+        ..Totals::default()
+    };
+    for trade in round_trips(fills).iter().filter(|t| window(t.closed_at)) {
+        totals.add_trade(trade);
+    }
+    totals.metrics()
+}
+
+/// Positions opened in the fills so far and not closed yet, so round trips can
+/// be followed across batches of fills.
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct RoundTrips {
+    open: HashMap<String, Trade>,
+}
+
+impl RoundTrips {
+    /// Follow the next fill; returns the trade it completes, if any.
+    pub fn push(&mut self, fill: &LeaderFill) -> Option<Trade> {
+        let start = fill.start_position?;
+        let end = if fill.side == "B" { start + fill.sz } else { start - fill.sz };
+        let opening = Trade {
+            opened_at: fill.filled_at,
+            closed_at: fill.filled_at,
+            pnl: dec!(0),
+        };
+
+        if start.is_zero() {
+            self.open.insert(fill.coin.clone(), Trade { pnl: fill.net_pnl(), ..opening });
+            return None;
+        }
+        if let Some(trade) = self.open.get_mut(&fill.coin) {
+            trade.pnl += fill.net_pnl();
+        }
+        let flipped = !end.is_zero() && start.is_sign_positive() != end.is_sign_positive();
+        if !end.is_zero() && !flipped {
+            return None;
+        }
+        let closed = self.open.remove(&fill.coin).map(|trade| Trade { closed_at: fill.filled_at, ..trade });
+        if flipped {
+            self.open.insert(fill.coin.clone(), opening);
+        }
+        closed
+    }
+}
+
+/// Every completed round trip in the fills. Positions already open when the
+/// fills start have an unknown opening and are left out.
+pub fn round_trips(fills: &[LeaderFill]) -> Vec<Trade> {
+    let mut open = RoundTrips::default();
+    fills.iter().filter_map(|fill| open.push(fill)).collect()
+}
+
+/// Realized return of each day in the window whose starting equity is known
+/// and positive.
+pub fn daily_returns(fills: &[LeaderFill], equity: &[EquitySnapshot], from: NaiveDateTime, to: NaiveDateTime) -> Vec<Decimal> {
+    let pnl_between = |after: NaiveDateTime, until: NaiveDateTime| -> Decimal {
+        fills.iter().filter(|f| f.filled_at > after && f.filled_at <= until).map(LeaderFill::net_pnl).sum()
+    };
+    let equity_at = |at: NaiveDateTime| -> Option<Decimal> {
+        match equity.iter().rev().find(|s| s.recorded_at <= at) {
+            Some(before) => Some(before.account_value + pnl_between(before.recorded_at, at)),
+            None => equity.first().map(|after| after.account_value - pnl_between(at, after.recorded_at)),
+        }
+    };
+
+    let mut returns = Vec::new();
+    let mut day_start = from;
+    while day_start < to {
+        let day_end = (day_start + Duration::days(1)).min(to);
+        if let Some(start_equity) = equity_at(day_start).filter(|e| *e > dec!(0)) {
+            returns.push(pnl_between(day_start, day_end) / start_equity);
+        }
+        day_start = day_end;
+    }
+    returns
+}
+
+fn cap(value: Decimal) -> Decimal {
+    value.clamp(-RATIO_CAP, RATIO_CAP)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn fill(coin: &str, side: &str, sz: Decimal, start: Decimal, closed_pnl: Decimal, at: NaiveDateTime) -> LeaderFill {
+        LeaderFill {
+            trader_address: "0x123".to_string(),
+            hash: "0xhash".to_string(),
+            tid: 1,
+            oid: 1,
+            coin: coin.to_string(),
+            side: side.to_string(),
+            px: dec!(100),
+            sz,
+            dir: None,
+            start_position: Some(start),
+            closed_pnl,
+            fee: dec!(0),
+            fee_token: "USDC".to_string(),
+            crossed: true,
+            filled_at: at,
+        }
+    }
+
+    fn snapshot(account_value: Decimal, recorded_at: NaiveDateTime) -> EquitySnapshot {
+        EquitySnapshot { account_value, recorded_at }
+    }
+
+    #[test]
+    fn test_round_trips() {
+        let t0 = Utc::now().naive_utc() - Duration::days(5);
+        let hour = Duration::hours(1);
+        let mut open_fee = fill("BTC", "B", dec!(1), dec!(0), dec!(0), t0);
+        open_fee.fee = dec!(2);
+        let fills = [
+            // a short from before the history: never a trade
+            fill("ETH", "B", dec!(1), dec!(-1), dec!(5), t0),
+            open_fee,
+            fill("BTC", "B", dec!(1), dec!(1), dec!(0), t0 + hour),
+            fill("BTC", "A", dec!(1), dec!(2), dec!(30), t0 + hour * 2),
+            // flips long to short: closes the long, opens a short
+            fill("BTC", "A", dec!(2), dec!(1), dec!(20), t0 + hour * 3),
+            fill("BTC", "B", dec!(1), dec!(-1), dec!(-10), t0 + hour * 5),
+            // still open
+            fill("SOL", "A", dec!(3), dec!(0), dec!(0), t0 + hour * 6),
+        ];
+
+        let trades = round_trips(&fills);
+        assert_eq!(trades.len(), 2);
+        assert_eq!((trades[0].pnl, trades[0].closed_at - trades[0].opened_at), (dec!(48), hour * 3));
+        assert_eq!((trades[1].pnl, trades[1].closed_at - trades[1].opened_at), (dec!(-10), hour * 2));
+
+        let metrics = compute(&fills, &[], t0 - hour, t0 + Duration::days(1));
+        assert_eq!(metrics.trades, 2);
+        assert_eq!(metrics.win_rate, dec!(50));
+        assert_eq!(metrics.profit_factor, Some(dec!(4.8)));
+        assert_eq!(metrics.avg_hold_secs, Some(9000));
+        assert_eq!(metrics.expectancy, Some(dec!(19)));
+        assert_eq!(metrics.volume, dec!(1000));
+        // no equity, no returns
+        assert_eq!((metrics.return_pct, metrics.sharpe, metrics.max_drawdown), (None, None, None));
+    }
+
+    #[test]
+    fn test_time_weighted_returns_and_drawdown() {
+        let to = Utc::now().naive_utc();
+        let from = to - Duration::days(3);
+        let fills = [
+            // +10% on 10000, then -10% on 11000, then flat
+            fill("BTC", "A", dec!(1), dec!(1), dec!(1000), from + Duration::hours(12)),
+            fill("BTC", "B", dec!(1), dec!(0), dec!(0), from + Duration::hours(30)),
+            fill("BTC", "A", dec!(1), dec!(1), dec!(-1100), from + Duration::hours(36)),
+        ];
+        // a deposit after the fact does not change the returns
+        let equity = [snapshot(dec!(10000), from), snapshot(dec!(14900), to - Duration::hours(1))];
+
+        let metrics = compute(&fills, &equity, from, to);
+        assert_eq!(metrics.return_pct, Some(dec!(-1)));
+        assert_eq!(metrics.max_drawdown, Some(dec!(10)));
+        // mean 0, so no edge either way
+        assert_eq!(metrics.sharpe, Some(dec!(0)));
+        assert_eq!(metrics.sortino, Some(dec!(0)));
+        assert!(metrics.calmar.unwrap() < dec!(0));
+    }
+
+    #[test]
+    fn test_equity_before_the_first_snapshot_is_reconstructed() {
+        let to = Utc::now().naive_utc();
+        let from = to - Duration::days(2);
+        let fills = [
+            fill("BTC", "A", dec!(1), dec!(1), dec!(500), from + Duration::hours(6)),
+            fill("BTC", "A", dec!(1), dec!(1), dec!(1050), from + Duration::hours(30)),
+        ];
+        // only today's snapshot: 11550 now means 10000 two days ago
+        let equity = [snapshot(dec!(11550), to)];
+
+        let metrics = compute(&fills, &equity, from, to);
+        // 5%, then 10% on 10500
+        assert_eq!(metrics.return_pct, Some(dec!(15.5)));
+        assert_eq!(metrics.max_drawdown, Some(dec!(0)));
+        assert_eq!(metrics.sortino, None);
+        assert_eq!(metrics.calmar, None);
+        // 7.5% a day, give or take 2.5%
+        let expected = dec!(3) * DAYS_PER_YEAR.sqrt().unwrap();
+        assert!((metrics.sharpe.unwrap() - expected).abs() < dec!(0.01));
+    }
+
+    #[test]
+    fn test_no_fills() {
+        let to = Utc::now().naive_utc();
+        let metrics = compute(&[], &[snapshot(dec!(1000), to)], to - Duration::days(30), to);
+
+        assert_eq!(metrics.trades, 0);
+        assert_eq!(metrics.win_rate, dec!(0));
+        assert_eq!(metrics.return_pct, Some(dec!(0)));
+        assert_eq!(metrics.max_drawdown, Some(dec!(0)));
+        assert_eq!((metrics.sharpe, metrics.profit_factor, metrics.expectancy), (None, None, None));
+    }
+}
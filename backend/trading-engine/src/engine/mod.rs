@@ -2,7 +2,20 @@
 // we will then consume the channel that gives us the trades if we identify a trade by the traders
 // we send a trade message to the redis queue for further processing
 
+pub mod backfill;
+pub mod backtest;
+pub mod dedup;
+pub mod equity;
+pub mod execution;
 pub mod executor;
 pub mod grouper;
+pub mod leader_fills;
 pub mod leaderboard;
+pub mod metadata;
+pub mod metrics;
+pub mod paper;
 pub mod parser;
+pub mod positions;
+pub mod sizing;
+pub mod subscriptions;
+pub mod trade_log;
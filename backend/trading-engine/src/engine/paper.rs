@@ -0,0 +1,95 @@
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+
+use crate::exchange::{OrderOutcome, OrderRequest, TimeInForce};
+
+/// Whether a copy config trades for real or only simulates (`copy_configs.mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CopyMode {
+    #[default]
+    Live,
+    /// Orders go through sizing and normalization but are filled by [`match_order`]
+    /// and never reach the exchange
+    Paper,
+}
+
+impl CopyMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            CopyMode::Live => "live",
+            CopyMode::Paper => "paper",
+        }
+    }
+
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "live" => Some(CopyMode::Live),
+            "paper" => Some(CopyMode::Paper),
+            _ => None,
+        }
+    }
+}
+
+/// Fill a paper copy against the leader's average fill price: the whole size fills
+/// there when the copy's limit would have reached it, as if the follower had taken
+/// the same liquidity. Returns `None` when it would not have (e.g. the mid moved
+/// past the slippage band), which the executor treats like an unfilled IOC.
+///
+/// GTC copies always fill: they rest at the leader's own price, which the market
+/// just traded at, and only miss it by the tick rounding.
+pub fn match_order(request: &OrderRequest, leader_px: Decimal) -> Option<OrderOutcome> {
+    let reachable = request.tif == TimeInForce::Gtc
+        || if request.is_buy {
+            leader_px <= request.limit_px
+        } else {
+            leader_px >= request.limit_px
+        };
+    reachable.then_some(OrderOutcome::Filled {
+        // paper fills have no exchange order id
+        oid: 0,
+        total_sz: request.sz,
+        avg_px: leader_px,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    fn request(is_buy: bool, limit_px: Decimal) -> OrderRequest {
+        OrderRequest {
+            coin: "ETH".to_string(),
+            is_buy,
+            reduce_only: false,
+            limit_px,
+            sz: dec!(0.5),
+            tif: TimeInForce::Ioc,
+        }
+    }
+
+    #[test]
+    fn test_match_order() {
+        // fills at the leader's price, not at the limit
+        let filled = match_order(&request(true, dec!(3015)), dec!(3000));
+        assert_eq!(filled, Some(OrderOutcome::Filled { oid: 0, total_sz: dec!(0.5), avg_px: dec!(3000) }));
+        assert!(match_order(&request(false, dec!(2985)), dec!(3000)).is_some());
+
+        // the band moved away from the leader's price
+        assert_eq!(match_order(&request(true, dec!(2990)), dec!(3000)), None);
+        assert_eq!(match_order(&request(false, dec!(3010)), dec!(3000)), None);
+
+        // a GTC buy at the leader's average rounded down to the tick
+        let gtc = OrderRequest { tif: TimeInForce::Gtc, ..request(true, dec!(2999.9)) };
+        assert!(match_order(&gtc, dec!(2999.95)).is_some());
+    }
+
+    #[test]
+    fn test_copy_mode_roundtrip() {
+        for mode in [CopyMode::Live, CopyMode::Paper] {
+            assert_eq!(CopyMode::parse(mode.as_str()), Some(mode));
+        }
+        assert_eq!(CopyMode::parse("demo"), None);
+    }
+}
@@ -7,6 +7,8 @@ pub enum ParseError {
     Price(String),
     #[error("Failed to parse size: {0}")]
     Size(String),
+    #[error("Failed to parse fee: {0}")]
+    Fee(String),
 }
 
 /// Parse trade side to human-readable format
@@ -22,16 +24,23 @@ pub fn parse_side(side: &str) -> &str {
 pub fn parse_price(price: &str) -> Result<Decimal, ParseError> {
     price
         .parse::<Decimal>()
-        .map_err(|e| ParseError::Price(e.to_string()))
+        .map_err(|_| ParseError::Price(format!("Invalid decimal: {price}")))
 }
 
 /// Parse size from string to Decimal
 pub fn parse_size(size: &str) -> Result<Decimal, ParseError> {
     size.parse::<Decimal>()
-        .map_err(|e| ParseError::Size(e.to_string()))
+        .map_err(|_| ParseError::Size(format!("Invalid decimal: {size}")))
+}
+
+/// Parse a fill's fee (negative for maker rebates) from string to Decimal
+pub fn parse_fee(fee: &str) -> Result<Decimal, ParseError> {
+    fee.parse::<Decimal>()
+        .map_err(|_| ParseError::Fee(format!("Invalid decimal: {fee}")))
 }
 
 /// Calculate trade value (price × size)
+#[allow(dead_code)]
 pub fn calculate_trade_value(price: &str, size: &str) -> Result<Decimal, ParseError> {
     let p = parse_price(price)?;
     let s = parse_size(size)?;
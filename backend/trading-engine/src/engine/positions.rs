@@ -0,0 +1,190 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use log::info;
+use rust_decimal::Decimal;
+use rust_decimal_macros::dec;
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::engine::grouper::FullOrder;
+use crate::engine::trade_log::TradeLog;
+use crate::exchange::{Exchange, ExchangeError};
+
+#[derive(Error, Debug)]
+pub enum PositionError {
+    #[error("Failed to fetch positions: {0}")]
+    Exchange(#[from] ExchangeError),
+    #[error("Failed to load paper positions: {0}")]
+    Database(#[from] sqlx::Error),
+}
+
+/// Where a follower's positions are read from the first time we need them.
+enum PositionSource {
+    /// The exchange's clearinghouse state
+    Exchange(Arc<dyn Exchange>),
+    /// The follower's recorded paper fills
+    PaperFills(TradeLog),
+}
+
+/// Signed positions (positive long, negative short) of each follower per coin.
+///
+/// A follower is seeded from its source the first time we need one of their
+/// positions, then kept current from our own fills.
+pub struct PositionBook {
+    source: PositionSource,
+    positions: RwLock<HashMap<String, HashMap<String, Decimal>>>,
+}
+
+impl PositionBook {
+    pub fn new(exchange: Arc<dyn Exchange>) -> Self {
+        Self {
+            source: PositionSource::Exchange(exchange),
+            positions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Positions of paper copies, which only ever change through our simulated fills.
+    pub fn paper(trades: TradeLog) -> Self {
+        Self {
+            source: PositionSource::PaperFills(trades),
+            positions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn position(&self, follower: &str, coin: &str) -> Result<Decimal, PositionError> {
+        if let Some(coins) = self.positions.read().await.get(follower) {
+            return Ok(coins.get(coin).copied().unwrap_or_default());
+        }
+
+        let coins = match &self.source {
+            PositionSource::Exchange(exchange) => exchange.account_state(follower).await?.positions,
+            PositionSource::PaperFills(trades) => trades.paper_positions(follower).await?,
+        };
+        info!("Seeded {} open positions for follower {}", coins.len(), follower);
+        let position = coins.get(coin).copied().unwrap_or_default();
+        self.positions.write().await.insert(follower.to_string(), coins);
+        Ok(position)
+    }
+
+    /// Apply one of our own fills to a follower's tracked position.
+    pub async fn apply_fill(&self, follower: &str, coin: &str, is_buy: bool, sz: Decimal) {
+        // not seeded yet: the next lookup will read the exchange, fill included
+        if let Some(coins) = self.positions.write().await.get_mut(follower) {
+            let signed = if is_buy { sz } else { -sz };
+            *coins.entry(coin.to_string()).or_default() += signed;
+        }
+    }
+
+    /// Drop every tracked position so the next lookups resync from the exchange,
+    /// picking up resting orders that filled later and trades made outside the engine.
+    pub async fn clear(&self) {
+        self.positions.write().await.clear();
+    }
+}
+
+/// How a leader order changed the leader's position, from its `dir`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LeaderAction {
+    Open { is_buy: bool },
+    Close { is_buy: bool },
+    /// One order closed the position and opened the opposite side
+    Flip { is_buy: bool },
+}
+
+impl LeaderAction {
+    pub fn from_dir(dir: &str) -> Option<Self> {
+        match dir {
+            "Open Long" => Some(LeaderAction::Open { is_buy: true }),
+            "Open Short" => Some(LeaderAction::Open { is_buy: false }),
+            "Close Long" => Some(LeaderAction::Close { is_buy: false }),
+            "Close Short" => Some(LeaderAction::Close { is_buy: true }),
+            "Short > Long" => Some(LeaderAction::Flip { is_buy: true }),
+            "Long > Short" => Some(LeaderAction::Flip { is_buy: false }),
+            _ => None,
+        }
+    }
+
+    /// Whether `dir` is that of a spot fill. Only perp positions are tracked, so
+    /// these are not copied.
+    pub fn is_spot(dir: &str) -> bool {
+        matches!(dir, "Buy" | "Sell")
+    }
+}
+
+/// Size the follower's close of `position` for a leader order that reduced its own
+/// position: the same fraction the leader closed, or everything when that is unknown.
+///
+/// Returns `None` when the follower holds nothing on the side being closed.
+pub fn close_size(order: &FullOrder, is_buy: bool, position: Decimal) -> Option<Decimal> {
+    // closing with a buy reduces a short and vice versa
+    let held = if is_buy { -position } else { position };
+    if held <= dec!(0) {
+        return None;
+    }
+
+    let fraction = match order.start_position {
+        Some(start) if !start.is_zero() => (order.total_sz / start.abs()).min(dec!(1)),
+        _ => dec!(1),
+    };
+    Some((held * fraction).round_dp(8))
+}
+
+/// Part of a flip order that opened the new side, in leader size.
+pub fn flip_open_size(order: &FullOrder) -> Decimal {
+    match order.start_position {
+        Some(start) => (order.total_sz - start.abs()).max(dec!(0)),
+        None => order.total_sz,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn order(dir: &str, total_sz: Decimal, start_position: Option<Decimal>) -> FullOrder {
+        FullOrder {
+            user: "0xleader".to_string(),
+            coin: "ETH".to_string(),
+            dir: dir.to_string(),
+            total_sz,
+            avg_px: dec!(3000),
+            timestamp: 0,
+            hash: "0xhash".to_string(),
+            oid: 1,
+            start_position,
+        }
+    }
+
+    #[test]
+    fn test_leader_action() {
+        assert_eq!(LeaderAction::from_dir("Close Long"), Some(LeaderAction::Close { is_buy: false }));
+        assert_eq!(LeaderAction::from_dir("Short > Long"), Some(LeaderAction::Flip { is_buy: true }));
+        assert_eq!(LeaderAction::from_dir("Liquidation"), None);
+        assert!(LeaderAction::is_spot("Sell"));
+        assert!(!LeaderAction::is_spot("Open Long"));
+    }
+
+    #[test]
+    fn test_close_size() {
+        // leader closed 2 of 8 ETH, follower holds 1 ETH long
+        let close_long = order("Close Long", dec!(2), Some(dec!(8)));
+        assert_eq!(close_size(&close_long, false, dec!(1)), Some(dec!(0.25)));
+
+        // nothing to close, or only the opposite side
+        assert_eq!(close_size(&close_long, false, dec!(0)), None);
+        assert_eq!(close_size(&close_long, false, dec!(-1)), None);
+
+        // full close, and unknown leader position closes everything
+        let full = order("Close Short", dec!(3), Some(dec!(-3)));
+        assert_eq!(close_size(&full, true, dec!(-0.5)), Some(dec!(0.5)));
+        let unknown = order("Close Short", dec!(3), None);
+        assert_eq!(close_size(&unknown, true, dec!(-0.5)), Some(dec!(0.5)));
+    }
+
+    #[test]
+    fn test_flip_open_size() {
+        // long 2 -> short 3
+        assert_eq!(flip_open_size(&order("Long > Short", dec!(5), Some(dec!(2)))), dec!(3));
+        assert_eq!(flip_open_size(&order("Long > Short", dec!(5), None)), dec!(5));
+    }
+}
@@ -0,0 +1,110 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use tokio::sync::RwLock;
+
+use crate::exchange::{Exchange, ExchangeError};
+
+/// How long an account value is reused before it is fetched again.
+const EQUITY_TTL: Duration = Duration::from_secs(60);
+
+#[derive(Error, Debug)]
+pub enum SizingError {
+    #[error("Failed to fetch account state: {0}")]
+    Exchange(#[from] ExchangeError),
+    #[error("Account {0} has no equity to scale against")]
+    NoEquity(String),
+}
+
+/// How a copy config sizes the follower's opening orders (`copy_configs.sizing_mode`).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SizingMode {
+    /// Leader size × `ratio`
+    #[default]
+    Ratio,
+    /// Leader size × follower equity / leader equity × `ratio`
+    Equity,
+    /// Always `fixed_notional` USD, whatever the leader traded
+    FixedNotional,
+}
+
+impl SizingMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SizingMode::Ratio => "ratio",
+            SizingMode::Equity => "equity",
+            SizingMode::FixedNotional => "fixed_notional",
+        }
+    }
+
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "ratio" => Some(SizingMode::Ratio),
+            "equity" => Some(SizingMode::Equity),
+            "fixed_notional" => Some(SizingMode::FixedNotional),
+            _ => None,
+        }
+    }
+}
+
+/// Account values of leaders and followers, fetched from the clearinghouse state
+/// and cached for [`EQUITY_TTL`].
+pub struct EquityCache {
+    exchange: Arc<dyn Exchange>,
+    values: RwLock<HashMap<String, (Instant, Decimal)>>,
+}
+
+impl EquityCache {
+    pub fn new(exchange: Arc<dyn Exchange>) -> Self {
+        Self {
+            exchange,
+            values: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub async fn account_value(&self, address: &str) -> Result<Decimal, SizingError> {
+        if let Some((fetched_at, value)) = self.values.read().await.get(address)
+            && fetched_at.elapsed() < EQUITY_TTL
+        {
+            return Ok(*value);
+        }
+
+        let value = self.exchange.account_state(address).await?.account_value;
+
+        self.values.write().await.insert(address.to_string(), (Instant::now(), value));
+        Ok(value)
+    }
+
+    /// Follower equity / leader equity, the scale applied in [`SizingMode::Equity`].
+    pub async fn equity_ratio(&self, leader: &str, follower: &str) -> Result<Decimal, SizingError> {
+        let leader_value = self.account_value(leader).await?;
+        if leader_value <= Decimal::ZERO {
+            return Err(SizingError::NoEquity(leader.to_string()));
+        }
+        let follower_value = self.account_value(follower).await?;
+        if follower_value <= Decimal::ZERO {
+            return Err(SizingError::NoEquity(follower.to_string()));
+        }
+        Ok(follower_value / leader_value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sizing_mode_roundtrip() {
+        for mode in [SizingMode::Ratio, SizingMode::Equity, SizingMode::FixedNotional] {
+            assert_eq!(SizingMode::parse(mode.as_str()), Some(mode));
+        }
+        assert_eq!(SizingMode::parse("kelly"), None);
+
+        let mode: SizingMode = serde_json::from_str("\"fixed_notional\"").unwrap();
+        assert_eq!(mode, SizingMode::FixedNotional);
+    }
+}
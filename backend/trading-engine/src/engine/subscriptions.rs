@@ -0,0 +1,102 @@
+use log::{info, warn};
+use sqlx::PgPool;
+use thiserror::Error;
+use tokio::sync::mpsc;
+
+use crate::hyperliquid::ws::WsPool;
+use crate::shutdown::Shutdown;
+
+#[derive(Error, Debug)]
+pub enum SubscriptionError {
+    #[error("Database error: {0}")]
+    SqlxError(#[from] sqlx::Error),
+    #[error("Subscription manager is not running")]
+    ManagerClosed,
+}
+
+#[derive(Debug)]
+enum Command {
+    Subscribe(String),
+    Unsubscribe(String),
+}
+
+/// Cheap, cloneable handle used by the API to add or remove leader subscriptions
+/// while the engine is running.
+#[derive(Clone, Debug)]
+pub struct SubscriptionHandle {
+    tx: mpsc::Sender<Command>,
+}
+
+impl SubscriptionHandle {
+    pub async fn subscribe(&self, address: &str) -> Result<(), SubscriptionError> {
+        self.tx
+            .send(Command::Subscribe(address.to_string()))
+            .await
+            .map_err(|_| SubscriptionError::ManagerClosed)
+    }
+
+    pub async fn unsubscribe(&self, address: &str) -> Result<(), SubscriptionError> {
+        self.tx
+            .send(Command::Unsubscribe(address.to_string()))
+            .await
+            .map_err(|_| SubscriptionError::ManagerClosed)
+    }
+}
+
+/// Keeps the set of `userFills` subscriptions in the WebSocket pool in sync with the
+/// `traders` table and API changes.
+pub struct SubscriptionManager {
+    pool: WsPool,
+    rx: mpsc::Receiver<Command>,
+}
+
+impl SubscriptionManager {
+    pub fn new(pool: WsPool) -> (Self, SubscriptionHandle) {
+        let (tx, rx) = mpsc::channel(256);
+        (Self { pool, rx }, SubscriptionHandle { tx })
+    }
+
+    /// Subscribe to every active leader in the `traders` table.
+    pub async fn load_active(&mut self, pool: &PgPool) -> Result<usize, SubscriptionError> {
+        let traders = sqlx::query_scalar::<_, String>(
+            "SELECT address FROM traders WHERE is_active = true",
+        )
+        .fetch_all(pool)
+        .await?;
+
+        let count = traders.len();
+        for trader in traders {
+            self.pool.subscribe(&trader);
+        }
+        Ok(count)
+    }
+
+    /// Apply subscription changes until shutdown; dropping the pool on return
+    /// closes every socket, which ends the fill stream.
+    pub async fn run(mut self, shutdown: Shutdown) {
+        loop {
+            let cmd = tokio::select! {
+                cmd = self.rx.recv() => cmd,
+                _ = shutdown.wait() => {
+                    info!("Shutting down — closing WebSocket pool");
+                    return;
+                }
+            };
+            let Some(cmd) = cmd else { break };
+            match cmd {
+                Command::Subscribe(address) => {
+                    if self.pool.subscribe(&address) {
+                        info!("Subscribed to userFills for {}", address);
+                    }
+                }
+                Command::Unsubscribe(address) => {
+                    if self.pool.unsubscribe(&address) {
+                        info!("Unsubscribed from userFills for {}", address);
+                    }
+                }
+            }
+        }
+
+        warn!("All subscription handles dropped — closing WebSocket pool");
+    }
+}
@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use chrono::NaiveDateTime;
+use rust_decimal::Decimal;
+use sqlx::{FromRow, PgPool};
+use sqlx::types::Json;
+
+use crate::engine::grouper::FullOrder;
+
+/// Lifecycle of a follower copy in `executed_trades`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TradeStatus {
+    /// Recorded before the order is sent to the exchange
+    Sent,
+    Resting,
+    Filled,
+    /// Exchange accepted the request but refused the order
+    Rejected,
+    /// Never reached the exchange or the response was unusable
+    Failed,
+    /// Could not fill within the copy config's slippage band
+    Skipped,
+    /// Rested past the GTC timeout and was cancelled
+    Cancelled,
+    /// Partly filled before the GTC timeout cancelled the rest; size and price
+    /// are what filled
+    PartiallyFilled,
+    /// Simulated fill of a paper copy
+    PaperFilled,
+}
+
+impl TradeStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TradeStatus::Sent => "sent",
+            TradeStatus::Resting => "resting",
+            TradeStatus::Filled => "filled",
+            TradeStatus::Rejected => "rejected",
+            TradeStatus::Failed => "failed",
+            TradeStatus::Skipped => "skipped",
+            TradeStatus::Cancelled => "cancelled",
+            TradeStatus::PartiallyFilled => "partially_filled",
+            TradeStatus::PaperFilled => "paper_filled",
+        }
+    }
+}
+
+/// A live GTC copy with size left on the book, cancelled at `cancel_at`
+/// (`resting_copy_orders`).
+#[derive(Debug, Clone, PartialEq, FromRow)]
+pub struct RestingCopy {
+    pub trade_id: i64,
+    pub follower_address: String,
+    pub agent_key_id: i32,
+    pub coin: String,
+    pub is_buy: bool,
+    pub oid: i64,
+    pub limit_px: Decimal,
+    /// Size as ordered
+    pub sz: Decimal,
+    /// Filled when the order was placed, already applied to positions
+    pub filled_sz: Decimal,
+    pub filled_px: Decimal,
+    pub cancel_at: NaiveDateTime,
+}
+
+/// Writes follower copies to `executed_trades`; a disabled log (backtests, tests)
+/// accepts every call and records nothing.
+#[derive(Clone)]
+pub struct TradeLog {
+    pool: Option<PgPool>,
+}
+
+impl TradeLog {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool: Some(pool) }
+    }
+
+    pub fn disabled() -> Self {
+        Self { pool: None }
+    }
+
+    /// Insert a `sent` row for a follower copy of `order` and return its id.
+    pub async fn record_sent(
+        &self,
+        order: &FullOrder,
+        follower: &str,
+        is_buy: bool,
+        size: Decimal,
+        price: Decimal,
+    ) -> Result<i64, sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(0);
+        };
+        sqlx::query_scalar(
+            "INSERT INTO executed_trades
+                (follower_address, trader_address, coin, side, size, price, order_hash, leader_oid, status)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+             RETURNING id",
+        )
+        .bind(follower)
+        .bind(&order.user)
+        .bind(&order.coin)
+        .bind(if is_buy { "B" } else { "A" })
+        .bind(size)
+        .bind(price)
+        .bind(&order.hash)
+        .bind(order.oid as i64)
+        .bind(TradeStatus::Sent.as_str())
+        .fetch_one(pool)
+        .await
+    }
+
+    /// Record the exchange's answer for a previously sent copy.
+    pub async fn record_placed(
+        &self,
+        id: i64,
+        status: TradeStatus,
+        hl_oid: u64,
+    ) -> Result<(), sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        sqlx::query("UPDATE executed_trades SET status = $2, hl_oid = $3, updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(status.as_str())
+            .bind(hl_oid as i64)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Record a fill, replacing the requested size/price with what actually executed.
+    pub async fn record_filled(
+        &self,
+        id: i64,
+        hl_oid: u64,
+        size: Decimal,
+        price: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        sqlx::query(
+            "UPDATE executed_trades
+             SET status = $2, hl_oid = $3, size = $4, price = $5, updated_at = NOW()
+             WHERE id = $1",
+        )
+        .bind(id)
+        .bind(TradeStatus::Filled.as_str())
+        .bind(hl_oid as i64)
+        .bind(size)
+        .bind(price)
+        .execute(pool)
+        .await?;
+        Ok(())
+    }
+
+    /// Record the simulated fill of a paper copy; it has no exchange order id.
+    pub async fn record_paper_fill(
+        &self,
+        id: i64,
+        size: Decimal,
+        price: Decimal,
+    ) -> Result<(), sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        sqlx::query("UPDATE executed_trades SET status = $2, size = $3, price = $4, updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(TradeStatus::PaperFilled.as_str())
+            .bind(size)
+            .bind(price)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Net paper position of `follower` per coin, summed from its paper fills.
+    pub async fn paper_positions(&self, follower: &str) -> Result<HashMap<String, Decimal>, sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(HashMap::new());
+        };
+        let rows: Vec<(String, Decimal)> = sqlx::query_as(
+            "SELECT coin, SUM(CASE WHEN side = 'B' THEN size ELSE -size END)
+             FROM executed_trades
+             WHERE follower_address = $1 AND status = $2
+             GROUP BY coin",
+        )
+        .bind(follower)
+        .bind(TradeStatus::PaperFilled.as_str())
+        .fetch_all(pool)
+        .await?;
+        Ok(rows.into_iter().collect())
+    }
+
+    /// Keep a copy of `order` for `follower` that was still queued at shutdown.
+    pub async fn save_pending(&self, follower: &str, order: &FullOrder) -> Result<(), sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        sqlx::query("INSERT INTO pending_copy_tasks (follower_address, leader_order) VALUES ($1, $2)")
+            .bind(follower)
+            .bind(Json(order))
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Remove and return every copy kept by `save_pending`, oldest first.
+    pub async fn take_pending(&self) -> Result<Vec<(String, FullOrder)>, sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(Vec::new());
+        };
+        let mut rows: Vec<(i64, String, Json<FullOrder>)> = sqlx::query_as(
+            "DELETE FROM pending_copy_tasks RETURNING id, follower_address, leader_order",
+        )
+        .fetch_all(pool)
+        .await?;
+        rows.sort_by_key(|(id, _, _)| *id);
+        Ok(rows.into_iter().map(|(_, follower, order)| (follower, order.0)).collect())
+    }
+
+    /// Mark a copy resting and keep its cancel deadline until `finish_resting`.
+    pub async fn record_resting(&self, copy: &RestingCopy) -> Result<(), sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        let mut tx = pool.begin().await?;
+        sqlx::query("UPDATE executed_trades SET status = $2, hl_oid = $3, updated_at = NOW() WHERE id = $1")
+            .bind(copy.trade_id)
+            .bind(TradeStatus::Resting.as_str())
+            .bind(copy.oid)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query(
+            "INSERT INTO resting_copy_orders
+                (trade_id, follower_address, coin, is_buy, oid, limit_px, sz, filled_sz, filled_px, cancel_at)
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(copy.trade_id)
+        .bind(&copy.follower_address)
+        .bind(&copy.coin)
+        .bind(copy.is_buy)
+        .bind(copy.oid)
+        .bind(copy.limit_px)
+        .bind(copy.sz)
+        .bind(copy.filled_sz)
+        .bind(copy.filled_px)
+        .bind(copy.cancel_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await
+    }
+
+    /// Every copy still waiting for its cancel deadline, signed with the follower's
+    /// current agent key. Copies of followers without one stay stored until they
+    /// have one again.
+    pub async fn resting_copies(&self) -> Result<Vec<RestingCopy>, sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(Vec::new());
+        };
+        sqlx::query_as(
+            "SELECT r.trade_id, r.follower_address, k.id AS agent_key_id, r.coin, r.is_buy, r.oid,
+                    r.limit_px, r.sz, r.filled_sz, r.filled_px, r.cancel_at
+             FROM resting_copy_orders r
+             JOIN followers f ON f.address = r.follower_address
+             JOIN agent_keys k ON k.follower_id = f.id AND k.revoked_at IS NULL
+             ORDER BY r.cancel_at",
+        )
+        .fetch_all(pool)
+        .await
+    }
+
+    /// Record how a resting copy ended, with the size and average price that filled
+    /// if any did. Returns false if it was already finished.
+    pub async fn finish_resting(
+        &self,
+        trade_id: i64,
+        status: TradeStatus,
+        filled: Option<(Decimal, Decimal)>,
+        reason: Option<&str>,
+    ) -> Result<bool, sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(true);
+        };
+        let mut tx = pool.begin().await?;
+        let claimed = sqlx::query("DELETE FROM resting_copy_orders WHERE trade_id = $1")
+            .bind(trade_id)
+            .execute(&mut *tx)
+            .await?
+            .rows_affected();
+        if claimed == 0 {
+            return Ok(false);
+        }
+        let (size, price) = filled.unzip();
+        sqlx::query(
+            "UPDATE executed_trades
+             SET status = $2, size = COALESCE($3, size), price = COALESCE($4, price),
+                 status_reason = $5, updated_at = NOW()
+             WHERE id = $1",
+        )
+        .bind(trade_id)
+        .bind(status.as_str())
+        .bind(size)
+        .bind(price)
+        .bind(reason)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    /// Record why a copy did not execute: rejected, failed, skipped or cancelled.
+    pub async fn record_failure(
+        &self,
+        id: i64,
+        status: TradeStatus,
+        reason: &str,
+    ) -> Result<(), sqlx::Error> {
+        let Some(pool) = &self.pool else {
+            return Ok(());
+        };
+        sqlx::query("UPDATE executed_trades SET status = $2, status_reason = $3, updated_at = NOW() WHERE id = $1")
+            .bind(id)
+            .bind(status.as_str())
+            .bind(reason)
+            .execute(pool)
+            .await?;
+        Ok(())
+    }
+}
@@ -16,6 +16,8 @@ pub enum AppError {
     BadRequest(String),
     #[error("Database error: {0}")]
     SqlxError(#[from] sqlx::Error),
+    #[error("Upstream error: {0}")]
+    Upstream(String),
 }
 
 impl IntoResponse for AppError {
@@ -23,6 +25,7 @@ impl IntoResponse for AppError {
         let (status, error_message) = match self {
             AppError::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
             AppError::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
+            AppError::Upstream(msg) => (StatusCode::BAD_GATEWAY, msg),
             AppError::InternalServerError | AppError::SqlxError(_) => (
                 StatusCode::INTERNAL_SERVER_ERROR,
                 self.to_string(), // Use the error message from thiserror
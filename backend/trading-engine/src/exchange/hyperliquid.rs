@@ -0,0 +1,494 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use async_trait::async_trait;
+use hyperliquid_rust_sdk::{
+    BaseUrl, ClientCancelRequest, ClientLimit, ClientOrder, ClientOrderRequest, ExchangeClient,
+    ExchangeDataStatus, ExchangeResponseStatus, InfoClient,
+};
+use log::info;
+use rust_decimal::{Decimal, prelude::ToPrimitive};
+use serde::Deserialize;
+use serde_json::json;
+use tokio::sync::RwLock;
+
+use crate::engine::metadata::AssetInfo;
+use crate::engine::parser::{parse_price, parse_size};
+use crate::exchange::{
+    AccountState, Exchange, ExchangeError, OrderOutcome, OrderRequest, OrderState, OrderStatus, Signer,
+    TimeInForce,
+};
+use crate::hyperliquid::network::NetworkConfig;
+use crate::vault::KeyVault;
+
+/// Error the exchange returns for an IOC order with nothing to match within its limit.
+const IOC_NO_MATCH: &str = "could not immediately match";
+
+struct CachedClient {
+    /// Vault key the client was built from, to notice rotation or revocation
+    agent_key_id: i32,
+    client: Arc<ExchangeClient>,
+}
+
+/// Hyperliquid through the official SDK, signing with each follower's own agent key.
+pub struct HyperliquidExchange {
+    api_url: String,
+    vault: Arc<KeyVault>,
+    info: InfoClient,
+    http: reqwest::Client,
+    info_url: String,
+    /// Exchange clients keyed by follower address
+    clients: RwLock<HashMap<String, CachedClient>>,
+}
+
+impl HyperliquidExchange {
+    pub async fn new(network: &NetworkConfig, vault: Arc<KeyVault>) -> Result<Self, ExchangeError> {
+        Ok(Self {
+            api_url: network.api_url.clone(),
+            vault,
+            info: info_client(&network.api_url).await?,
+            http: reqwest::Client::new(),
+            info_url: format!("{}/info", network.api_url),
+            clients: RwLock::new(HashMap::new()),
+        })
+    }
+
+    async fn client_for(&self, signer: Signer<'_>) -> Result<Arc<ExchangeClient>, ExchangeError> {
+        // the executor may still know the follower by a key rotated since
+        let Some(agent_key_id) = self.vault.current_key(signer.agent_key_id) else {
+            self.clients.write().await.remove(signer.address);
+            return Err(ExchangeError::InvalidAgentKey(format!("agent key for {} was revoked", signer.address)));
+        };
+
+        {
+            let clients = self.clients.read().await;
+            if let Some(cached) = clients.get(signer.address)
+                && cached.agent_key_id == agent_key_id
+            {
+                return Ok(cached.client.clone());
+            }
+        }
+
+        let agent_key = self
+            .vault
+            .signing_key(agent_key_id)
+            .await
+            .map_err(|e| ExchangeError::InvalidAgentKey(e.to_string()))?;
+        let wallet = agent_key
+            .parse()
+            .map_err(|_| ExchangeError::InvalidAgentKey(format!("agent key for {} is not a private key", signer.address)))?;
+        // what `ExchangeClient::new` does, against our URL instead of a built-in one
+        let init_error = |e: hyperliquid_rust_sdk::Error| ExchangeError::ClientInitialization(e.to_string());
+        let info = info_client(&self.api_url).await?;
+        let meta = info.meta().await.map_err(init_error)?;
+        let coin_to_asset = meta
+            .universe
+            .iter()
+            .enumerate()
+            .map(|(index, asset)| (asset.name.clone(), index as u32))
+            .collect();
+        let coin_to_asset = info.spot_meta().await.map_err(init_error)?.add_pair_and_name_to_index_map(coin_to_asset);
+        let client = ExchangeClient {
+            http_client: info.http_client,
+            wallet,
+            meta,
+            vault_address: None,
+            coin_to_asset,
+        };
+
+        info!("Initialized exchange client for follower {}", signer.address);
+        let client = Arc::new(client);
+        self.clients.write().await.insert(
+            signer.address.to_string(),
+            CachedClient {
+                agent_key_id,
+                client: client.clone(),
+            },
+        );
+        Ok(client)
+    }
+
+    async fn info_request<T: serde::de::DeserializeOwned>(&self, request: serde_json::Value) -> Result<T, ExchangeError> {
+        let response = self
+            .http
+            .post(&self.info_url)
+            .json(&request)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| ExchangeError::Request(e.to_string()))?;
+        response.json().await.map_err(|e| ExchangeError::Request(e.to_string()))
+    }
+}
+
+#[async_trait]
+impl Exchange for HyperliquidExchange {
+    async fn place_order(&self, signer: Signer<'_>, order: &OrderRequest) -> Result<OrderOutcome, ExchangeError> {
+        let client = self.client_for(signer).await?;
+        let client_order = ClientOrderRequest {
+            asset: order.coin.clone(),
+            is_buy: order.is_buy,
+            reduce_only: order.reduce_only,
+            limit_px: order.limit_px.to_f64().ok_or(ExchangeError::DecimalConversion)?,
+            sz: order.sz.to_f64().ok_or(ExchangeError::DecimalConversion)?,
+            cloid: None,
+            order_type: ClientOrder::Limit(ClientLimit {
+                tif: match order.tif {
+                    TimeInForce::Ioc => "Ioc",
+                    TimeInForce::Gtc => "Gtc",
+                }
+                .to_string(),
+            }),
+        };
+
+        let response = client
+            .order(client_order, None)
+            .await
+            .map_err(|e| request_error(e, &order.coin))?;
+        order_outcome(first_status(response)?)
+    }
+
+    async fn cancel_order(&self, signer: Signer<'_>, coin: &str, oid: u64) -> Result<bool, ExchangeError> {
+        let client = self.client_for(signer).await?;
+        let response = client
+            .cancel(ClientCancelRequest { asset: coin.to_string(), oid }, None)
+            .await
+            .map_err(|e| request_error(e, coin))?;
+        Ok(matches!(first_status(response)?, ExchangeDataStatus::Success))
+    }
+
+    async fn order_status(&self, address: &str, oid: u64) -> Result<OrderState, ExchangeError> {
+        let response: OrderStatusResponse =
+            self.info_request(json!({ "type": "orderStatus", "user": address, "oid": oid })).await?;
+        order_state(response)
+    }
+
+    async fn account_state(&self, address: &str) -> Result<AccountState, ExchangeError> {
+        let user = address
+            .parse()
+            .map_err(|_| ExchangeError::InvalidAddress(address.to_string()))?;
+        let state = self
+            .info
+            .user_state(user)
+            .await
+            .map_err(|e| ExchangeError::Request(e.to_string()))?;
+
+        let mut positions = HashMap::new();
+        for asset in state.asset_positions {
+            positions.insert(asset.position.coin, parse_size(&asset.position.szi)?);
+        }
+        Ok(AccountState {
+            account_value: parse_size(&state.margin_summary.account_value)?,
+            positions,
+        })
+    }
+
+    async fn mid_price(&self, coin: &str) -> Result<Decimal, ExchangeError> {
+        let mids = self
+            .info
+            .all_mids()
+            .await
+            .map_err(|e| ExchangeError::Request(e.to_string()))?;
+        let mid = mids.get(coin).ok_or_else(|| ExchangeError::NoMidPrice(coin.to_string()))?;
+        Ok(parse_price(mid)?)
+    }
+
+    async fn universe(&self) -> Result<HashMap<String, AssetInfo>, ExchangeError> {
+        // the SDK's meta types drop maxLeverage, so read the raw responses
+        let perps: PerpUniverse = self.info_request(json!({ "type": "meta" })).await?;
+        let spot: SpotUniverse = self.info_request(json!({ "type": "spotMeta" })).await?;
+        Ok(build_assets(perps, spot))
+    }
+
+    async fn retain_accounts(&self, active: &HashSet<String>) {
+        self.clients.write().await.retain(|address, _| active.contains(address));
+    }
+}
+
+/// SDK info client for `api_url`. The SDK only knows its built-in URLs, so the
+/// one it was built with is replaced; signing follows the URL, mainnet or not.
+async fn info_client(api_url: &str) -> Result<InfoClient, ExchangeError> {
+    let mut info = InfoClient::new(None, Some(BaseUrl::Localhost))
+        .await
+        .map_err(|e| ExchangeError::ClientInitialization(e.to_string()))?;
+    info.http_client.base_url = api_url.to_string();
+    Ok(info)
+}
+
+fn request_error(e: hyperliquid_rust_sdk::Error, coin: &str) -> ExchangeError {
+    match e {
+        hyperliquid_rust_sdk::Error::AssetNotFound => ExchangeError::UnknownAsset(coin.to_string()),
+        e => ExchangeError::Request(e.to_string()),
+    }
+}
+
+fn first_status(response: ExchangeResponseStatus) -> Result<ExchangeDataStatus, ExchangeError> {
+    match response {
+        ExchangeResponseStatus::Ok(exchange_response) => {
+            let data = exchange_response.data.ok_or(ExchangeError::NoDataInResponse)?;
+            data.statuses.into_iter().next().ok_or(ExchangeError::NoDataInResponse)
+        }
+        ExchangeResponseStatus::Err(e) => Err(ExchangeError::Request(e)),
+    }
+}
+
+fn order_outcome(status: ExchangeDataStatus) -> Result<OrderOutcome, ExchangeError> {
+    match status {
+        ExchangeDataStatus::Filled(o) => Ok(OrderOutcome::Filled {
+            oid: o.oid,
+            total_sz: parse_size(&o.total_sz)?,
+            avg_px: parse_price(&o.avg_px)?,
+        }),
+        ExchangeDataStatus::Resting(o) => Ok(OrderOutcome::Resting { oid: o.oid }),
+        ExchangeDataStatus::Error(reason) if reason.contains(IOC_NO_MATCH) => Err(ExchangeError::Unmatched(reason)),
+        ExchangeDataStatus::Error(reason) => Err(ExchangeError::Rejected(reason)),
+        status => Err(ExchangeError::UnexpectedStatus(format!("{:?}", status))),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "status", rename_all = "camelCase")]
+enum OrderStatusResponse {
+    Order { order: StatusOrder },
+    UnknownOid,
+}
+
+#[derive(Deserialize)]
+struct StatusOrder {
+    order: StatusOrderSizes,
+    status: String,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StatusOrderSizes {
+    /// Size still open
+    sz: String,
+    orig_sz: String,
+}
+
+fn order_state(response: OrderStatusResponse) -> Result<OrderState, ExchangeError> {
+    let OrderStatusResponse::Order { order } = response else {
+        return Ok(OrderState::unknown());
+    };
+    let status = match order.status.as_str() {
+        "open" | "triggered" => OrderStatus::Open,
+        "filled" => OrderStatus::Filled,
+        s if s.ends_with("ejected") => OrderStatus::Rejected,
+        // canceled, marginCanceled, reduceOnlyCanceled and the other ways it closes
+        _ => OrderStatus::Cancelled,
+    };
+    let filled_sz = parse_size(&order.order.orig_sz)? - parse_size(&order.order.sz)?;
+    Ok(OrderState { status, filled_sz })
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PerpUniverse {
+    universe: Vec<PerpAsset>,
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct PerpAsset {
+    name: String,
+    sz_decimals: u32,
+    max_leverage: u32,
+    #[serde(default)]
+    is_delisted: bool,
+}
+
+#[derive(Deserialize)]
+struct SpotUniverse {
+    universe: Vec<SpotPair>,
+    tokens: Vec<SpotToken>,
+}
+
+#[derive(Deserialize)]
+struct SpotPair {
+    name: String,
+    tokens: [usize; 2],
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SpotToken {
+    name: String,
+    index: usize,
+    sz_decimals: u32,
+}
+
+fn build_assets(perps: PerpUniverse, spot: SpotUniverse) -> HashMap<String, AssetInfo> {
+    let mut assets = HashMap::new();
+    for asset in perps.universe.into_iter().filter(|a| !a.is_delisted) {
+        assets.insert(asset.name, AssetInfo::perp(asset.sz_decimals, asset.max_leverage));
+    }
+
+    let tokens: HashMap<usize, &SpotToken> = spot.tokens.iter().map(|t| (t.index, t)).collect();
+    for pair in spot.universe {
+        let (Some(base), Some(quote)) = (tokens.get(&pair.tokens[0]), tokens.get(&pair.tokens[1])) else {
+            continue;
+        };
+        // spot sizes are in the base token
+        let info = AssetInfo::spot(base.sz_decimals);
+        assets.insert(format!("{}/{}", base.name, quote.name), info);
+        assets.insert(pair.name, info);
+    }
+    assets
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use hyperliquid_rust_sdk::{FilledOrder, RestingOrder};
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_order_outcome() {
+        let filled = ExchangeDataStatus::Filled(FilledOrder {
+            total_sz: "0.02".to_string(),
+            avg_px: "50010.5".to_string(),
+            oid: 7,
+        });
+        assert_eq!(
+            order_outcome(filled).unwrap(),
+            OrderOutcome::Filled { oid: 7, total_sz: dec!(0.02), avg_px: dec!(50010.5) }
+        );
+
+        let resting = ExchangeDataStatus::Resting(RestingOrder { oid: 8 });
+        assert_eq!(order_outcome(resting).unwrap(), OrderOutcome::Resting { oid: 8 });
+
+        let rejected = ExchangeDataStatus::Error("Insufficient margin".to_string());
+        assert!(matches!(order_outcome(rejected), Err(ExchangeError::Rejected(r)) if r == "Insufficient margin"));
+
+        let unmatched = ExchangeDataStatus::Error("Order could not immediately match against any resting orders. asset=4".to_string());
+        assert!(matches!(order_outcome(unmatched), Err(ExchangeError::Unmatched(_))));
+    }
+
+    #[test]
+    fn test_order_state() {
+        let partly_cancelled: OrderStatusResponse = serde_json::from_value(json!({
+            "status": "order",
+            "order": {
+                "order": { "coin": "ETH", "side": "B", "limitPx": "2412.7", "sz": "0.3", "oid": 9, "origSz": "1.0" },
+                "status": "canceled",
+                "statusTimestamp": 1_724_361_546_645u64
+            }
+        }))
+        .unwrap();
+        assert_eq!(
+            order_state(partly_cancelled).unwrap(),
+            OrderState { status: OrderStatus::Cancelled, filled_sz: dec!(0.7) }
+        );
+
+        let unknown: OrderStatusResponse = serde_json::from_value(json!({ "status": "unknownOid" })).unwrap();
+        assert_eq!(order_state(unknown).unwrap(), OrderState::unknown());
+    }
+
+    #[test]
+    fn test_build_assets() {
+        let perps: PerpUniverse = serde_json::from_value(json!({
+            "universe": [
+                { "name": "BTC", "szDecimals": 5, "maxLeverage": 40 },
+                { "name": "OLD", "szDecimals": 1, "maxLeverage": 3, "isDelisted": true }
+            ]
+        }))
+        .unwrap();
+        let spot: SpotUniverse = serde_json::from_value(json!({
+            "universe": [{ "name": "@1", "tokens": [1, 0], "index": 1, "isCanonical": false }],
+            "tokens": [
+                { "name": "USDC", "index": 0, "szDecimals": 8 },
+                { "name": "HFUN", "index": 1, "szDecimals": 2 }
+            ]
+        }))
+        .unwrap();
+
+        let assets = build_assets(perps, spot);
+        assert_eq!(assets["BTC"], AssetInfo::perp(5, 40));
+        assert!(!assets.contains_key("OLD"));
+        assert_eq!(assets["@1"], AssetInfo::spot(2));
+        assert_eq!(assets["HFUN/USDC"], AssetInfo::spot(2));
+    }
+
+    #[tokio::test]
+    async fn test_talks_to_a_custom_network() {
+        use axum::{Json, Router, routing::post};
+        use crate::vault::{AgentKeyCipher, MasterKeys};
+
+        // a stand-in for the info endpoint
+        let app = Router::new().route(
+            "/info",
+            post(|Json(request): Json<serde_json::Value>| async move {
+                assert_eq!(request["type"], "allMids");
+                Json(json!({ "BTC": "50010.5" }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let network =
+            NetworkConfig::custom(&format!("http://{addr}"), &format!("ws://{addr}/ws")).unwrap();
+        let master = MasterKeys::new("test", AgentKeyCipher::from_hex(&"11".repeat(32)).unwrap());
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let exchange = HyperliquidExchange::new(&network, Arc::new(KeyVault::new(pool, master))).await.unwrap();
+
+        assert_eq!(exchange.mid_price("BTC").await.unwrap(), dec!(50010.5));
+        assert!(matches!(exchange.mid_price("ETH").await, Err(ExchangeError::NoMidPrice(_))));
+    }
+
+    #[tokio::test]
+    async fn test_rotated_key_keeps_signing() {
+        use axum::{Json, Router, routing::post};
+        use hyperliquid_rust_sdk::{AssetMeta, Meta};
+        use crate::vault::{AgentKeyCipher, MasterKeys};
+
+        // a stand-in for the exchange endpoint that fills every order
+        let app = Router::new().route(
+            "/exchange",
+            post(|| async {
+                Json(json!({
+                    "status": "ok",
+                    "response": { "type": "order", "data": { "statuses": [
+                        { "filled": { "totalSz": "0.02", "avgPx": "50010.5", "oid": 7 } }
+                    ] } }
+                }))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let addr = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let network =
+            NetworkConfig::custom(&format!("http://{addr}"), &format!("ws://{addr}/ws")).unwrap();
+        let master = MasterKeys::new("test", AgentKeyCipher::from_hex(&"11".repeat(32)).unwrap());
+        let pool = sqlx::PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let vault = Arc::new(KeyVault::new(pool, master));
+        let exchange = HyperliquidExchange::new(&network, vault.clone()).await.unwrap();
+
+        // the follower already signs with key 2, which replaced key 1
+        let client = ExchangeClient {
+            http_client: info_client(&exchange.api_url).await.unwrap().http_client,
+            wallet: "0xe908f86dbb4d55ac876378565aafeabc187f6690f046459397b17d9b9a19688e".parse().unwrap(),
+            meta: Meta { universe: vec![AssetMeta { name: "BTC".to_string(), sz_decimals: 5 }] },
+            vault_address: None,
+            coin_to_asset: HashMap::from([("BTC".to_string(), 0)]),
+        };
+        exchange
+            .clients
+            .write()
+            .await
+            .insert("0xfollower".to_string(), CachedClient { agent_key_id: 2, client: Arc::new(client) });
+        vault.mark_rotated(vec![1], 2);
+
+        let order = OrderRequest {
+            coin: "BTC".to_string(),
+            is_buy: true,
+            sz: dec!(0.02),
+            limit_px: dec!(50100),
+            reduce_only: false,
+            tif: TimeInForce::Ioc,
+        };
+        let stale = Signer { address: "0xfollower", agent_key_id: 1 };
+        assert!(matches!(exchange.place_order(stale, &order).await, Ok(OrderOutcome::Filled { .. })));
+    }
+}
@@ -0,0 +1,344 @@
+use std::collections::HashMap;
+use std::sync::{Mutex, MutexGuard};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+
+use crate::engine::metadata::AssetInfo;
+use crate::exchange::{
+    AccountState, Exchange, ExchangeError, OrderOutcome, OrderRequest, OrderState, OrderStatus, Signer,
+    TimeInForce,
+};
+
+/// Rejection reasons, worded like Hyperliquid's so callers treat them the same way.
+pub const IOC_NO_MATCH: &str = "Order could not immediately match against any resting orders.";
+pub const INSUFFICIENT_MARGIN: &str = "Insufficient margin to place order.";
+pub const REDUCE_ONLY_INCREASE: &str = "Reduce only order would increase position.";
+
+/// An order the mock received, with what it answered.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedOrder {
+    pub account: String,
+    pub order: OrderRequest,
+    pub outcome: Result<OrderOutcome, String>,
+}
+
+#[derive(Debug, Clone)]
+struct Level {
+    px: Decimal,
+    sz: Decimal,
+}
+
+/// Liquidity other traders rest on the book: bids best (highest) first, asks best (lowest) first.
+#[derive(Debug, Clone, Default)]
+struct Book {
+    bids: Vec<Level>,
+    asks: Vec<Level>,
+}
+
+/// An order the mock accepted, as `order_status` reports it.
+#[derive(Debug, Clone)]
+struct MockOrder {
+    account: String,
+    coin: String,
+    is_buy: bool,
+    sz: Decimal,
+    state: OrderState,
+}
+
+#[derive(Default)]
+struct MockState {
+    assets: HashMap<String, AssetInfo>,
+    books: HashMap<String, Book>,
+    accounts: HashMap<String, AccountState>,
+    orders: HashMap<u64, MockOrder>,
+    placed: Vec<PlacedOrder>,
+    next_oid: u64,
+}
+
+/// In-process exchange for tests: followers' orders match against a
+/// scripted order book, move their positions, and are rejected the way the real
+/// exchange would reject them.
+///
+/// Resting orders only fill later through [`MockExchange::fill_resting`].
+#[derive(Default)]
+pub struct MockExchange {
+    state: Mutex<MockState>,
+}
+
+impl MockExchange {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_asset(self, coin: &str, info: AssetInfo) -> Self {
+        self.add_asset(coin, info);
+        self
+    }
+
+    /// Set the book of `coin` from `(px, sz)` levels on each side, in any order.
+    pub fn with_book(self, coin: &str, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) -> Self {
+        self.set_book(coin, bids, asks);
+        self
+    }
+
+    pub fn with_account(self, address: &str, account_value: Decimal) -> Self {
+        self.state().accounts.entry(address.to_string()).or_default().account_value = account_value;
+        self
+    }
+
+    pub fn with_position(self, address: &str, coin: &str, szi: Decimal) -> Self {
+        self.state()
+            .accounts
+            .entry(address.to_string())
+            .or_default()
+            .positions
+            .insert(coin.to_string(), szi);
+        self
+    }
+
+    pub fn add_asset(&self, coin: &str, info: AssetInfo) {
+        self.state().assets.insert(coin.to_string(), info);
+    }
+
+    pub fn set_book(&self, coin: &str, bids: &[(Decimal, Decimal)], asks: &[(Decimal, Decimal)]) {
+        let levels = |side: &[(Decimal, Decimal)]| -> Vec<Level> {
+            side.iter().map(|&(px, sz)| Level { px, sz }).collect()
+        };
+        let mut book = Book { bids: levels(bids), asks: levels(asks) };
+        book.bids.sort_by_key(|l| std::cmp::Reverse(l.px));
+        book.asks.sort_by_key(|l| l.px);
+        self.state().books.insert(coin.to_string(), book);
+    }
+
+    /// Fill `sz` more of resting order `oid`, as if someone traded against it.
+    pub fn fill_resting(&self, oid: u64, sz: Decimal) {
+        let mut state = self.state();
+        let Some(order) = state.orders.get_mut(&oid) else {
+            panic!("no order {oid}");
+        };
+        assert_eq!(order.state.status, OrderStatus::Open, "order {oid} is not resting");
+        let sz = sz.min(order.sz - order.state.filled_sz);
+        order.state.filled_sz += sz;
+        if order.state.filled_sz == order.sz {
+            order.state.status = OrderStatus::Filled;
+        }
+        let (account, coin, signed) = (order.account.clone(), order.coin.clone(), if order.is_buy { sz } else { -sz });
+        *state.accounts.entry(account).or_default().positions.entry(coin).or_default() += signed;
+    }
+
+    /// Every order received so far, oldest first.
+    pub fn placed_orders(&self) -> Vec<PlacedOrder> {
+        self.state().placed.clone()
+    }
+
+    pub fn position(&self, address: &str, coin: &str) -> Decimal {
+        self.state()
+            .accounts
+            .get(address)
+            .and_then(|a| a.positions.get(coin).copied())
+            .unwrap_or_default()
+    }
+
+    fn state(&self) -> MutexGuard<'_, MockState> {
+        // a panicking test must not hide the state from the next assertion
+        self.state.lock().unwrap_or_else(|e| e.into_inner())
+    }
+}
+
+impl MockState {
+    fn execute(&mut self, account: &str, order: &OrderRequest) -> Result<OrderOutcome, ExchangeError> {
+        if !self.assets.contains_key(&order.coin) {
+            return Err(ExchangeError::UnknownAsset(order.coin.clone()));
+        }
+        let state = self.accounts.entry(account.to_string()).or_default();
+        if state.account_value <= Decimal::ZERO {
+            return Err(ExchangeError::Rejected(INSUFFICIENT_MARGIN.to_string()));
+        }
+
+        let position = state.positions.get(&order.coin).copied().unwrap_or_default();
+        let mut sz = order.sz;
+        if order.reduce_only {
+            let reducible = if order.is_buy { -position } else { position };
+            if reducible <= Decimal::ZERO {
+                return Err(ExchangeError::Rejected(REDUCE_ONLY_INCREASE.to_string()));
+            }
+            sz = sz.min(reducible);
+        }
+
+        let book = self.books.entry(order.coin.clone()).or_default();
+        let levels = if order.is_buy { &mut book.asks } else { &mut book.bids };
+        let mut filled = Decimal::ZERO;
+        let mut notional = Decimal::ZERO;
+        for level in levels.iter_mut() {
+            let crosses = if order.is_buy { level.px <= order.limit_px } else { level.px >= order.limit_px };
+            if !crosses || filled == sz {
+                break;
+            }
+            let take = level.sz.min(sz - filled);
+            level.sz -= take;
+            filled += take;
+            notional += take * level.px;
+        }
+        levels.retain(|l| l.sz > Decimal::ZERO);
+
+        self.next_oid += 1;
+        let oid = self.next_oid;
+
+        let status = match order.tif {
+            TimeInForce::Ioc if filled.is_zero() => return Err(ExchangeError::Unmatched(IOC_NO_MATCH.to_string())),
+            TimeInForce::Gtc if filled < sz => OrderStatus::Open,
+            TimeInForce::Ioc if filled < sz => OrderStatus::Cancelled,
+            _ => OrderStatus::Filled,
+        };
+        self.orders.insert(oid, MockOrder {
+            account: account.to_string(),
+            coin: order.coin.clone(),
+            is_buy: order.is_buy,
+            sz,
+            state: OrderState { status, filled_sz: filled },
+        });
+        if filled.is_zero() {
+            return Ok(OrderOutcome::Resting { oid });
+        }
+
+        let signed = if order.is_buy { filled } else { -filled };
+        *self
+            .accounts
+            .entry(account.to_string())
+            .or_default()
+            .positions
+            .entry(order.coin.clone())
+            .or_default() += signed;
+
+        Ok(OrderOutcome::Filled { oid, total_sz: filled, avg_px: notional / filled })
+    }
+}
+
+#[async_trait]
+impl Exchange for MockExchange {
+    async fn place_order(&self, signer: Signer<'_>, order: &OrderRequest) -> Result<OrderOutcome, ExchangeError> {
+        let mut state = self.state();
+        let result = state.execute(signer.address, order);
+        let outcome = match &result {
+            Ok(outcome) => Ok(outcome.clone()),
+            Err(e) => Err(e.to_string()),
+        };
+        state.placed.push(PlacedOrder {
+            account: signer.address.to_string(),
+            order: order.clone(),
+            outcome,
+        });
+        result
+    }
+
+    async fn cancel_order(&self, signer: Signer<'_>, _coin: &str, oid: u64) -> Result<bool, ExchangeError> {
+        let mut state = self.state();
+        match state.orders.get_mut(&oid) {
+            Some(order) if order.account == signer.address && order.state.status == OrderStatus::Open => {
+                order.state.status = OrderStatus::Cancelled;
+                Ok(true)
+            }
+            _ => Ok(false),
+        }
+    }
+
+    async fn order_status(&self, address: &str, oid: u64) -> Result<OrderState, ExchangeError> {
+        match self.state().orders.get(&oid) {
+            Some(order) if order.account == address => Ok(order.state),
+            _ => Ok(OrderState::unknown()),
+        }
+    }
+
+    async fn account_state(&self, address: &str) -> Result<AccountState, ExchangeError> {
+        Ok(self.state().accounts.get(address).cloned().unwrap_or_default())
+    }
+
+    async fn mid_price(&self, coin: &str) -> Result<Decimal, ExchangeError> {
+        let state = self.state();
+        let book = state.books.get(coin).ok_or_else(|| ExchangeError::NoMidPrice(coin.to_string()))?;
+        match (book.bids.first(), book.asks.first()) {
+            (Some(bid), Some(ask)) => Ok((bid.px + ask.px) / Decimal::TWO),
+            (Some(level), None) | (None, Some(level)) => Ok(level.px),
+            (None, None) => Err(ExchangeError::NoMidPrice(coin.to_string())),
+        }
+    }
+
+    async fn universe(&self) -> Result<HashMap<String, AssetInfo>, ExchangeError> {
+        Ok(self.state().assets.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    const FOLLOWER: Signer<'static> = Signer { address: "0xfollower", agent_key_id: 1 };
+
+    fn exchange() -> MockExchange {
+        MockExchange::new()
+            .with_asset("ETH", AssetInfo::perp(4, 25))
+            .with_book("ETH", &[(dec!(1999), dec!(5))], &[(dec!(2001), dec!(1)), (dec!(2002), dec!(1))])
+            .with_account("0xfollower", dec!(10000))
+    }
+
+    fn order(is_buy: bool, limit_px: Decimal, sz: Decimal, tif: TimeInForce) -> OrderRequest {
+        OrderRequest { coin: "ETH".to_string(), is_buy, reduce_only: false, limit_px, sz, tif }
+    }
+
+    #[tokio::test]
+    async fn test_matching() {
+        let exchange = exchange();
+        assert_eq!(exchange.mid_price("ETH").await.unwrap(), dec!(2000));
+
+        // walks two ask levels
+        let outcome = exchange.place_order(FOLLOWER, &order(true, dec!(2002), dec!(1.5), TimeInForce::Ioc)).await.unwrap();
+        assert_eq!(outcome, OrderOutcome::Filled { oid: 1, total_sz: dec!(1.5), avg_px: (dec!(2001) + dec!(1001)) / dec!(1.5) });
+        assert_eq!(exchange.position("0xfollower", "ETH"), dec!(1.5));
+
+        // nothing left inside the limit
+        let err = exchange.place_order(FOLLOWER, &order(true, dec!(2001), dec!(1), TimeInForce::Ioc)).await.unwrap_err();
+        assert!(matches!(err, ExchangeError::Unmatched(r) if r == IOC_NO_MATCH));
+
+        // GTC rests instead, can fill later and be cancelled once
+        let outcome = exchange.place_order(FOLLOWER, &order(true, dec!(1990), dec!(1), TimeInForce::Gtc)).await.unwrap();
+        let OrderOutcome::Resting { oid } = outcome else { panic!("expected a resting order, got {outcome:?}") };
+        exchange.fill_resting(oid, dec!(0.25));
+        assert_eq!(exchange.position("0xfollower", "ETH"), dec!(1.75));
+        assert!(exchange.cancel_order(FOLLOWER, "ETH", oid).await.unwrap());
+        assert!(!exchange.cancel_order(FOLLOWER, "ETH", oid).await.unwrap());
+        let state = exchange.order_status("0xfollower", oid).await.unwrap();
+        assert_eq!(state, OrderState { status: OrderStatus::Cancelled, filled_sz: dec!(0.25) });
+
+        assert_eq!(exchange.placed_orders().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_rejections() {
+        let exchange = exchange();
+
+        let mut close = order(false, dec!(1999), dec!(1), TimeInForce::Ioc);
+        close.reduce_only = true;
+        let err = exchange.place_order(FOLLOWER, &close).await.unwrap_err();
+        assert!(matches!(err, ExchangeError::Rejected(r) if r == REDUCE_ONLY_INCREASE));
+
+        let broke = Signer { address: "0xbroke", agent_key_id: 2 };
+        let err = exchange.place_order(broke, &order(true, dec!(2001), dec!(1), TimeInForce::Ioc)).await.unwrap_err();
+        assert!(matches!(err, ExchangeError::Rejected(r) if r == INSUFFICIENT_MARGIN));
+
+        let mut unknown = order(true, dec!(1), dec!(1), TimeInForce::Ioc);
+        unknown.coin = "NOPE".to_string();
+        assert!(matches!(exchange.place_order(FOLLOWER, &unknown).await, Err(ExchangeError::UnknownAsset(_))));
+    }
+
+    #[tokio::test]
+    async fn test_reduce_only_is_capped_at_position() {
+        let exchange = exchange().with_position("0xfollower", "ETH", dec!(0.5));
+
+        let mut close = order(false, dec!(1999), dec!(2), TimeInForce::Ioc);
+        close.reduce_only = true;
+        let outcome = exchange.place_order(FOLLOWER, &close).await.unwrap();
+        assert_eq!(outcome, OrderOutcome::Filled { oid: 1, total_sz: dec!(0.5), avg_px: dec!(1999) });
+        assert_eq!(exchange.position("0xfollower", "ETH"), dec!(0));
+    }
+}
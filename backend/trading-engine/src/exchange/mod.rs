@@ -0,0 +1,142 @@
+// The executor talks to an exchange only through the `Exchange` trait: Hyperliquid in
+// production, `ReplayExchange` in backtests and the in-process `MockExchange` in tests.
+
+use std::collections::{HashMap, HashSet};
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use thiserror::Error;
+
+use crate::engine::metadata::AssetInfo;
+use crate::engine::parser::ParseError;
+
+pub mod hyperliquid;
+#[cfg(test)]
+pub mod mock;
+pub mod replay;
+
+#[derive(Error, Debug)]
+pub enum ExchangeError {
+    #[error("Invalid agent key: {0}")]
+    InvalidAgentKey(String),
+    #[error("Exchange client initialization failed: {0}")]
+    ClientInitialization(String),
+    #[error("Exchange request failed: {0}")]
+    Request(String),
+    #[error("No data in exchange response")]
+    NoDataInResponse,
+    #[error("Order rejected by exchange: {0}")]
+    Rejected(String),
+    /// An immediate-or-cancel order found nothing to match within its limit
+    #[error("Order found no liquidity within its limit: {0}")]
+    Unmatched(String),
+    #[error("Unexpected status from exchange: {0}")]
+    UnexpectedStatus(String),
+    #[error("Invalid address: {0}")]
+    InvalidAddress(String),
+    #[error("Unknown asset: {0}")]
+    UnknownAsset(String),
+    #[error("No mid price for {0}")]
+    NoMidPrice(String),
+    #[error("Failed to convert decimal to f64")]
+    DecimalConversion,
+    #[error("Failed to parse exchange response: {0}")]
+    ParseError(#[from] ParseError),
+}
+
+/// Follower account an order is signed for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Signer<'a> {
+    pub address: &'a str,
+    /// Vault id of the agent key that signs for `address`
+    pub agent_key_id: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TimeInForce {
+    Ioc,
+    Gtc,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct OrderRequest {
+    pub coin: String,
+    pub is_buy: bool,
+    pub reduce_only: bool,
+    pub limit_px: Decimal,
+    pub sz: Decimal,
+    pub tif: TimeInForce,
+}
+
+/// What the exchange did with a follower order.
+#[derive(Debug, Clone, PartialEq)]
+pub enum OrderOutcome {
+    Filled { oid: u64, total_sz: Decimal, avg_px: Decimal },
+    Resting { oid: u64 },
+}
+
+impl OrderOutcome {
+    pub fn oid(&self) -> u64 {
+        match self {
+            OrderOutcome::Filled { oid, .. } | OrderOutcome::Resting { oid } => *oid,
+        }
+    }
+}
+
+/// Where a placed order stands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum OrderStatus {
+    /// On the book, possibly partly filled
+    Open,
+    Filled,
+    /// Cancelled by us or by the exchange, possibly after partly filling
+    Cancelled,
+    Rejected,
+    /// The exchange does not know the order
+    Unknown,
+}
+
+/// A placed order as the exchange reports it now.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct OrderState {
+    pub status: OrderStatus,
+    /// Size executed so far
+    pub filled_sz: Decimal,
+}
+
+impl OrderState {
+    pub fn unknown() -> Self {
+        Self { status: OrderStatus::Unknown, filled_sz: Decimal::ZERO }
+    }
+}
+
+/// Positions and margin of an account.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AccountState {
+    pub account_value: Decimal,
+    /// Signed size per coin, positive long and negative short
+    pub positions: HashMap<String, Decimal>,
+}
+
+#[async_trait]
+pub trait Exchange: Send + Sync {
+    /// Place an order signed with the follower's agent key. An order refused by the
+    /// matching engine is `ExchangeError::Rejected` with the exchange's reason, or
+    /// `ExchangeError::Unmatched` for an IOC order with nothing to match.
+    async fn place_order(&self, signer: Signer<'_>, order: &OrderRequest) -> Result<OrderOutcome, ExchangeError>;
+
+    /// Cancel a resting order; `false` when it was no longer open (usually filled).
+    async fn cancel_order(&self, signer: Signer<'_>, coin: &str, oid: u64) -> Result<bool, ExchangeError>;
+
+    /// Status and filled size of order `oid` placed for `address`.
+    async fn order_status(&self, address: &str, oid: u64) -> Result<OrderState, ExchangeError>;
+
+    async fn account_state(&self, address: &str) -> Result<AccountState, ExchangeError>;
+
+    async fn mid_price(&self, coin: &str) -> Result<Decimal, ExchangeError>;
+
+    /// Trading rules of every listed asset, keyed by coin name.
+    async fn universe(&self) -> Result<HashMap<String, AssetInfo>, ExchangeError>;
+
+    /// Release per-account resources of followers that are no longer copying.
+    async fn retain_accounts(&self, _active: &HashSet<String>) {}
+}
@@ -0,0 +1,67 @@
+use std::collections::HashMap;
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+
+use crate::engine::metadata::AssetInfo;
+use crate::engine::parser::{parse_price, parse_size};
+use crate::exchange::{AccountState, Exchange, ExchangeError, OrderOutcome, OrderRequest, OrderState, Signer};
+use crate::hyperliquid::ws::WsFill;
+
+/// Lot size decimals assumed for every replayed coin: BTC's, the finest among perps,
+/// so follower sizes are never rounded away by a coarser guess.
+const REPLAY_SZ_DECIMALS: u32 = 5;
+
+/// Exchange reconstructed from a leader's historical fills, so a backtest can run
+/// the executor offline. It only knows the traded assets and never takes orders.
+pub struct ReplayExchange {
+    assets: HashMap<String, AssetInfo>,
+}
+
+impl ReplayExchange {
+    /// Infer each coin's rules from the fills: every leader fill was at a valid price
+    /// and size, so the real decimals are at least the most the fills used.
+    pub fn from_fills(fills: &[WsFill]) -> Result<Self, ExchangeError> {
+        let mut assets: HashMap<String, AssetInfo> = HashMap::new();
+        for fill in fills {
+            let sz_decimals = parse_size(&fill.sz)?.normalize().scale().max(REPLAY_SZ_DECIMALS);
+            let price_decimals = parse_price(&fill.px)?.normalize().scale();
+            let asset = assets.entry(fill.coin.clone()).or_insert(AssetInfo {
+                sz_decimals,
+                max_price_decimals: price_decimals,
+                // spot pairs are "@<index>" or "BASE/QUOTE"; backtested copies are
+                // paper copies, which are never checked against leverage
+                max_leverage: (!fill.coin.starts_with('@') && !fill.coin.contains('/')).then_some(1),
+            });
+            asset.sz_decimals = asset.sz_decimals.max(sz_decimals);
+            asset.max_price_decimals = asset.max_price_decimals.max(price_decimals);
+        }
+        Ok(Self { assets })
+    }
+}
+
+#[async_trait]
+impl Exchange for ReplayExchange {
+    async fn place_order(&self, _signer: Signer<'_>, _order: &OrderRequest) -> Result<OrderOutcome, ExchangeError> {
+        Err(ExchangeError::Rejected("the replay exchange does not accept orders".to_string()))
+    }
+
+    async fn cancel_order(&self, _signer: Signer<'_>, _coin: &str, _oid: u64) -> Result<bool, ExchangeError> {
+        Ok(false)
+    }
+
+    async fn order_status(&self, _address: &str, _oid: u64) -> Result<OrderState, ExchangeError> {
+        Ok(OrderState::unknown())
+    }
+
+    async fn account_state(&self, _address: &str) -> Result<AccountState, ExchangeError> {
+        Ok(AccountState::default())
+    }
+
+    async fn mid_price(&self, coin: &str) -> Result<Decimal, ExchangeError> {
+        Err(ExchangeError::NoMidPrice(coin.to_string()))
+    }
+
+    async fn universe(&self) -> Result<HashMap<String, AssetInfo>, ExchangeError> {
+        Ok(self.assets.clone())
+    }
+}
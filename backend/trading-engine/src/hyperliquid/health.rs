@@ -0,0 +1,138 @@
+use std::collections::HashMap;
+use std::sync::RwLock;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ConnectionState {
+    /// No leader assigned, so no socket is open
+    Idle,
+    Connecting,
+    Connected,
+    /// Lost and waiting out the backoff before the next attempt
+    Reconnecting,
+}
+
+#[derive(Debug, Clone)]
+struct ConnectionHealth {
+    state: ConnectionState,
+    connected_since: Option<DateTime<Utc>>,
+    last_message_at: Option<DateTime<Utc>>,
+    reconnects: u64,
+    last_error: Option<String>,
+}
+
+#[derive(Debug)]
+struct Assignment {
+    connection: usize,
+    last_fill_at: Option<DateTime<Utc>>,
+}
+
+/// What operators see for one leader subscription.
+#[derive(Debug, Clone, Serialize)]
+pub struct SubscriptionStatus {
+    pub address: String,
+    pub connection: usize,
+    pub state: ConnectionState,
+    /// Fills of this leader are not being received right now
+    pub blind: bool,
+    pub connected_since: Option<DateTime<Utc>>,
+    /// Last frame of any kind on the leader's socket
+    pub last_message_at: Option<DateTime<Utc>>,
+    pub last_fill_at: Option<DateTime<Utc>>,
+    pub reconnects: u64,
+    pub last_error: Option<String>,
+}
+
+/// Live state of the pool's sockets and of the leaders assigned to them, shared
+/// with the API.
+#[derive(Debug)]
+pub struct PoolHealth {
+    connections: Vec<RwLock<ConnectionHealth>>,
+    /// Keyed by address as subscribed
+    subscriptions: RwLock<HashMap<String, Assignment>>,
+}
+
+impl PoolHealth {
+    pub fn new(connections: usize) -> Self {
+        Self {
+            connections: (0..connections)
+                .map(|_| {
+                    RwLock::new(ConnectionHealth {
+                        state: ConnectionState::Idle,
+                        connected_since: None,
+                        last_message_at: None,
+                        reconnects: 0,
+                        last_error: None,
+                    })
+                })
+                .collect(),
+            subscriptions: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn subscribed(&self, address: &str, connection: usize) {
+        self.subscriptions.write().unwrap_or_else(|e| e.into_inner()).insert(address.to_string(), Assignment { connection, last_fill_at: None });
+    }
+
+    pub fn unsubscribed(&self, address: &str) {
+        self.subscriptions.write().unwrap_or_else(|e| e.into_inner()).remove(address);
+    }
+
+    pub fn set_state(&self, connection: usize, state: ConnectionState) {
+        self.update(connection, |c| {
+            c.state = state;
+            c.connected_since = (state == ConnectionState::Connected).then(Utc::now);
+        });
+    }
+
+    pub fn connection_lost(&self, connection: usize, error: String) {
+        self.update(connection, |c| {
+            c.state = ConnectionState::Reconnecting;
+            c.connected_since = None;
+            c.reconnects += 1;
+            c.last_error = Some(error);
+        });
+    }
+
+    pub fn message_received(&self, connection: usize) {
+        self.update(connection, |c| c.last_message_at = Some(Utc::now()));
+    }
+
+    pub fn fill_received(&self, address: &str) {
+        if let Some(assignment) = self.subscriptions.write().unwrap_or_else(|e| e.into_inner()).get_mut(address) {
+            assignment.last_fill_at = Some(Utc::now());
+        }
+    }
+
+    /// Every subscription, blind ones first.
+    pub fn statuses(&self) -> Vec<SubscriptionStatus> {
+        let subscriptions = self.subscriptions.read().unwrap_or_else(|e| e.into_inner());
+        let mut statuses: Vec<SubscriptionStatus> = subscriptions
+            .iter()
+            .filter_map(|(address, assignment)| {
+                let health = self.connections.get(assignment.connection)?.read().unwrap_or_else(|e| e.into_inner()).clone();
+                Some(SubscriptionStatus {
+                    address: address.clone(),
+                    connection: assignment.connection,
+                    state: health.state,
+                    blind: health.state != ConnectionState::Connected,
+                    connected_since: health.connected_since,
+                    last_message_at: health.last_message_at,
+                    last_fill_at: assignment.last_fill_at,
+                    reconnects: health.reconnects,
+                    last_error: health.last_error,
+                })
+            })
+            .collect();
+        statuses.sort_by(|a, b| b.blind.cmp(&a.blind).then_with(|| a.address.cmp(&b.address)));
+        statuses
+    }
+
+    fn update(&self, connection: usize, f: impl FnOnce(&mut ConnectionHealth)) {
+        if let Some(health) = self.connections.get(connection) {
+            f(&mut health.write().unwrap_or_else(|e| e.into_inner()));
+        }
+    }
+}
@@ -0,0 +1,74 @@
+use rust_decimal::Decimal;
+use serde::Deserialize;
+use serde::de::DeserializeOwned;
+use serde_json::{json, Value};
+use thiserror::Error;
+
+use crate::hyperliquid::ws::WsFill;
+
+#[derive(Error, Debug)]
+pub enum InfoError {
+    #[error("Info request failed: {0}")]
+    Request(String),
+    #[error("Unexpected info response: {0}")]
+    Response(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ClearinghouseState {
+    margin_summary: MarginSummary,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct MarginSummary {
+    account_value: String,
+}
+
+/// The info endpoint queries about leaders that the SDK has no call for.
+#[derive(Debug, Clone)]
+pub struct InfoApi {
+    http: reqwest::Client,
+    info_url: String,
+}
+
+impl InfoApi {
+    pub fn new(api_url: &str) -> Self {
+        Self { http: reqwest::Client::new(), info_url: format!("{api_url}/info") }
+    }
+
+    /// Fills of `user` from `start` to `end` (unix ms), oldest first; at most one
+    /// page of 2000.
+    pub async fn user_fills_by_time(&self, user: &str, start: u64, end: u64) -> Result<Vec<WsFill>, InfoError> {
+        let mut fills: Vec<WsFill> = self
+            .post(json!({
+                "type": "userFillsByTime",
+                "user": user,
+                "startTime": start,
+                "endTime": end,
+            }))
+            .await?;
+        fills.sort_by_key(|f| (f.time, f.tid));
+        Ok(fills)
+    }
+
+    /// Value of the perp account of `user`, margin included.
+    pub async fn account_value(&self, user: &str) -> Result<Decimal, InfoError> {
+        let state: ClearinghouseState = self.post(json!({ "type": "clearinghouseState", "user": user })).await?;
+        let value = &state.margin_summary.account_value;
+        value.parse().map_err(|_| InfoError::Response(format!("account value {value:?}")))
+    }
+
+    async fn post<T: DeserializeOwned>(&self, request: Value) -> Result<T, InfoError> {
+        let response = self
+            .http
+            .post(&self.info_url)
+            .json(&request)
+            .send()
+            .await
+            .and_then(|r| r.error_for_status())
+            .map_err(|e| InfoError::Request(e.to_string()))?;
+        response.json().await.map_err(|e| InfoError::Response(e.to_string()))
+    }
+}
@@ -1 +1,5 @@
+pub mod health;
+pub mod info;
+pub mod network;
+pub mod watermarks;
 pub mod ws;
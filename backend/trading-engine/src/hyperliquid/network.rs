@@ -0,0 +1,166 @@
+use std::fmt;
+use hyperliquid_rust_sdk::{LOCAL_API_URL, MAINNET_API_URL, TESTNET_API_URL};
+use serde::Deserialize;
+use thiserror::Error;
+use url::Url;
+
+#[derive(Error, Debug, PartialEq)]
+pub enum NetworkError {
+    #[error("Unknown network {0:?}, expected mainnet, testnet, local or custom")]
+    UnknownNetwork(String),
+    #[error("Custom network needs both an API and a WebSocket URL")]
+    MissingUrl,
+    #[error("API and WebSocket URLs only apply to the custom network, not {0}")]
+    UnexpectedUrl(Network),
+    #[error("Invalid {0} URL {1:?}: {2}")]
+    InvalidUrl(&'static str, String, String),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum Network {
+    Mainnet,
+    Testnet,
+    /// A node or stand-in on localhost:3001
+    Local,
+    Custom,
+}
+
+impl Network {
+    pub fn parse(value: &str) -> Result<Self, NetworkError> {
+        match value.to_lowercase().as_str() {
+            "mainnet" => Ok(Self::Mainnet),
+            "testnet" => Ok(Self::Testnet),
+            "local" | "localhost" => Ok(Self::Local),
+            "custom" => Ok(Self::Custom),
+            _ => Err(NetworkError::UnknownNetwork(value.to_string())),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Mainnet => "mainnet",
+            Self::Testnet => "testnet",
+            Self::Local => "local",
+            Self::Custom => "custom",
+        }
+    }
+}
+
+impl TryFrom<String> for Network {
+    type Error = NetworkError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Self::parse(&value)
+    }
+}
+
+impl fmt::Display for Network {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Where leader fills are streamed from and follower orders are sent to. Both
+/// always point at the same network.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetworkConfig {
+    pub network: Network,
+    /// HTTP API root, without the `/info` or `/exchange` path
+    pub api_url: String,
+    pub ws_url: String,
+}
+
+impl NetworkConfig {
+    pub fn mainnet() -> Self {
+        Self::known(Network::Mainnet, MAINNET_API_URL)
+    }
+
+    pub fn testnet() -> Self {
+        Self::known(Network::Testnet, TESTNET_API_URL)
+    }
+
+    pub fn local() -> Self {
+        Self::known(Network::Local, LOCAL_API_URL)
+    }
+
+    /// A stand-in server. Orders to it are signed as for testnet.
+    pub fn custom(api_url: &str, ws_url: &str) -> Result<Self, NetworkError> {
+        Ok(Self {
+            network: Network::Custom,
+            api_url: check_url("API", api_url, &["http", "https"])?,
+            ws_url: check_url("WebSocket", ws_url, &["ws", "wss"])?,
+        })
+    }
+
+    pub fn resolve(network: Network, api_url: Option<String>, ws_url: Option<String>) -> Result<Self, NetworkError> {
+        match (network, api_url, ws_url) {
+            (Network::Custom, Some(api_url), Some(ws_url)) => Self::custom(&api_url, &ws_url),
+            (Network::Custom, _, _) => Err(NetworkError::MissingUrl),
+            (network, None, None) => Ok(match network {
+                Network::Mainnet => Self::mainnet(),
+                Network::Testnet => Self::testnet(),
+                _ => Self::local(),
+            }),
+            // a half-overridden network would stream one chain and trade on another
+            (network, _, _) => Err(NetworkError::UnexpectedUrl(network)),
+        }
+    }
+
+    fn known(network: Network, api_url: &str) -> Self {
+        // the WebSocket API lives on the same host
+        let ws_url = format!("ws{}/ws", api_url.trim_start_matches("http"));
+        Self { network, api_url: api_url.to_string(), ws_url }
+    }
+}
+
+fn check_url(kind: &'static str, value: &str, schemes: &[&str]) -> Result<String, NetworkError> {
+    let invalid = |reason: String| NetworkError::InvalidUrl(kind, value.to_string(), reason);
+    let url = Url::parse(value).map_err(|e| invalid(e.to_string()))?;
+    if !schemes.contains(&url.scheme()) {
+        return Err(invalid(format!("scheme must be one of {}", schemes.join(", "))));
+    }
+    if url.host_str().is_none() {
+        return Err(invalid("no host".to_string()));
+    }
+    Ok(value.trim_end_matches('/').to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_known_networks() {
+        let mainnet = NetworkConfig::mainnet();
+        assert_eq!(mainnet.ws_url, "wss://api.hyperliquid.xyz/ws");
+
+        let testnet = NetworkConfig::resolve(Network::parse("Testnet").unwrap(), None, None).unwrap();
+        assert_eq!(testnet.ws_url, "wss://api.hyperliquid-testnet.xyz/ws");
+
+        assert_eq!(NetworkConfig::local().ws_url, "ws://localhost:3001/ws");
+        assert!(Network::parse("devnet").is_err());
+    }
+
+    #[test]
+    fn test_custom_network() {
+        let custom = NetworkConfig::resolve(
+            Network::Custom,
+            Some("http://127.0.0.1:8080/".to_string()),
+            Some("ws://127.0.0.1:8080/ws".to_string()),
+        )
+        .unwrap();
+        assert_eq!(custom.api_url, "http://127.0.0.1:8080");
+
+        assert_eq!(
+            NetworkConfig::resolve(Network::Custom, Some("http://127.0.0.1:8080".to_string()), None),
+            Err(NetworkError::MissingUrl)
+        );
+        assert_eq!(
+            NetworkConfig::resolve(Network::Mainnet, None, Some("ws://127.0.0.1:8080/ws".to_string())),
+            Err(NetworkError::UnexpectedUrl(Network::Mainnet))
+        );
+        assert!(NetworkConfig::custom("ws://127.0.0.1:8080", "ws://127.0.0.1:8080/ws").is_err());
+        assert!(NetworkConfig::custom("http://127.0.0.1:8080", "not a url").is_err());
+    }
+}
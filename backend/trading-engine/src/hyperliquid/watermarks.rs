@@ -0,0 +1,258 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::{Mutex, RwLock};
+use std::time::Duration;
+use log::{error, info};
+use serde::{Deserialize, Serialize};
+use sqlx::PgPool;
+use sqlx::types::Json;
+
+use crate::hyperliquid::ws::WsFill;
+
+/// How often advanced marks are written back to the database.
+pub const FLUSH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Identity of a leader fill.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct FillId {
+    pub hash: String,
+    pub tid: u64,
+}
+
+impl FillId {
+    fn of(fill: &WsFill) -> Self {
+        Self { hash: fill.hash.clone(), tid: fill.tid }
+    }
+}
+
+/// Time of the latest fill seen for a leader, with every fill seen at exactly
+/// that millisecond. Nothing is assumed about how tids order within it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Watermark {
+    pub time: u64,
+    pub fills: HashSet<FillId>,
+}
+
+impl Watermark {
+    fn of(fill: &WsFill) -> Self {
+        Self {
+            time: fill.time,
+            fills: HashSet::from([FillId::of(fill)]),
+        }
+    }
+
+    fn is_before(&self, fill: &WsFill) -> bool {
+        self.time < fill.time || (self.time == fill.time && !self.fills.contains(&FillId::of(fill)))
+    }
+
+    /// Take `fill` into the mark; returns false if that changed nothing.
+    fn advance(&mut self, fill: &WsFill) -> bool {
+        if fill.time > self.time {
+            *self = Self::of(fill);
+            true
+        } else {
+            fill.time == self.time && self.fills.insert(FillId::of(fill))
+        }
+    }
+}
+
+/// Per-leader high-water marks of forwarded fills, persisted in `leader_fill_marks`
+/// so the `userFills` snapshot sent on every (re)subscription can be reconciled:
+/// fills after the mark were missed while disconnected and are forwarded, older
+/// ones were already handled.
+pub struct FillWatermarks {
+    /// Keyed by lowercased leader address
+    marks: RwLock<HashMap<String, Watermark>>,
+    dirty: Mutex<HashSet<String>>,
+    /// Missed fills older than this are not copied anymore, only acknowledged
+    max_age: Option<Duration>,
+}
+
+impl FillWatermarks {
+    pub fn new(max_age: Option<Duration>) -> Self {
+        Self {
+            marks: RwLock::new(HashMap::new()),
+            dirty: Mutex::new(HashSet::new()),
+            max_age,
+        }
+    }
+
+    /// Load the persisted marks; returns how many leaders have one.
+    pub async fn load(&self, pool: &PgPool) -> Result<usize, sqlx::Error> {
+        let rows: Vec<(String, i64, Json<HashSet<FillId>>)> =
+            sqlx::query_as("SELECT trader_address, last_time, last_fills FROM leader_fill_marks")
+                .fetch_all(pool)
+                .await?;
+
+        let mut marks = self.marks.write().unwrap_or_else(|e| e.into_inner());
+        for (leader, time, fills) in rows {
+            marks.insert(leader, Watermark { time: time as u64, fills: fills.0 });
+        }
+        Ok(marks.len())
+    }
+
+    pub fn get(&self, leader: &str) -> Option<Watermark> {
+        self.marks.read().unwrap_or_else(|e| e.into_inner()).get(&leader.to_lowercase()).cloned()
+    }
+
+    /// Advance `leader`'s mark past a streamed fill.
+    pub fn observe(&self, leader: &str, fill: &WsFill) {
+        let leader = leader.to_lowercase();
+        let mut marks = self.marks.write().unwrap_or_else(|e| e.into_inner());
+        let advanced = match marks.get_mut(&leader) {
+            Some(mark) => mark.advance(fill),
+            None => {
+                marks.insert(leader.clone(), Watermark::of(fill));
+                true
+            }
+        };
+        if advanced {
+            self.dirty.lock().unwrap_or_else(|e| e.into_inner()).insert(leader);
+        }
+    }
+
+    /// Pick the fills of a snapshot that still have to be copied, oldest first, and
+    /// advance the mark past the whole snapshot.
+    ///
+    /// A leader without a mark has never been streamed: its snapshot is history and
+    /// only sets the mark.
+    pub fn reconcile_snapshot(&self, leader: &str, mut fills: Vec<WsFill>, now_ms: u64) -> Vec<WsFill> {
+        fills.sort_by_key(|f| (f.time, f.tid));
+        let Some(mark) = self.get(leader) else {
+            for fill in &fills {
+                self.observe(leader, fill);
+            }
+            return Vec::new();
+        };
+
+        let missed: Vec<WsFill> = fills.into_iter().filter(|f| mark.is_before(f)).collect();
+        for fill in &missed {
+            self.observe(leader, fill);
+        }
+
+        let cutoff = self.max_age.map(|age| now_ms.saturating_sub(age.as_millis() as u64));
+        let (fresh, stale): (Vec<_>, Vec<_>) = missed.into_iter().partition(|f| cutoff.is_none_or(|c| f.time >= c));
+        if !stale.is_empty() {
+            info!("Not copying {} missed fills of {} older than the freshness window", stale.len(), leader);
+        }
+        fresh
+    }
+
+    /// Write every mark advanced since the last flush.
+    pub async fn flush(&self, pool: &PgPool) -> Result<usize, sqlx::Error> {
+        let leaders: Vec<String> = self.dirty.lock().unwrap_or_else(|e| e.into_inner()).drain().collect();
+        let marks: Vec<(String, Watermark)> = {
+            let marks = self.marks.read().unwrap_or_else(|e| e.into_inner());
+            leaders.iter().filter_map(|l| marks.get(l).map(|m| (l.clone(), m.clone()))).collect()
+        };
+
+        for (i, (leader, mark)) in marks.iter().enumerate() {
+            let written = sqlx::query(
+                "INSERT INTO leader_fill_marks (trader_address, last_time, last_fills, updated_at)
+                 VALUES ($1, $2, $3, NOW())
+                 ON CONFLICT (trader_address) DO UPDATE
+                 SET last_time = EXCLUDED.last_time, last_fills = EXCLUDED.last_fills, updated_at = NOW()
+                 WHERE leader_fill_marks.last_time <= EXCLUDED.last_time",
+            )
+            .bind(leader)
+            .bind(mark.time as i64)
+            .bind(Json(&mark.fills))
+            .execute(pool)
+            .await;
+
+            if let Err(e) = written {
+                // retry the unwritten ones next time
+                let mut dirty = self.dirty.lock().unwrap_or_else(|e| e.into_inner());
+                dirty.extend(marks[i..].iter().map(|(l, _)| l.clone()));
+                return Err(e);
+            }
+        }
+        Ok(marks.len())
+    }
+
+    /// Flush every [`FLUSH_INTERVAL`] for as long as the process runs.
+    pub async fn run_flusher(&self, pool: PgPool) {
+        loop {
+            tokio::time::sleep(FLUSH_INTERVAL).await;
+            if let Err(e) = self.flush(&pool).await {
+                error!("Failed to persist leader fill marks: {}", e);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn fill(time: u64, tid: u64) -> WsFill {
+        WsFill {
+            coin: "BTC".to_string(),
+            px: "50000".to_string(),
+            sz: "0.1".to_string(),
+            side: "B".to_string(),
+            time,
+            hash: format!("0xhash{tid}"),
+            oid: tid,
+            tid,
+            start_position: None,
+            closed_pnl: None,
+            dir: Some("Open Long".to_string()),
+            crossed: true,
+            fee: "0".to_string(),
+            fee_token: "USDC".to_string(),
+        }
+    }
+
+    fn last_tids(marks: &FillWatermarks) -> Vec<u64> {
+        let mut tids: Vec<u64> = marks.get("0xleader").unwrap().fills.iter().map(|f| f.tid).collect();
+        tids.sort();
+        tids
+    }
+
+    fn tids(fills: &[WsFill]) -> Vec<u64> {
+        fills.iter().map(|f| f.tid).collect()
+    }
+
+    #[test]
+    fn test_first_snapshot_only_sets_the_mark() {
+        let marks = FillWatermarks::new(None);
+        assert!(marks.reconcile_snapshot("0xLeader", vec![fill(2, 20), fill(1, 10)], 100).is_empty());
+        assert_eq!(last_tids(&marks), vec![20]);
+    }
+
+    #[test]
+    fn test_snapshot_forwards_fills_after_the_mark() {
+        let marks = FillWatermarks::new(None);
+        marks.observe("0xleader", &fill(10, 100));
+
+        // other trades in the same millisecond are new whatever their tid; the
+        // marked one is not
+        let snapshot = vec![fill(12, 120), fill(9, 90), fill(10, 100), fill(10, 101), fill(10, 99)];
+        assert_eq!(tids(&marks.reconcile_snapshot("0xleader", snapshot.clone(), 100)), vec![99, 101, 120]);
+        assert_eq!(last_tids(&marks), vec![120]);
+
+        // the same snapshot again (another reconnect) forwards nothing
+        assert!(marks.reconcile_snapshot("0xleader", snapshot, 100).is_empty());
+    }
+
+    #[test]
+    fn test_stale_missed_fills_are_acknowledged_only() {
+        let marks = FillWatermarks::new(Some(Duration::from_secs(60)));
+        marks.observe("0xleader", &fill(1_000, 1));
+
+        let now = 200_000;
+        let snapshot = vec![fill(100_000, 2), fill(150_000, 3)];
+        assert_eq!(tids(&marks.reconcile_snapshot("0xleader", snapshot, now)), vec![3]);
+        assert_eq!(last_tids(&marks), vec![3]);
+    }
+
+    #[test]
+    fn test_observe_never_moves_backwards() {
+        let marks = FillWatermarks::new(None);
+        marks.observe("0xleader", &fill(5, 50));
+        marks.observe("0xLEADER", &fill(4, 40));
+        marks.observe("0xleader", &fill(5, 49));
+        assert_eq!(marks.get("0xleader").map(|m| m.time), Some(5));
+        assert_eq!(last_tids(&marks), vec![49, 50]);
+    }
+}
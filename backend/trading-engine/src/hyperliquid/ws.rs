@@ -1,12 +1,20 @@
-use std::{ thread::sleep, time::Duration};
+use std::collections::HashMap;
+use std::hash::{BuildHasher, RandomState};
+use std::sync::Arc;
+use std::time::Duration;
 use futures_util::{SinkExt, StreamExt};
+use log::{error, info};
 use serde::Deserialize;
+use tokio::sync::{broadcast, mpsc};
+use tokio::time::{interval_at, sleep_until, Instant, MissedTickBehavior};
 use tokio_tungstenite::tungstenite::Message;
-use tokio_tungstenite::tungstenite::client::IntoClientRequest;
 use serde::Serialize;
 use thiserror::Error;
 
 use crate::channel::WsFillChannel;
+use crate::engine::parser::parse_side;
+use crate::hyperliquid::health::{ConnectionState, PoolHealth};
+use crate::hyperliquid::watermarks::FillWatermarks;
 
 #[derive(Error, Debug)]
 pub enum WsError {
@@ -16,14 +24,23 @@ pub enum WsError {
     SerializationFailed(#[from] serde_json::Error),
     #[error("WebSocket message processing error: {0}")]
     MessageProcessing(tokio_tungstenite::tungstenite::Error),
-    #[error("WebSocket closed for user {0}")]
-    WebSocketClosed(String),
-    #[error("WebSocket stream ended unexpectedly for {0}")]
-    StreamEnded(String),
+    #[error("WebSocket connection {0} closed by server")]
+    WebSocketClosed(usize),
+    #[error("WebSocket stream ended unexpectedly on connection {0}")]
+    StreamEnded(usize),
+    #[error("No message on WebSocket connection {0} for {1:?}, assuming it is dead")]
+    HeartbeatTimeout(usize, Duration),
 }
 
 
-const WS_MAINNET: &str = "wss://api.hyperliquid.xyz/ws";
+/// Number of sockets shared by all leader subscriptions.
+pub const DEFAULT_POOL_SIZE: usize = 4;
+const RECONNECT_BASE_DELAY: Duration = Duration::from_secs(1);
+const RECONNECT_MAX_DELAY: Duration = Duration::from_secs(60);
+/// The server drops sockets idle for 60s; a ping also proves a quiet one is alive.
+const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(20);
+/// A socket that didn't even answer the pings in this long is half-open.
+const HEARTBEAT_TIMEOUT: Duration = Duration::from_secs(50);
 
 #[derive(Debug, Serialize, Deserialize)]
 struct SubscriptionRequest {
@@ -45,6 +62,8 @@ enum Incoming {
     UserFills(UserFillsResponse),
     #[serde(rename = "subscriptionResponse")]
     SubscriptionResponse(SubscriptionResponse),
+    #[serde(rename = "pong")]
+    Pong,
     // Add other channels if needed
 }
 
@@ -54,14 +73,16 @@ struct UserFillsResponse {
 }
 
 #[derive(Debug, Deserialize, Serialize)]
+#[serde(rename_all = "camelCase")]
 struct WsUserFills {
     #[serde(default)]
-    isSnapshot: bool,
+    is_snapshot: bool,
     user: String,
     fills: Vec<WsFill>,
 }
 
 #[derive(Debug, Deserialize, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
 pub struct WsFill {
     pub coin: String,
     pub px: String,
@@ -70,12 +91,15 @@ pub struct WsFill {
     pub time: u64,
     pub hash: String,
     pub oid: u64,
-    pub startPosition: Option<String>,
-    pub closedPnl: Option<String>,
+    /// Trade id, unique per fill; missing from older exports
+    #[serde(default)]
+    pub tid: u64,
+    pub start_position: Option<String>,
+    pub closed_pnl: Option<String>,
     pub dir: Option<String>,
     pub crossed: bool,
     pub fee: String,
-    pub feeToken: String,
+    pub fee_token: String,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -83,97 +107,524 @@ struct SubscriptionResponse {
     data: serde_json::Value,
 }
 
-pub async fn fetch_fills(
-    trader_addr: String,
-    channel_tx: tokio::sync::broadcast::Sender<WsFillChannel>,
-) -> Result<(), WsError> {
-    let url = WS_MAINNET.into_client_request().unwrap();
-    let (mut ws_stream, _) = tokio_tungstenite::connect_async(url).await?;
-
-    let sub = SubscriptionRequest {
-        method: "subscribe".to_string(),
-        subscription: Subscription {
-            type_: "userFills".to_string(),
-            user: trader_addr.clone(),
-        },
-    };
-
-    let sub_msg = Message::text(serde_json::to_string(&sub)?);
-    ws_stream.send(sub_msg).await?;
-
-    while let Some(msg) = ws_stream.next().await {
-        let msg = msg.map_err(WsError::MessageProcessing)?;
-        if let Message::Text(text) = msg {
-            if text.contains("subscriptionResponse") {
-                let response: Incoming = serde_json::from_str(&text)?;
-                if let Incoming::SubscriptionResponse(_) = response {
-                    println!("Successfully subscribed to userFills for {trader_addr}");
-                    break;
+impl SubscriptionRequest {
+    fn user_fills(method: &str, user: &str) -> Result<Message, WsError> {
+        let req = SubscriptionRequest {
+            method: method.to_string(),
+            subscription: Subscription {
+                type_: "userFills".to_string(),
+                user: user.to_string(),
+            },
+        };
+        Ok(Message::text(serde_json::to_string(&req)?))
+    }
+}
+
+fn ping() -> Message {
+    Message::text(r#"{"method":"ping"}"#)
+}
+
+/// Delay before reconnect attempt `attempt` (0-based): doubles from
+/// [`RECONNECT_BASE_DELAY`] up to [`RECONNECT_MAX_DELAY`], randomised over its upper
+/// half so connections dropped together don't all come back at once.
+fn backoff_delay(attempt: u32) -> Duration {
+    let ceiling = RECONNECT_BASE_DELAY
+        .saturating_mul(2u32.saturating_pow(attempt))
+        .min(RECONNECT_MAX_DELAY);
+    let jitter = RandomState::new().hash_one(attempt) % 1_000;
+    ceiling / 2 + ceiling / 2 * jitter as u32 / 1_000
+}
+
+#[derive(Debug)]
+enum ConnCommand {
+    Subscribe(String),
+    Unsubscribe(String),
+}
+
+/// Multiplexes `userFills` subscriptions for many leaders over a fixed number of sockets.
+///
+/// Each connection owns the set of users assigned to it and resubscribes all of them
+/// whenever it reconnects; the snapshot each resubscription returns is reconciled
+/// against `watermarks` so fills missed while disconnected are still forwarded.
+/// Dropping the pool closes every connection.
+pub struct WsPool {
+    connections: Vec<mpsc::UnboundedSender<ConnCommand>>,
+    /// Lowercased address -> connection and address as subscribed
+    assignments: HashMap<String, (usize, String)>,
+    health: Arc<PoolHealth>,
+}
+
+impl WsPool {
+    pub fn new(
+        url: &str,
+        size: usize,
+        channel_tx: broadcast::Sender<WsFillChannel>,
+        watermarks: Arc<FillWatermarks>,
+    ) -> Self {
+        let size = size.max(1);
+        let health = Arc::new(PoolHealth::new(size));
+        let connections = (0..size)
+            .map(|id| {
+                let (tx, rx) = mpsc::unbounded_channel();
+                tokio::spawn(run_connection(
+                    id,
+                    url.to_string(),
+                    rx,
+                    channel_tx.clone(),
+                    watermarks.clone(),
+                    health.clone(),
+                ));
+                tx
+            })
+            .collect();
+
+        Self {
+            connections,
+            assignments: HashMap::new(),
+            health,
+        }
+    }
+
+    /// Connection state of every subscription, kept up to date by the connections.
+    pub fn health(&self) -> Arc<PoolHealth> {
+        self.health.clone()
+    }
+
+    /// Subscribe `user` on the least loaded connection. Returns false if already
+    /// subscribed, in any casing.
+    pub fn subscribe(&mut self, user: &str) -> bool {
+        let key = user.to_lowercase();
+        if self.assignments.contains_key(&key) {
+            return false;
+        }
+
+        let mut load = vec![0usize; self.connections.len()];
+        for (conn, _) in self.assignments.values() {
+            load[*conn] += 1;
+        }
+        let conn = (0..load.len()).min_by_key(|i| load[*i]).unwrap_or(0);
+
+        let _ = self.connections[conn].send(ConnCommand::Subscribe(user.to_string()));
+        self.assignments.insert(key, (conn, user.to_string()));
+        self.health.subscribed(user, conn);
+        true
+    }
+
+    /// Returns false if `user` was not subscribed, in any casing.
+    pub fn unsubscribe(&mut self, user: &str) -> bool {
+        match self.assignments.remove(&user.to_lowercase()) {
+            Some((conn, subscribed)) => {
+                let _ = self.connections[conn].send(ConnCommand::Unsubscribe(subscribed.clone()));
+                self.health.unsubscribed(&subscribed);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+async fn run_connection(
+    id: usize,
+    url: String,
+    mut commands: mpsc::UnboundedReceiver<ConnCommand>,
+    channel_tx: broadcast::Sender<WsFillChannel>,
+    watermarks: Arc<FillWatermarks>,
+    health: Arc<PoolHealth>,
+) {
+    // lowercased address -> address as it was subscribed
+    let mut users: HashMap<String, String> = HashMap::new();
+    // consecutive failed attempts; reset once a session receives anything
+    let mut attempt = 0;
+
+    loop {
+        // Don't hold an idle socket open for a connection with nothing assigned
+        if users.is_empty() {
+            health.set_state(id, ConnectionState::Idle);
+            attempt = 0;
+        }
+        while users.is_empty() {
+            match commands.recv().await {
+                Some(cmd) => {
+                    apply_command(&mut users, cmd);
                 }
+                None => return,
+            }
+        }
+
+        health.set_state(id, ConnectionState::Connecting);
+        let session = Session { id, channel_tx: &channel_tx, watermarks: &watermarks, health: &health };
+        match session.run(&url, &mut users, &mut commands, &mut attempt).await {
+            Ok(()) => return,
+            Err(e) => {
+                error!("WebSocket connection {id} lost: {e}");
+                health.connection_lost(id, e.to_string());
             }
         }
+
+        let delay = backoff_delay(attempt);
+        attempt = attempt.saturating_add(1);
+        // keep taking commands while backing off; the pool closing ends the wait
+        let deadline = Instant::now() + delay;
+        loop {
+            tokio::select! {
+                _ = sleep_until(deadline) => break,
+                cmd = commands.recv() => match cmd {
+                    Some(cmd) => {
+                        apply_command(&mut users, cmd);
+                    }
+                    None => return,
+                },
+            }
+        }
+        info!("Reconnecting WebSocket connection {id} ({} users, attempt {attempt})...", users.len());
     }
+}
 
-    // Main event loop
-    while let Some(result) = ws_stream.next().await {
-        match result {
-            Ok(Message::Text(text)) => {
-                if text.contains("userFills") {
-                    match serde_json::from_str::<Incoming>(&text) {
-                        Ok(Incoming::UserFills(resp)) => {
-                            for fill in resp.data.fills {
-                                // Ignore snapshot fills if you already have historical state
-                                if resp.data.isSnapshot {
-                                    continue; // or handle snapshot once at startup
-                                }
-
-                                let channelfill = WsFillChannel{
-                                    fill: fill.clone(),
-                                    user : resp.data.user.clone(),
-                                };
-
-                                let _ = channel_tx.send(channelfill);
-                                // Optional: log or emit metrics
-                                println!(
-                                    "[{}] {} {} @ {} | Dir: {:?}",
-                                    trader_addr, fill.side, fill.sz, fill.px, fill.dir
-                                );
-                            }
+fn apply_command(users: &mut HashMap<String, String>, cmd: ConnCommand) -> bool {
+    match cmd {
+        ConnCommand::Subscribe(user) => users.insert(user.to_lowercase(), user).is_none(),
+        ConnCommand::Unsubscribe(user) => users.remove(&user.to_lowercase()).is_some(),
+    }
+}
+
+/// What one connection's sockets share across reconnects.
+struct Session<'a> {
+    id: usize,
+    channel_tx: &'a broadcast::Sender<WsFillChannel>,
+    watermarks: &'a FillWatermarks,
+    health: &'a PoolHealth,
+}
+
+impl Session<'_> {
+    /// Runs a single socket until it fails (`Err`) or the pool is dropped (`Ok`).
+    async fn run(
+        &self,
+        url: &str,
+        users: &mut HashMap<String, String>,
+        commands: &mut mpsc::UnboundedReceiver<ConnCommand>,
+        attempt: &mut u32,
+    ) -> Result<(), WsError> {
+        let id = self.id;
+        let (mut ws_stream, _) = tokio_tungstenite::connect_async(url).await?;
+
+        for user in users.values() {
+            ws_stream.send(SubscriptionRequest::user_fills("subscribe", user)?).await?;
+        }
+        self.health.set_state(id, ConnectionState::Connected);
+        info!("WebSocket connection {id} subscribed to {} users", users.len());
+
+        let mut heartbeat = interval_at(Instant::now() + HEARTBEAT_INTERVAL, HEARTBEAT_INTERVAL);
+        heartbeat.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        let mut last_message = Instant::now();
+
+        loop {
+            tokio::select! {
+                _ = heartbeat.tick() => {
+                    if last_message.elapsed() >= HEARTBEAT_TIMEOUT {
+                        return Err(WsError::HeartbeatTimeout(id, last_message.elapsed()));
+                    }
+                    ws_stream.send(ping()).await?;
+                }
+                cmd = commands.recv() => {
+                    let Some(cmd) = cmd else {
+                        let _ = ws_stream.close(None).await;
+                        return Ok(());
+                    };
+                    let msg = match &cmd {
+                        ConnCommand::Subscribe(user) => SubscriptionRequest::user_fills("subscribe", user)?,
+                        ConnCommand::Unsubscribe(user) => SubscriptionRequest::user_fills("unsubscribe", user)?,
+                    };
+                    if apply_command(users, cmd) {
+                        ws_stream.send(msg).await?;
+                    }
+                }
+                msg = ws_stream.next() => {
+                    if let Some(Ok(_)) = &msg {
+                        last_message = Instant::now();
+                        *attempt = 0;
+                        self.health.message_received(id);
+                    }
+                    match msg {
+                        Some(Ok(Message::Text(text))) => {
+                            route_message(&text, users, self.channel_tx, self.watermarks, self.health)
                         }
-                        Err(e) => {
-                            eprintln!("Failed to parse userFills: {e}\nText: {text}");
+                        Some(Ok(Message::Ping(data))) => {
+                            ws_stream.send(Message::Pong(data)).await?;
                         }
+                        Some(Ok(Message::Close(_))) => return Err(WsError::WebSocketClosed(id)),
+                        Some(Err(e)) => return Err(WsError::MessageProcessing(e)),
+                        None => return Err(WsError::StreamEnded(id)),
                         _ => {}
                     }
                 }
             }
-            Ok(Message::Ping(data)) => {
-                let _ = ws_stream.send(Message::Pong(data)).await;
+        }
+    }
+}
+
+/// Forward fills to the channel, keyed by the subscribed user the message belongs to.
+fn route_message(
+    text: &str,
+    users: &HashMap<String, String>,
+    channel_tx: &broadcast::Sender<WsFillChannel>,
+    watermarks: &FillWatermarks,
+    health: &PoolHealth,
+) {
+    match serde_json::from_str::<Incoming>(text) {
+        Ok(Incoming::UserFills(resp)) => {
+            // Fills can still arrive for a user between unsubscribe and the server's ack
+            let Some(user) = users.get(&resp.data.user.to_lowercase()) else {
+                return;
+            };
+            if !resp.data.fills.is_empty() {
+                health.fill_received(user);
+            }
+
+            let fills = if resp.data.is_snapshot {
+                let now_ms = chrono::Utc::now().timestamp_millis() as u64;
+                let missed = watermarks.reconcile_snapshot(user, resp.data.fills, now_ms);
+                if !missed.is_empty() {
+                    info!("[{}] forwarding {} fills missed while disconnected", user, missed.len());
+                }
+                missed
+            } else {
+                for fill in &resp.data.fills {
+                    watermarks.observe(user, fill);
+                }
+                resp.data.fills
+            };
+
+            for fill in fills {
+                info!(
+                    "[{}] {} {} @ {} | Dir: {:?}",
+                    user, parse_side(&fill.side), fill.sz, fill.px, fill.dir
+                );
+                let _ = channel_tx.send(WsFillChannel {
+                    fill,
+                    user: user.clone(),
+                });
             }
-            Ok(Message::Close(_)) | Err(_) => {
-                return Err(WsError::WebSocketClosed(trader_addr));
+        }
+        Ok(Incoming::SubscriptionResponse(resp)) => {
+            info!("Subscription response: {}", resp.data);
+        }
+        Ok(Incoming::Pong) => {}
+        Err(e) => {
+            if text.contains("userFills") {
+                error!("Failed to parse userFills: {e}\nText: {text}");
             }
-            _ => {}
         }
     }
-
-    Err(WsError::StreamEnded(trader_addr))
 }
 
-pub async fn fetch_fills_with_retry(
-    user_addr: String,
-    channel_tx: tokio::sync::broadcast::Sender<WsFillChannel>,
-) -> ! {
-    loop {
-        match fetch_fills(user_addr.clone(), channel_tx.clone()).await {
-            Ok(_) => println!("fetch_fills exited cleanly (should not happen)"),
-            Err(e) => {
-                eprintln!("Lost connection for {user_addr}: {e}");
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tokio::net::{TcpListener, TcpStream};
+    use tokio::time::{sleep, timeout};
+    use tokio_tungstenite::WebSocketStream;
+
+    /// One fill per oid, at time and tid `oid`.
+    fn user_fills_message(user: &str, oids: &[u64], is_snapshot: bool) -> Message {
+        let fills: Vec<_> = oids
+            .iter()
+            .map(|oid| serde_json::json!({
+                "coin": "BTC", "px": "50000.0", "sz": "0.1", "side": "B", "time": oid,
+                "hash": "0xhash", "oid": oid, "tid": oid, "startPosition": "0", "closedPnl": "0",
+                "dir": "Open Long", "crossed": true, "fee": "1.0", "feeToken": "USDC"
+            }))
+            .collect();
+        let msg = serde_json::json!({
+            "channel": "userFills",
+            "data": { "isSnapshot": is_snapshot, "user": user, "fills": fills }
+        });
+        Message::text(msg.to_string())
+    }
+
+    fn fills_message(user: &str, oid: u64) -> Message {
+        user_fills_message(user, &[oid], false)
+    }
+
+    async fn accept(listener: &TcpListener) -> WebSocketStream<TcpStream> {
+        let (stream, _) = listener.accept().await.unwrap();
+        tokio_tungstenite::accept_async(stream).await.unwrap()
+    }
+
+    /// Read `n` subscription requests, returning (method, user) pairs.
+    async fn read_requests(server: &mut WebSocketStream<TcpStream>, n: usize) -> Vec<(String, String)> {
+        let mut requests = Vec::new();
+        while requests.len() < n {
+            let msg = timeout(Duration::from_secs(5), server.next()).await.unwrap().unwrap().unwrap();
+            if let Message::Text(text) = msg {
+                let req: SubscriptionRequest = serde_json::from_str(&text).unwrap();
+                assert_eq!(req.subscription.type_, "userFills");
+                requests.push((req.method, req.subscription.user));
+            }
+        }
+        requests.sort();
+        requests
+    }
+
+    #[tokio::test]
+    async fn test_pool_routes_fills_by_user() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (fill_tx, mut fill_rx) = broadcast::channel::<WsFillChannel>(16);
+
+        let mut pool = WsPool::new(&url, 1, fill_tx, Arc::new(FillWatermarks::new(None)));
+        assert!(pool.subscribe("0xAAA"));
+        assert!(pool.subscribe("0xbbb"));
+        assert!(!pool.subscribe("0xAAA"));
+        assert!(!pool.subscribe("0xaaa"));
+        assert_eq!(pool.health().statuses().len(), 2);
+
+        let mut server = accept(&listener).await;
+        let requests = read_requests(&mut server, 2).await;
+        assert_eq!(requests, vec![
+            ("subscribe".to_string(), "0xAAA".to_string()),
+            ("subscribe".to_string(), "0xbbb".to_string()),
+        ]);
+
+        server.send(fills_message("0xaaa", 1)).await.unwrap();
+        server.send(fills_message("0xccc", 2)).await.unwrap(); // not ours, dropped
+        server.send(fills_message("0xbbb", 3)).await.unwrap();
+
+        let first = timeout(Duration::from_secs(5), fill_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(first.user, "0xAAA");
+        assert_eq!(first.fill.oid, 1);
+        let second = timeout(Duration::from_secs(5), fill_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(second.user, "0xbbb");
+        assert_eq!(second.fill.oid, 3);
+
+        // unsubscribing in another casing still finds the subscription
+        assert!(pool.unsubscribe("0xaaa"));
+        assert!(!pool.unsubscribe("0xAAA"));
+        assert_eq!(read_requests(&mut server, 1).await, vec![("unsubscribe".to_string(), "0xAAA".to_string())]);
+        assert_eq!(pool.health().statuses().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_pool_resubscribes_on_reconnect() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (fill_tx, _fill_rx) = broadcast::channel::<WsFillChannel>(16);
+
+        let mut pool = WsPool::new(&url, 1, fill_tx, Arc::new(FillWatermarks::new(None)));
+        pool.subscribe("0xaaa");
+        pool.subscribe("0xbbb");
+
+        let mut server = accept(&listener).await;
+        read_requests(&mut server, 2).await;
+
+        assert!(pool.unsubscribe("0xbbb"));
+        assert!(!pool.unsubscribe("0xbbb"));
+        assert_eq!(
+            read_requests(&mut server, 1).await,
+            vec![("unsubscribe".to_string(), "0xbbb".to_string())]
+        );
+
+        // server drops the socket; only the remaining user should be resubscribed
+        drop(server);
+        let mut server = timeout(Duration::from_secs(10), accept(&listener)).await.unwrap();
+        assert_eq!(
+            read_requests(&mut server, 1).await,
+            vec![("subscribe".to_string(), "0xaaa".to_string())]
+        );
+
+        let health = pool.health();
+        let statuses = health.statuses();
+        assert_eq!(statuses.len(), 1);
+        assert_eq!(statuses[0].address, "0xaaa");
+        assert_eq!(statuses[0].reconnects, 1);
+        assert!(statuses[0].last_error.is_some());
+    }
+
+    #[tokio::test]
+    async fn test_pool_reports_connection_state() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (fill_tx, _fill_rx) = broadcast::channel::<WsFillChannel>(16);
+
+        let mut pool = WsPool::new(&url, 2, fill_tx, Arc::new(FillWatermarks::new(None)));
+        let health = pool.health();
+        pool.subscribe("0xaaa");
+        let mut server = accept(&listener).await;
+        read_requests(&mut server, 1).await;
+        server.send(fills_message("0xaaa", 1)).await.unwrap();
+
+        let status = timeout(Duration::from_secs(5), async {
+            loop {
+                let status = health.statuses().remove(0);
+                if status.last_fill_at.is_some() {
+                    return status;
+                }
+                sleep(Duration::from_millis(10)).await;
+            }
+        })
+        .await
+        .unwrap();
+        assert_eq!(status.state, ConnectionState::Connected);
+        assert!(!status.blind);
+        assert!(status.connected_since.is_some() && status.last_message_at.is_some());
+
+        // lost leaders are reported blind until the socket is back
+        drop(server);
+        timeout(Duration::from_secs(5), async {
+            while !health.statuses()[0].blind {
+                sleep(Duration::from_millis(10)).await;
             }
+        })
+        .await
+        .unwrap();
+        assert_eq!(health.statuses()[0].state, ConnectionState::Reconnecting);
+
+        pool.unsubscribe("0xaaa");
+        assert!(health.statuses().is_empty());
+    }
+
+    #[test]
+    fn test_backoff_delay() {
+        for attempt in 0..20 {
+            let ceiling = RECONNECT_BASE_DELAY.saturating_mul(1 << attempt.min(10)).min(RECONNECT_MAX_DELAY);
+            let delay = backoff_delay(attempt);
+            assert!(delay >= ceiling / 2 && delay <= ceiling, "attempt {attempt}: {delay:?}");
         }
+        assert!(backoff_delay(30) >= RECONNECT_MAX_DELAY / 2);
+    }
+
+    #[test]
+    fn test_pong_is_not_a_parse_error() {
+        assert!(matches!(serde_json::from_str::<Incoming>(r#"{"channel":"pong"}"#), Ok(Incoming::Pong)));
+    }
+
+    #[tokio::test]
+    async fn test_pool_reconciles_snapshots() {
+        let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("ws://{}", listener.local_addr().unwrap());
+        let (fill_tx, mut fill_rx) = broadcast::channel::<WsFillChannel>(16);
+
+        let mut pool = WsPool::new(&url, 1, fill_tx, Arc::new(FillWatermarks::new(None)));
+        pool.subscribe("0xaaa");
+        let mut server = accept(&listener).await;
+        read_requests(&mut server, 1).await;
+
+        // history on the first subscription, then a live fill
+        server.send(user_fills_message("0xaaa", &[1, 2], true)).await.unwrap();
+        server.send(fills_message("0xaaa", 3)).await.unwrap();
+        let live = timeout(Duration::from_secs(5), fill_rx.recv()).await.unwrap().unwrap();
+        assert_eq!(live.fill.tid, 3);
+
+        // after a reconnect only what happened in between is forwarded
+        drop(server);
+        let mut server = timeout(Duration::from_secs(10), accept(&listener)).await.unwrap();
+        read_requests(&mut server, 1).await;
+        server.send(user_fills_message("0xaaa", &[2, 3, 4, 5], true)).await.unwrap();
 
-        // Exponential backoff or fixed delay
-        sleep(Duration::from_secs(3));
-        println!("Reconnecting to userFills for {user_addr}...");
+        let missed: Vec<u64> = vec![
+            timeout(Duration::from_secs(5), fill_rx.recv()).await.unwrap().unwrap().fill.tid,
+            timeout(Duration::from_secs(5), fill_rx.recv()).await.unwrap().unwrap().fill.tid,
+        ];
+        assert_eq!(missed, vec![4, 5]);
+        assert!(fill_rx.try_recv().is_err());
     }
 }
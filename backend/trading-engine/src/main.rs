@@ -1,15 +1,32 @@
-use std::env;
+use std::sync::Arc;
+use clap::Parser;
 use serde::Deserialize;
-use crate::{api::Server, channel::WsFillChannel, hyperliquid::ws::fetch_fills_with_retry};
+use crate::{
+    api::Server,
+    channel::WsFillChannel,
+    cli::{Cli, Command},
+    config::Config,
+    engine::{backfill::Backfill, dedup::SeenStore, subscriptions::SubscriptionManager},
+    exchange::hyperliquid::HyperliquidExchange,
+    hyperliquid::{info::InfoApi, watermarks::FillWatermarks, ws::WsPool},
+    supervisor::Supervisor,
+    vault::{KeyVault, MasterKeys},
+};
 
 mod api;
 mod channel;
+mod cli;
+mod config;
 mod cron;
 mod engine;
 mod error;
+mod exchange;
 mod hyperliquid;
 mod models;
 mod routes;
+mod shutdown;
+mod supervisor;
+mod vault;
 
 /// the trade response from hyperliquid api
 #[derive(Debug, Clone, Deserialize)]
@@ -32,64 +49,120 @@ pub struct TradeMessage {
 
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
+    let cli = Cli::parse();
+    if let Some(Command::Backtest(args)) = cli.command {
+        return cli::backtest(args).await;
+    }
+
     println!("Starting Hyperliquid Copy Trading Engine...\n");
     dotenvy::dotenv().ok();
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("info")).init();
+    let config = Config::load(cli.config.as_deref())?;
 
-    let db_url = env::var("DB_URL").expect("DB_URL must be set");
-    let pg_pool = sqlx::postgres::PgPool::connect(&db_url).await?;
-    sqlx::migrate!().run(&pg_pool).await?;
+    let (shutdown_trigger, shutdown) = shutdown::channel();
+    tokio::spawn(async move {
+        shutdown::signal().await;
+        println!("Shutting down: stopping ingest and draining in-flight orders...");
+        shutdown_trigger.trigger();
+    });
 
-    let monitored_traders = vec![
-        "0x5b5d51203a0f9079f8aeb098a6523a13f298c060".to_string(),
-        "0x7fdafde5cfb5465924316eced2d3715494c517d1".to_string(),
-    ];
+    // leaders are watched and followers trade on the same network
+    let network = config.network()?;
+    println!("network: {} ({}, {})", network.network, network.api_url, network.ws_url);
+
+    let pg_pool = sqlx::postgres::PgPool::connect(&config.database.url).await?;
+    sqlx::migrate!().run(&pg_pool).await?;
 
     let (tx, rx) = tokio::sync::broadcast::channel::<WsFillChannel>(10_000);
 
-    for trader in monitored_traders {
-        let tx = tx.clone();
-        tokio::spawn(async move {
-            fetch_fills_with_retry(trader, tx).await;
-        });
-    }
+    // leaders come from the `traders` table; the API adds/removes them at runtime
+    let watermarks = Arc::new(FillWatermarks::new(config.ingest.snapshot_max_age()));
+    let marked = watermarks.load(&pg_pool).await?;
+    println!("loaded fill marks for {} leaders", marked);
+    let flusher_watermarks = watermarks.clone();
+    let flusher_pool = pg_pool.clone();
+    tokio::spawn(async move { flusher_watermarks.run_flusher(flusher_pool).await });
 
-    let pg_pool_clone = pg_pool.clone();
-    tokio::spawn(async move {
-        if let Err(e) = crate::cron::start_scheduler(pg_pool_clone).await {
+    let ws_pool = WsPool::new(&network.ws_url, config.ingest.pool_size, tx, watermarks.clone());
+    let ws_health = ws_pool.health();
+    let (mut subscription_manager, subscriptions) = SubscriptionManager::new(ws_pool);
+    let monitored = subscription_manager.load_active(&pg_pool).await?;
+    println!("monitoring {} traders", monitored);
+    tokio::spawn(subscription_manager.run(shutdown.clone()));
+
+    let info = InfoApi::new(&network.api_url);
+    let backfill = Arc::new(Backfill::new(info.clone(), config.backfill.window()));
+    let scheduler = match crate::cron::start_scheduler(pg_pool.clone(), config.cron.clone(), info, backfill.clone()).await {
+        Ok(scheduler) => Some(scheduler),
+        Err(e) => {
             log::error!("Scheduler failed: {}", e);
+            None
         }
-    });
+    };
 
+    // the grouper and executor are restarted whenever they fail
+    let supervisor = Arc::new(Supervisor::new());
     let (full_order_tx, full_order_reciever) = tokio::sync::broadcast::channel(10_000);
-    let grouper_rx = rx.resubscribe();
-    tokio::spawn(async move {
+    let seen = Arc::new(SeenStore::new(pg_pool.clone()));
+    let recorder_rx = rx.resubscribe();
+    let grouper_seen = seen.clone();
+    let liveness = supervisor.liveness.clone();
+    let grouper_config = config.grouper.clone();
+    let grouper = supervisor.spawn("grouper", shutdown.clone(), move || {
         println!("grouper starts");
-        if let Err(e) = engine::grouper::start(grouper_rx, full_order_tx).await {
-            eprintln!("Grouper failed: {}", e);
-        }
+        engine::grouper::start(rx.resubscribe(), full_order_tx.clone(), grouper_seen.clone(), liveness.clone(), grouper_config.clone())
+    });
+
+    // leader fills are kept for the leaderboard
+    let recorder_pool = pg_pool.clone();
+    let recorder = supervisor.spawn("fill_recorder", shutdown.clone(), move || {
+        engine::leader_fills::record(recorder_rx.resubscribe(), recorder_pool.clone())
     });
 
     // grouper -> executor
-    let agent_key = env::var("AGENT_KEY").expect("AGENT_KEY must be set");
-    let executor_full_order_reciever = full_order_reciever.resubscribe();
-    tokio::spawn(async move {
+    let vault = Arc::new(KeyVault::new(pg_pool.clone(), MasterKeys::from_env()?));
+    let imported = vault.import_legacy_keys().await?;
+    let rewrapped = vault.rotate_master_key().await?;
+    if imported > 0 || rewrapped > 0 {
+        println!("agent keys: imported {} into vault, rewrapped {} under active master key", imported, rewrapped);
+    }
+    let exchange = Arc::new(HyperliquidExchange::new(&network, vault.clone()).await?);
+    let executor_pool = pg_pool.clone();
+    let executor_config = config.executor.clone();
+    let executor = supervisor.spawn("executor", shutdown.clone(), move || {
         println!("executor started");
-        if let Err(e) = engine::executor::start(executor_full_order_reciever, pg_pool.clone(), &agent_key).await {
-            eprintln!("Executor failed: {}", e);
-        }
+        engine::executor::start(full_order_reciever.resubscribe(), executor_pool.clone(), exchange.clone(), seen.clone(), executor_config.clone())
     });
-    let server = Server::new(3000, db_url);
+    let server = Server::new(&config.api, &config.database)
+        .with_subscriptions(subscriptions)
+        .with_ws_health(ws_health)
+        .with_vault(vault)
+        .with_supervisor(supervisor)
+        .with_backfill(backfill, config.backfill.on_register)
+        .with_shutdown(shutdown);
     server.start().await?;
 
-    let mut main_full_order_reciever = full_order_reciever.resubscribe();
-    loop {
-        match main_full_order_reciever.recv().await {
-            Ok(trade) => {
-                println!("{:?}", trade);
-            }
-            Err(e) => {
-                eprintln!("Error receiving detected trade: {}", e);
-            }
-        }
+    // The server only returns on shutdown. The subscription manager has closed the
+    // pool by now, so the grouper flushes its pending orders and the executor
+    // drains its queues once the grouper is done.
+    if let Some(mut scheduler) = scheduler
+        && let Err(e) = scheduler.shutdown().await
+    {
+        log::error!("Failed to stop scheduler: {}", e);
+    }
+    let drained = tokio::time::timeout(config.executor.drain_timeout() * 2, async {
+        let _ = grouper.await;
+        let _ = executor.await;
+        let _ = recorder.await;
+    })
+    .await;
+    if drained.is_err() {
+        eprintln!("Order pipeline did not drain in time, exiting anyway");
+    }
+    match watermarks.flush(&pg_pool).await {
+        Ok(flushed) => println!("Persisted fill marks of {} leaders", flushed),
+        Err(e) => eprintln!("Failed to persist leader fill marks: {}", e),
     }
+    println!("Shutdown complete");
+    Ok(())
 }
@@ -1,4 +1,4 @@
-use chrono::NaiveDateTime;
+use chrono::{NaiveDate, NaiveDateTime};
 use rust_decimal::Decimal;
 use serde::Serialize;
 use sqlx::FromRow;
@@ -15,7 +15,6 @@ pub struct Trader {
 pub struct Follower {
     pub id: i32,
     pub address: String,
-    pub agent_signature: String,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize)]
@@ -26,18 +25,12 @@ pub struct CopyConfig {
     pub ratio: Decimal,
     pub is_active: bool,
     pub max_risk_per_trade: Option<Decimal>,
-}
-
-#[derive(Debug, Clone, Serialize)]
-pub struct FullOrder {
-    pub leader: String,
-    pub coin: String,
-    pub dir: String,
-    pub total_sz: Decimal,
-    pub avg_px: Decimal,
-    pub timestamp: u64,
-    pub hash: String,
-    pub oid: u64,
+    pub sizing_mode: String,
+    pub fixed_notional: Option<Decimal>,
+    pub execution_style: String,
+    pub max_slippage_bps: i32,
+    pub gtc_timeout_secs: i32,
+    pub mode: String,
 }
 
 #[derive(Debug, Clone, FromRow, Serialize)]
@@ -53,16 +46,98 @@ pub struct Trade {
     pub hl_oid: Option<i64>,
     pub timestamp: Option<NaiveDateTime>,
     pub status: Option<String>,
+    pub leader_oid: Option<i64>,
+    pub status_reason: Option<String>,
+    pub updated_at: Option<NaiveDateTime>,
+}
+
+/// A leader's fill as recorded in `leader_fills`.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct LeaderFill {
+    pub trader_address: String,
+    pub hash: String,
+    pub tid: i64,
+    pub oid: i64,
+    pub coin: String,
+    pub side: String,
+    pub px: Decimal,
+    pub sz: Decimal,
+    pub dir: Option<String>,
+    pub start_position: Option<Decimal>,
+    pub closed_pnl: Decimal,
+    pub fee: Decimal,
+    pub fee_token: String,
+    pub crossed: bool,
+    pub filled_at: NaiveDateTime,
+}
+
+impl LeaderFill {
+    /// Realized PnL net of the fee.
+    pub fn net_pnl(&self) -> Decimal {
+        self.closed_pnl - self.fee
+    }
+
+    pub fn notional(&self) -> Decimal {
+        self.px * self.sz
+    }
 }
 
+/// A leader's account value at one point in time, from `leader_equity`.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct EquitySnapshot {
+    pub account_value: Decimal,
+    pub recorded_at: NaiveDateTime,
+}
+
+/// A trader's metrics over one window, from `leaderboard`.
 #[derive(Debug, Clone, FromRow, Serialize)]
 pub struct LeaderboardEntry {
     pub trader_address: String,
-    pub pnl_percent_30d: Option<Decimal>,
+    pub time_window: String,
+    /// Time-weighted return, percent
+    pub return_pct: Option<Decimal>,
     pub win_rate: Option<Decimal>,
     pub sharpe: Option<Decimal>,
+    pub sortino: Option<Decimal>,
+    pub calmar: Option<Decimal>,
     pub max_drawdown: Option<Decimal>,
+    pub profit_factor: Option<Decimal>,
+    pub avg_hold_secs: Option<i64>,
+    pub expectancy: Option<Decimal>,
+    pub trades: i32,
     pub followers_count: Option<i32>,
-    pub volume_7d: Option<Decimal>,
+    pub volume: Option<Decimal>,
     pub updated_at: Option<NaiveDateTime>,
 }
+
+/// A trader's leaderboard row as of one daily run, from `leaderboard_snapshots`.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct LeaderboardSnapshot {
+    pub snapshot_date: NaiveDate,
+    pub rank: i32,
+    /// Places gained since the previous snapshot; negative when falling
+    pub rank_change: Option<i32>,
+    pub return_pct: Option<Decimal>,
+    pub win_rate: Option<Decimal>,
+    pub sharpe: Option<Decimal>,
+    pub sortino: Option<Decimal>,
+    pub calmar: Option<Decimal>,
+    pub max_drawdown: Option<Decimal>,
+    pub profit_factor: Option<Decimal>,
+    pub avg_hold_secs: Option<i64>,
+    pub expectancy: Option<Decimal>,
+    pub trades: i32,
+    pub followers_count: Option<i32>,
+    pub volume: Option<Decimal>,
+}
+
+/// A trader who climbed the leaderboard between two snapshots.
+#[derive(Debug, Clone, FromRow, Serialize)]
+pub struct RisingEntry {
+    pub trader_address: String,
+    pub rank: i32,
+    pub previous_rank: i32,
+    pub rank_change: i32,
+    pub return_pct: Option<Decimal>,
+    pub previous_return_pct: Option<Decimal>,
+}
@@ -0,0 +1,54 @@
+use axum::{
+    extract::{DefaultBodyLimit, State},
+    routing::post,
+    Json, Router,
+};
+use serde::Deserialize;
+use std::sync::Arc;
+
+use crate::{
+    error::AppError,
+    api::Server,
+    engine::backtest::{self, BacktestConfig, BacktestError, BacktestReport},
+    hyperliquid::ws::WsFill,
+};
+
+/// Fill exports of active leaders run to several MB.
+const MAX_BODY_BYTES: usize = 64 * 1024 * 1024;
+
+pub fn create_router() -> Router<Arc<Server>> {
+    Router::new()
+        .route("/", post(run_backtest))
+        .layer(DefaultBodyLimit::max(MAX_BODY_BYTES))
+}
+
+#[derive(Debug, Deserialize)]
+struct BacktestRequest {
+    leader: String,
+    /// Unix ms, inclusive
+    from: Option<u64>,
+    to: Option<u64>,
+    #[serde(flatten)]
+    config: BacktestConfig,
+    /// The leader's `userFills` export
+    fills: Vec<WsFill>,
+}
+
+async fn run_backtest(
+    State(_state): State<Arc<Server>>,
+    Json(payload): Json<BacktestRequest>,
+) -> Result<Json<BacktestReport>, AppError> {
+    let report = backtest::run(&payload.leader, payload.fills, payload.from, payload.to, &payload.config)
+        .await
+        .map_err(|e| match e {
+            BacktestError::InvalidConfig(_) | BacktestError::NoFills | BacktestError::Parse(_) => {
+                AppError::BadRequest(e.to_string())
+            }
+            e => {
+                log::error!("Backtest of {} failed: {}", payload.leader, e);
+                AppError::InternalServerError
+            }
+        })?;
+
+    Ok(Json(report))
+}
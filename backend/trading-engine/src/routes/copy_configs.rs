@@ -5,16 +5,18 @@ use axum::{
 };
 use rust_decimal::Decimal;
 use serde::Deserialize;
+use sqlx::{Postgres, QueryBuilder};
 use std::sync::Arc;
 
 use crate::{
     error::AppError,
     models::CopyConfig,
     api::Server,
+    engine::{execution::ExecutionStyleKind, paper::CopyMode, sizing::SizingMode},
 };
 
 pub fn create_router() -> Router<Arc<Server>> {
-    Router::new().route("/:id", put(update_copy_config))
+    Router::new().route("/{id}", put(update_copy_config))
 }
 
 #[derive(Debug, Deserialize)]
@@ -22,6 +24,12 @@ struct UpdateCopyConfig {
     ratio: Option<Decimal>,
     is_active: Option<bool>,
     max_risk_per_trade: Option<Decimal>,
+    sizing_mode: Option<SizingMode>,
+    fixed_notional: Option<Decimal>,
+    execution_style: Option<ExecutionStyleKind>,
+    max_slippage_bps: Option<i32>,
+    gtc_timeout_secs: Option<i32>,
+    mode: Option<CopyMode>,
 }
 
 async fn update_copy_config(
@@ -31,40 +39,77 @@ async fn update_copy_config(
 ) -> Result<Json<CopyConfig>, AppError> {
     let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
 
-    let mut set_clauses = Vec::new();
-    if payload.ratio.is_some() {
-        set_clauses.push("ratio = $2");
+    if let Some(notional) = payload.fixed_notional
+        && notional <= Decimal::ZERO
+    {
+        return Err(AppError::BadRequest("fixed_notional must be positive".to_string()));
     }
-    if payload.is_active.is_some() {
-        set_clauses.push("is_active = $3");
+    if let Some(bps) = payload.max_slippage_bps
+        && !(0..=10_000).contains(&bps)
+    {
+        return Err(AppError::BadRequest("max_slippage_bps must be between 0 and 10000".to_string()));
     }
-    if payload.max_risk_per_trade.is_some() {
-        set_clauses.push("max_risk_per_trade = $4");
+    if let Some(secs) = payload.gtc_timeout_secs
+        && secs <= 0
+    {
+        return Err(AppError::BadRequest("gtc_timeout_secs must be positive".to_string()));
     }
-
-    if set_clauses.is_empty() {
-        // Or return the current config without changes
-        return Err(AppError::BadRequest("No fields to update".to_string()));
+    if payload.sizing_mode == Some(SizingMode::FixedNotional) && payload.fixed_notional.is_none() {
+        let current: Option<Decimal> =
+            sqlx::query_scalar("SELECT fixed_notional FROM copy_configs WHERE id = $1")
+                .bind(id)
+                .fetch_optional(pool)
+                .await?
+                .ok_or_else(|| AppError::NotFound(format!("Copy config {} not found", id)))?;
+        if current.is_none() {
+            return Err(AppError::BadRequest(
+                "fixed_notional is required for the fixed_notional sizing mode".to_string(),
+            ));
+        }
     }
 
-    let query_str = format!(
-        "UPDATE copy_configs SET {} WHERE id = $1 RETURNING *",
-        set_clauses.join(", ")
-    );
-
-    let mut query = sqlx::query_as::<_, CopyConfig>(&query_str).bind(id);
-
+    let mut query = QueryBuilder::<Postgres>::new("UPDATE copy_configs SET ");
+    let mut set_clauses = query.separated(", ");
     if let Some(ratio) = payload.ratio {
-        query = query.bind(ratio);
+        set_clauses.push("ratio = ").push_bind_unseparated(ratio);
     }
     if let Some(is_active) = payload.is_active {
-        query = query.bind(is_active);
+        set_clauses.push("is_active = ").push_bind_unseparated(is_active);
     }
     if let Some(max_risk) = payload.max_risk_per_trade {
-        query = query.bind(max_risk);
+        set_clauses.push("max_risk_per_trade = ").push_bind_unseparated(max_risk);
+    }
+    if let Some(mode) = payload.sizing_mode {
+        set_clauses.push("sizing_mode = ").push_bind_unseparated(mode.as_str());
+    }
+    if let Some(notional) = payload.fixed_notional {
+        set_clauses.push("fixed_notional = ").push_bind_unseparated(notional);
+    }
+    if let Some(style) = payload.execution_style {
+        set_clauses.push("execution_style = ").push_bind_unseparated(style.as_str());
     }
+    if let Some(bps) = payload.max_slippage_bps {
+        set_clauses.push("max_slippage_bps = ").push_bind_unseparated(bps);
+    }
+    if let Some(secs) = payload.gtc_timeout_secs {
+        set_clauses.push("gtc_timeout_secs = ").push_bind_unseparated(secs);
+    }
+    if let Some(mode) = payload.mode {
+        set_clauses.push("mode = ").push_bind_unseparated(mode.as_str());
+    }
+
+    if query.sql().ends_with("SET ") {
+        // Or return the current config without changes
+        return Err(AppError::BadRequest("No fields to update".to_string()));
+    }
+
+    query.push(" WHERE id = ").push_bind(id).push(" RETURNING *");
 
-    let updated_config = query.fetch_one(pool).await?;
+    let updated_config = query
+        .build_query_as::<CopyConfig>()
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Copy config {} not found", id)))?;
 
     Ok(Json(updated_config))
 }
@@ -1,25 +1,36 @@
 use axum::{
     extract::{Path, State},
-    routing::get,
+    routing::{get, put},
     Json, Router,
 };
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use std::sync::Arc;
+use zeroize::Zeroizing;
 
 
 use crate::{
     error::AppError,
     models::{CopyConfig, Follower},
     api::Server,
+    engine::paper::CopyMode,
+    vault::VaultError,
 };
 
 
 pub fn create_router() -> Router<Arc<Server>> {
     Router::new()
         .route("/", get(get_followers).post(register_follower))
-        .route("/:id", get(get_follower).delete(delete_follower))
+        .route("/{id}", get(get_follower).delete(delete_follower))
+        .route("/{id}/agent_key", put(rotate_agent_key).delete(revoke_agent_key))
+}
+
+impl From<VaultError> for AppError {
+    fn from(e: VaultError) -> Self {
+        log::error!("Agent key vault error: {}", e);
+        AppError::InternalServerError
+    }
 }
 
 
@@ -27,7 +38,6 @@ pub fn create_router() -> Router<Arc<Server>> {
 struct FullFollower {
     id: i32,
     address: String,
-    agent_signature: String,
     trader_address: String,
     ratio: Decimal,
     is_active: bool,
@@ -40,7 +50,7 @@ async fn get_followers(
     let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
 
     let followers = sqlx::query_as::<_, FullFollower>(
-        "SELECT f.id, f.address, f.agent_signature, c.trader_address, c.ratio, c.is_active, c.max_risk_per_trade FROM followers f JOIN copy_configs c ON f.id = c.follower_id",
+        "SELECT f.id, f.address, c.trader_address, c.ratio, c.is_active, c.max_risk_per_trade FROM followers f JOIN copy_configs c ON f.id = c.follower_id",
     )
     .fetch_all(pool)
     .await?;
@@ -55,7 +65,7 @@ async fn get_follower(
     let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
 
     let follower = sqlx::query_as::<_, FullFollower>(
-        "SELECT f.id, f.address, f.agent_signature, c.trader_address, c.ratio, c.is_active, c.max_risk_per_trade FROM followers f JOIN copy_configs c ON f.id = c.follower_id WHERE f.id = $1",
+        "SELECT f.id, f.address, c.trader_address, c.ratio, c.is_active, c.max_risk_per_trade FROM followers f JOIN copy_configs c ON f.id = c.follower_id WHERE f.id = $1",
     )
     .bind(id)
     .fetch_one(pool)
@@ -71,7 +81,7 @@ async fn delete_follower(
     let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
 
     let follower =
-        sqlx::query_as::<_, Follower>("DELETE FROM followers WHERE id = $1 RETURNING *")
+        sqlx::query_as::<_, Follower>("DELETE FROM followers WHERE id = $1 RETURNING id, address")
             .bind(id)
             .fetch_one(pool)
             .await?;
@@ -85,13 +95,18 @@ struct FollowerDetails {
     copy_config: CopyConfig,
 }
 
-#[derive(Debug, Deserialize)]
+// No Debug: the payload carries the follower's private agent key
+#[derive(Deserialize)]
 struct RegisterFollower {
     address: String,
-    agent_signature: String,
+    /// Only optional in paper mode, which never signs an order
+    #[serde(alias = "agent_signature")]
+    agent_key: Option<String>,
     trader_address: String,
     ratio: Decimal,
     max_risk_per_trade: Option<Decimal>,
+    #[serde(default)]
+    mode: CopyMode,
 }
 
 
@@ -100,27 +115,93 @@ async fn register_follower(
     Json(payload): Json<RegisterFollower>,
 ) -> Result<Json<FollowerDetails>, AppError> {
     let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
-
+    let vault = state.vault.as_ref().ok_or(AppError::InternalServerError)?;
+    if payload.mode == CopyMode::Live && payload.agent_key.is_none() {
+        return Err(AppError::BadRequest("agent_key is required unless mode is paper".to_string()));
+    }
+
+    // one transaction: a failed step must not leave a follower behind whose
+    // address can never be registered again
+    let mut tx = pool.begin().await?;
     let follower: Follower = sqlx::query_as(
-        "INSERT INTO followers (address, agent_signature) VALUES ($1, $2) RETURNING *",
+        "INSERT INTO followers (address) VALUES ($1) RETURNING id, address",
     )
     .bind(&payload.address)
-    .bind(&payload.agent_signature)
-    .fetch_one(pool)
+    .fetch_one(&mut *tx)
     .await?;
 
+    // The agent key only ever lives in the vault, never in the followers table
+    if let Some(agent_key) = payload.agent_key {
+        let agent_key = Zeroizing::new(agent_key);
+        vault.store_new(&mut tx, follower.id, &agent_key).await?;
+    }
+
     let copy_config: CopyConfig = sqlx::query_as(
-        "INSERT INTO copy_configs (follower_id, trader_address, ratio, max_risk_per_trade) VALUES ($1, $2, $3, $4) RETURNING *",
+        "INSERT INTO copy_configs (follower_id, trader_address, ratio, max_risk_per_trade, mode) VALUES ($1, $2, $3, $4, $5) RETURNING *",
     )
     .bind(follower.id)
     .bind(&payload.trader_address)
     .bind(payload.ratio)
     .bind(payload.max_risk_per_trade)
-    .fetch_one(pool)
+    .bind(payload.mode.as_str())
+    .fetch_one(&mut *tx)
     .await?;
+    tx.commit().await?;
 
     Ok(Json(FollowerDetails {
         follower,
         copy_config,
     }))
 }
+
+#[derive(Deserialize)]
+struct AgentKeyPayload {
+    agent_key: String,
+}
+
+#[derive(Debug, Serialize)]
+struct AgentKeyStatus {
+    follower_id: i32,
+    agent_key_id: Option<i32>,
+}
+
+/// Replace a follower's agent key; the previous key is revoked immediately.
+async fn rotate_agent_key(
+    State(state): State<Arc<Server>>,
+    Path(id): Path<i32>,
+    Json(payload): Json<AgentKeyPayload>,
+) -> Result<Json<AgentKeyStatus>, AppError> {
+    let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
+    let vault = state.vault.as_ref().ok_or(AppError::InternalServerError)?;
+
+    sqlx::query_scalar::<_, i32>("SELECT id FROM followers WHERE id = $1")
+        .bind(id)
+        .fetch_optional(pool)
+        .await?
+        .ok_or_else(|| AppError::NotFound(format!("Follower {} not found", id)))?;
+
+    let agent_key = Zeroizing::new(payload.agent_key);
+    let key_id = vault.store(id, &agent_key).await?;
+
+    Ok(Json(AgentKeyStatus {
+        follower_id: id,
+        agent_key_id: Some(key_id),
+    }))
+}
+
+/// Revoke a follower's agent key; copying stops until a new key is stored.
+async fn revoke_agent_key(
+    State(state): State<Arc<Server>>,
+    Path(id): Path<i32>,
+) -> Result<Json<AgentKeyStatus>, AppError> {
+    let vault = state.vault.as_ref().ok_or(AppError::InternalServerError)?;
+
+    if !vault.revoke(id).await? {
+        return Err(AppError::NotFound(format!("No active agent key for follower {}", id)));
+    }
+
+    Ok(Json(AgentKeyStatus {
+        follower_id: id,
+        agent_key_id: None,
+    }))
+}
@@ -0,0 +1,103 @@
+use axum::{
+    extract::State,
+    http::StatusCode,
+    Json,
+};
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use crate::{
+    api::Server,
+    supervisor::ComponentStatus,
+};
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    /// Every pipeline component is running
+    pub live: bool,
+    pub components: BTreeMap<&'static str, ComponentStatus>,
+    pub last_fill_at: Option<DateTime<Utc>>,
+    pub last_fill_age_secs: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadyReport {
+    pub ready: bool,
+    pub database: bool,
+    pub subscriptions: usize,
+    /// Leaders whose fill stream is down
+    pub blind_subscriptions: usize,
+    #[serde(flatten)]
+    pub health: HealthReport,
+}
+
+fn health_report(state: &Server) -> HealthReport {
+    let Some(supervisor) = &state.supervisor else {
+        return HealthReport { live: true, components: BTreeMap::new(), last_fill_at: None, last_fill_age_secs: None };
+    };
+    let last_fill_at = supervisor.liveness.last_fill_at();
+    HealthReport {
+        live: supervisor.is_live(),
+        components: supervisor.statuses(),
+        last_fill_at,
+        last_fill_age_secs: last_fill_at.map(|at| (Utc::now() - at).num_seconds()),
+    }
+}
+
+fn status_code(ok: bool) -> StatusCode {
+    if ok { StatusCode::OK } else { StatusCode::SERVICE_UNAVAILABLE }
+}
+
+/// 503 while a grouper or executor is down and waiting to be restarted.
+pub async fn health(State(state): State<Arc<Server>>) -> (StatusCode, Json<HealthReport>) {
+    let report = health_report(&state);
+    (status_code(report.live), Json(report))
+}
+
+/// Ready to copy trades: the pipeline runs, the database answers and at least
+/// one leader stream is connected, if there are any leaders.
+pub async fn ready(State(state): State<Arc<Server>>) -> (StatusCode, Json<ReadyReport>) {
+    let database = match &state.pool {
+        Some(pool) => sqlx::query("SELECT 1").execute(pool).await.is_ok(),
+        None => false,
+    };
+    let statuses = state.ws_health.as_ref().map(|h| h.statuses()).unwrap_or_default();
+    let blind = statuses.iter().filter(|s| s.blind).count();
+    let health = health_report(&state);
+
+    let ready = health.live && database && (statuses.is_empty() || blind < statuses.len());
+    let report = ReadyReport { ready, database, subscriptions: statuses.len(), blind_subscriptions: blind, health };
+    (status_code(ready), Json(report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::{ApiConfig, DatabaseConfig};
+    use crate::supervisor::{ComponentState, Supervisor};
+
+    #[tokio::test(start_paused = true)]
+    async fn test_health_reports_failed_components() {
+        let supervisor = Arc::new(Supervisor::new());
+        let (_trigger, shutdown) = crate::shutdown::channel();
+        supervisor.spawn("grouper", shutdown, || async { Err::<(), _>("unparsable size") });
+        while supervisor.statuses().get("grouper").is_none_or(|c| c.state == ComponentState::Running) {
+            tokio::task::yield_now().await;
+        }
+
+        let server = Server::new(&ApiConfig::default(), &DatabaseConfig::default()).with_supervisor(supervisor.clone());
+        let (code, Json(report)) = health(State(Arc::new(server.clone()))).await;
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert_eq!(report.components["grouper"].last_error.as_deref(), Some("unparsable size"));
+        assert!(report.last_fill_at.is_none());
+
+        // no database, so never ready
+        supervisor.liveness.fill_processed();
+        let (code, Json(report)) = ready(State(Arc::new(server))).await;
+        assert_eq!(code, StatusCode::SERVICE_UNAVAILABLE);
+        assert!(!report.ready && !report.database);
+        assert_eq!(report.health.last_fill_age_secs, Some(0));
+    }
+}
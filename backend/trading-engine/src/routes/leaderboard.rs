@@ -1,29 +1,361 @@
 use axum::{
-    extract::State,
+    extract::{Path, Query, State},
     routing::get,
     Json, Router,
 };
+use chrono::{Duration, NaiveDate, Utc};
+use rust_decimal::Decimal;
+use serde::{Deserialize, Serialize};
+use sqlx::{Postgres, QueryBuilder};
 use std::sync::Arc;
 
 use crate::{
     error::AppError,
-    models::LeaderboardEntry,
+    engine::leaderboard::Window,
+    models::{LeaderboardEntry, LeaderboardSnapshot, RisingEntry},
     api::Server,
 };
 
+const DEFAULT_LIMIT: i64 = 50;
+const MAX_LIMIT: i64 = 200;
+const DEFAULT_HISTORY_DAYS: i64 = 90;
+/// How far back rank changes are looked up by default: since yesterday
+const DEFAULT_RISING_DAYS: i64 = 1;
+const MAX_DAYS: i64 = 3650;
+
 pub fn create_router() -> Router<Arc<Server>> {
-    Router::new().route("/", get(get_leaderboard))
+    Router::new()
+        .route("/", get(get_leaderboard))
+        .route("/rising", get(get_rising))
+        .route("/{address}/history", get(get_history))
+}
+
+fn parse_window(window: Option<&str>) -> Result<Window, AppError> {
+    match window {
+        None => Ok(Window::default()),
+        Some(window) => Window::parse(window).ok_or_else(|| {
+            AppError::BadRequest(format!("window must be one of 24h, 7d, 30d, 90d, all, not {window:?}"))
+        }),
+    }
+}
+
+fn parse_days(days: Option<i64>, default: i64) -> Result<i64, AppError> {
+    let days = days.unwrap_or(default);
+    if !(1..=MAX_DAYS).contains(&days) {
+        return Err(AppError::BadRequest(format!("days must be between 1 and {MAX_DAYS}")));
+    }
+    Ok(days)
+}
+
+fn parse_limit(limit: Option<i64>) -> Result<i64, AppError> {
+    let limit = limit.unwrap_or(DEFAULT_LIMIT);
+    if !(1..=MAX_LIMIT).contains(&limit) {
+        return Err(AppError::BadRequest(format!("limit must be between 1 and {MAX_LIMIT}")));
+    }
+    Ok(limit)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum SortBy {
+    Pnl,
+    Sharpe,
+    WinRate,
+    Drawdown,
+    Followers,
+    Volume,
+}
+
+impl SortBy {
+    fn parse(sort_by: &str) -> Option<Self> {
+        match sort_by {
+            "pnl" => Some(SortBy::Pnl),
+            "sharpe" => Some(SortBy::Sharpe),
+            "win_rate" => Some(SortBy::WinRate),
+            "drawdown" => Some(SortBy::Drawdown),
+            "followers" => Some(SortBy::Followers),
+            "volume" => Some(SortBy::Volume),
+            _ => None,
+        }
+    }
+
+    fn column(&self) -> &'static str {
+        match self {
+            SortBy::Pnl => "return_pct",
+            SortBy::Sharpe => "sharpe",
+            SortBy::WinRate => "win_rate",
+            SortBy::Drawdown => "max_drawdown",
+            SortBy::Followers => "followers_count",
+            SortBy::Volume => "volume",
+        }
+    }
+
+    /// Best first: the shallowest drawdown, the highest of everything else.
+    fn default_descending(&self) -> bool {
+        *self != SortBy::Drawdown
+    }
 }
 
+#[derive(Debug, Default, Deserialize)]
+struct LeaderboardParams {
+    window: Option<String>,
+    sort_by: Option<String>,
+    /// `asc` or `desc`; best first by default
+    order: Option<String>,
+    min_trades: Option<i32>,
+    /// Percent
+    max_drawdown: Option<Decimal>,
+    limit: Option<i64>,
+    offset: Option<i64>,
+}
+
+#[derive(Debug, PartialEq)]
+struct LeaderboardQuery {
+    window: Window,
+    sort_by: SortBy,
+    descending: bool,
+    min_trades: Option<i32>,
+    max_drawdown: Option<Decimal>,
+    limit: i64,
+    offset: i64,
+}
+
+impl TryFrom<LeaderboardParams> for LeaderboardQuery {
+    type Error = AppError;
+
+    fn try_from(params: LeaderboardParams) -> Result<Self, Self::Error> {
+        let window = parse_window(params.window.as_deref())?;
+        let sort_by = match params.sort_by.as_deref() {
+            None => SortBy::Pnl,
+            Some(sort_by) => SortBy::parse(sort_by).ok_or_else(|| {
+                AppError::BadRequest(format!(
+                    "sort_by must be one of pnl, sharpe, win_rate, drawdown, followers, volume, not {sort_by:?}"
+                ))
+            })?,
+        };
+        let descending = match params.order.as_deref() {
+            None => sort_by.default_descending(),
+            Some("desc") => true,
+            Some("asc") => false,
+            Some(order) => return Err(AppError::BadRequest(format!("order must be asc or desc, not {order:?}"))),
+        };
+        let limit = parse_limit(params.limit)?;
+        let offset = params.offset.unwrap_or(0);
+        if offset < 0 {
+            return Err(AppError::BadRequest("offset must not be negative".to_string()));
+        }
+
+        Ok(Self {
+            window,
+            sort_by,
+            descending,
+            min_trades: params.min_trades,
+            max_drawdown: params.max_drawdown,
+            limit,
+            offset,
+        })
+    }
+}
+
+impl LeaderboardQuery {
+    fn push_filters<'a>(&self, query: &mut QueryBuilder<'a, Postgres>) {
+        query.push(" WHERE time_window = ").push_bind(self.window.as_str());
+        if let Some(min_trades) = self.min_trades {
+            query.push(" AND trades >= ").push_bind(min_trades);
+        }
+        // leaders without equity data have no drawdown and are left out
+        if let Some(max_drawdown) = self.max_drawdown {
+            query.push(" AND max_drawdown <= ").push_bind(max_drawdown);
+        }
+    }
+}
+
+#[derive(Debug, Serialize)]
+struct LeaderboardPage {
+    window: &'static str,
+    /// Entries matching the filters, across all pages
+    total: i64,
+    limit: i64,
+    offset: i64,
+    entries: Vec<LeaderboardEntry>,
+}
+
+/// Traders ranked over one window, filtered and paginated.
 async fn get_leaderboard(
     State(state): State<Arc<Server>>,
-) -> Result<Json<Vec<LeaderboardEntry>>, AppError> {
+    Query(params): Query<LeaderboardParams>,
+) -> Result<Json<LeaderboardPage>, AppError> {
     let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
+    let query = LeaderboardQuery::try_from(params)?;
+
+    let mut count = QueryBuilder::<Postgres>::new("SELECT COUNT(*) FROM leaderboard");
+    query.push_filters(&mut count);
+    let total: i64 = count.build_query_scalar().fetch_one(pool).await?;
+
+    let mut select = QueryBuilder::<Postgres>::new("SELECT * FROM leaderboard");
+    query.push_filters(&mut select);
+    select
+        .push(format!(
+            " ORDER BY {} {} NULLS LAST, trader_address",
+            query.sort_by.column(),
+            if query.descending { "DESC" } else { "ASC" }
+        ))
+        .push(" LIMIT ")
+        .push_bind(query.limit)
+        .push(" OFFSET ")
+        .push_bind(query.offset);
+    let entries = select.build_query_as::<LeaderboardEntry>().fetch_all(pool).await?;
+
+    Ok(Json(LeaderboardPage { window: query.window.as_str(), total, limit: query.limit, offset: query.offset, entries }))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct HistoryParams {
+    window: Option<String>,
+    days: Option<i64>,
+}
+
+/// A trader's daily snapshots of the last `days` days, oldest first, with the
+/// places gained or lost since the day before; no change when that day has no
+/// snapshot.
+async fn get_history(
+    State(state): State<Arc<Server>>,
+    Path(address): Path<String>,
+    Query(params): Query<HistoryParams>,
+) -> Result<Json<Vec<LeaderboardSnapshot>>, AppError> {
+    let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
+    let window = parse_window(params.window.as_deref())?;
+    let since = days_ago(parse_days(params.days, DEFAULT_HISTORY_DAYS)?);
+
+    let history = sqlx::query_as::<_, LeaderboardSnapshot>(
+        r#"
+        SELECT s.snapshot_date, s.rank, prev.rank - s.rank AS rank_change,
+            s.return_pct, s.win_rate, s.sharpe, s.sortino, s.calmar, s.max_drawdown, s.profit_factor,
+            s.avg_hold_secs, s.expectancy, s.trades, s.followers_count, s.volume
+        FROM leaderboard_snapshots s
+        LEFT JOIN leaderboard_snapshots prev
+            ON prev.trader_address = s.trader_address AND prev.time_window = s.time_window
+            AND prev.snapshot_date = s.snapshot_date - 1
+        WHERE s.trader_address = $1 AND s.time_window = $2 AND s.snapshot_date > $3
+        ORDER BY s.snapshot_date
+        "#,
+    )
+    .bind(address)
+    .bind(window.as_str())
+    .bind(since)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Json(history))
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct RisingParams {
+    window: Option<String>,
+    /// Compare the latest snapshot with the one exactly this many days earlier
+    days: Option<i64>,
+    limit: Option<i64>,
+}
+
+/// Traders who climbed the most places between the latest snapshot and the one
+/// `days` days before it. Nobody is listed when that day was not snapshotted.
+async fn get_rising(
+    State(state): State<Arc<Server>>,
+    Query(params): Query<RisingParams>,
+) -> Result<Json<Vec<RisingEntry>>, AppError> {
+    let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
+    let window = parse_window(params.window.as_deref())?;
+    let days = parse_days(params.days, DEFAULT_RISING_DAYS)?;
+    let limit = parse_limit(params.limit)?;
+
+    let rising = sqlx::query_as::<_, RisingEntry>(
+        r#"
+        WITH latest AS (
+            SELECT MAX(snapshot_date) AS day FROM leaderboard_snapshots WHERE time_window = $1
+        ), earlier AS (
+            SELECT day - $2::int AS day FROM latest
+        )
+        SELECT cur.trader_address, cur.rank, prev.rank AS previous_rank,
+            prev.rank - cur.rank AS rank_change,
+            cur.return_pct, prev.return_pct AS previous_return_pct
+        FROM leaderboard_snapshots cur
+        JOIN leaderboard_snapshots prev
+            ON prev.trader_address = cur.trader_address AND prev.time_window = cur.time_window
+        WHERE cur.time_window = $1
+            AND cur.snapshot_date = (SELECT day FROM latest)
+            AND prev.snapshot_date = (SELECT day FROM earlier)
+            AND prev.rank > cur.rank
+        ORDER BY rank_change DESC, cur.rank
+        LIMIT $3
+        "#,
+    )
+    .bind(window.as_str())
+    .bind(days as i32)
+    .bind(limit)
+    .fetch_all(pool)
+    .await?;
+
+    Ok(Json(rising))
+}
+
+fn days_ago(days: i64) -> NaiveDate {
+    (Utc::now() - Duration::days(days)).date_naive()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_decimal_macros::dec;
+
+    #[test]
+    fn test_leaderboard_query_defaults() {
+        let query = LeaderboardQuery::try_from(LeaderboardParams::default()).unwrap();
+        assert_eq!((query.window, query.sort_by, query.descending), (Window::Month, SortBy::Pnl, true));
+        assert_eq!((query.limit, query.offset), (DEFAULT_LIMIT, 0));
+    }
+
+    #[test]
+    fn test_leaderboard_query_params() {
+        let params = LeaderboardParams {
+            window: Some("7d".to_string()),
+            sort_by: Some("drawdown".to_string()),
+            min_trades: Some(10),
+            max_drawdown: Some(dec!(25)),
+            limit: Some(20),
+            offset: Some(40),
+            ..Default::default()
+        };
+        let query = LeaderboardQuery::try_from(params).unwrap();
+        assert_eq!((query.window, query.sort_by), (Window::Week, SortBy::Drawdown));
+        // shallowest drawdown first
+        assert!(!query.descending);
+
+        let mut sql = QueryBuilder::<Postgres>::new("SELECT * FROM leaderboard");
+        query.push_filters(&mut sql);
+        assert_eq!(sql.sql(), "SELECT * FROM leaderboard WHERE time_window = $1 AND trades >= $2 AND max_drawdown <= $3");
+    }
 
-    let leaderboard =
-        sqlx::query_as::<_, LeaderboardEntry>("SELECT * FROM leaderboard ORDER BY pnl_percent_30d DESC")
-            .fetch_all(pool)
-            .await?;
+    #[test]
+    fn test_invalid_leaderboard_params_are_rejected() {
+        let invalid = [
+            LeaderboardParams { window: Some("1y".to_string()), ..Default::default() },
+            LeaderboardParams { sort_by: Some("luck".to_string()), ..Default::default() },
+            LeaderboardParams { order: Some("up".to_string()), ..Default::default() },
+            LeaderboardParams { limit: Some(0), ..Default::default() },
+            LeaderboardParams { limit: Some(MAX_LIMIT + 1), ..Default::default() },
+            LeaderboardParams { offset: Some(-1), ..Default::default() },
+        ];
+        for (i, params) in invalid.into_iter().enumerate() {
+            assert!(matches!(LeaderboardQuery::try_from(params), Err(AppError::BadRequest(_))), "case {i} was accepted");
+        }
+    }
 
-    Ok(Json(leaderboard))
+    #[test]
+    fn test_history_and_rising_params() {
+        assert_eq!(parse_window(Some("all")).unwrap(), Window::All);
+        assert_eq!(parse_days(None, DEFAULT_HISTORY_DAYS).unwrap(), 90);
+        assert_eq!(parse_days(Some(7), DEFAULT_RISING_DAYS).unwrap(), 7);
+        assert!(matches!(parse_days(Some(0), DEFAULT_RISING_DAYS), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse_days(Some(MAX_DAYS + 1), DEFAULT_HISTORY_DAYS), Err(AppError::BadRequest(_))));
+        assert!(matches!(parse_window(Some("week")), Err(AppError::BadRequest(_))));
+    }
 }
@@ -3,3 +3,5 @@ pub mod followers;
 pub mod copy_configs;
 pub mod leaderboard;
 pub mod trades;
+pub mod backtest;
+pub mod health;
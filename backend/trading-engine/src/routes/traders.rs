@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, State},
-    routing::get,
+    routing::{get, post},
     Json, Router,
 };
 use std::sync::Arc;
@@ -9,6 +9,8 @@ use crate::{
     error::AppError,
     models::Trader,
     api::Server,
+    hyperliquid::health::SubscriptionStatus,
+    engine::backfill::BackfillReport,
 };
 
 use serde::Deserialize;
@@ -17,7 +19,9 @@ use serde::Deserialize;
 pub fn create_router() -> Router<Arc<Server>> {
     Router::new()
         .route("/", get(get_traders).post(register_trader))
-        .route("/:address", get(get_trader).delete(delete_trader))
+        .route("/subscriptions", get(get_subscriptions))
+        .route("/{address}", get(get_trader).delete(delete_trader))
+        .route("/{address}/backfill", post(backfill_trader))
 }
 
 async fn get_traders(
@@ -33,6 +37,14 @@ async fn get_traders(
     Ok(Json(traders))
 }
 
+/// Connection state of every leader's fill stream; `blind` ones are not being copied.
+async fn get_subscriptions(
+    State(state): State<Arc<Server>>,
+) -> Result<Json<Vec<SubscriptionStatus>>, AppError> {
+    let health = state.ws_health.as_ref().ok_or(AppError::InternalServerError)?;
+    Ok(Json(health.statuses()))
+}
+
 async fn get_trader(
     State(state): State<Arc<Server>>,
     Path(address): Path<String>,
@@ -58,9 +70,16 @@ async fn delete_trader(
     let trader = sqlx::query_as::<_, Trader>(
         "UPDATE traders SET is_active = false WHERE address = $1 RETURNING *",
     )
-    .bind(address)
-    .fetch_one(pool)
-    .await?;
+    .bind(&address)
+    .fetch_optional(pool)
+    .await?
+    .ok_or_else(|| AppError::NotFound(format!("Trader {} not found", address)))?;
+
+    if let Some(subscriptions) = &state.subscriptions
+        && let Err(e) = subscriptions.unsubscribe(&trader.address).await
+    {
+        log::error!("Failed to unsubscribe from {}: {}", trader.address, e);
+    }
 
     Ok(Json(trader))
 }
@@ -77,12 +96,57 @@ async fn register_trader(
 ) -> Result<Json<Trader>, AppError> {
     let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
 
-    let trader =
-        sqlx::query_as::<_, Trader>("INSERT INTO traders (address, name) VALUES ($1, $2) RETURNING *")
-            .bind(payload.address)
-            .bind(payload.name)
-            .fetch_one(pool)
-            .await?;
+    // Re-registering a deactivated trader brings them back instead of failing on the primary key
+    let trader = sqlx::query_as::<_, Trader>(
+        "INSERT INTO traders (address, name) VALUES ($1, $2)
+         ON CONFLICT (address) DO UPDATE SET
+            is_active = true,
+            name = COALESCE(EXCLUDED.name, traders.name)
+         RETURNING *",
+    )
+    .bind(payload.address)
+    .bind(payload.name)
+    .fetch_one(pool)
+    .await?;
+
+    if let Some(subscriptions) = &state.subscriptions
+        && let Err(e) = subscriptions.subscribe(&trader.address).await
+    {
+        log::error!("Failed to subscribe to {}: {}", trader.address, e);
+    }
+
+    // the leaderboard needs their history, not just what they trade from now on
+    if state.backfill_on_register
+        && let Some(backfill) = state.backfill.clone()
+    {
+        let pool = pool.clone();
+        let address = trader.address.clone();
+        tokio::spawn(async move {
+            if let Err(e) = backfill.leader(&pool, &address).await {
+                log::error!("Backfill of {} failed: {}", address, e);
+            }
+        });
+    }
 
     Ok(Json(trader))
 }
+
+/// Load the trader's fill history from the info API; fills already stored are kept.
+async fn backfill_trader(
+    State(state): State<Arc<Server>>,
+    Path(address): Path<String>,
+) -> Result<Json<BackfillReport>, AppError> {
+    let pool = state.pool.as_ref().ok_or(AppError::InternalServerError)?;
+    let backfill = state.backfill.as_ref().ok_or(AppError::InternalServerError)?;
+
+    let registered: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM traders WHERE address = $1)")
+        .bind(&address)
+        .fetch_one(pool)
+        .await?;
+    if !registered {
+        return Err(AppError::NotFound(format!("Trader {address} is not registered")));
+    }
+
+    let report = backfill.leader(pool, &address).await.map_err(|e| AppError::Upstream(e.to_string()))?;
+    Ok(Json(report))
+}
@@ -13,8 +13,8 @@ use crate::{
 
 pub fn create_router() -> Router<Arc<Server>> {
     Router::new()
-        .route("/trader/:trader_address", get(get_trades_by_trader))
-        .route("/follower/:follower_id", get(get_trades_by_follower))
+        .route("/trader/{trader_address}", get(get_trades_by_trader))
+        .route("/follower/{follower_id}", get(get_trades_by_follower))
 }
 
 async fn get_trades_by_trader(
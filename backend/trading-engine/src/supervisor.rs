@@ -0,0 +1,180 @@
+use std::collections::BTreeMap;
+use std::fmt::Display;
+use std::future::Future;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use tokio::task::JoinHandle;
+use tokio::time::{sleep, Instant};
+
+use crate::shutdown::Shutdown;
+
+const RESTART_BASE_DELAY: Duration = Duration::from_secs(1);
+const RESTART_MAX_DELAY: Duration = Duration::from_secs(60);
+/// A component that ran this long before failing restarts without delay growth.
+const HEALTHY_RUN: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ComponentState {
+    Running,
+    /// Failed and waiting out the backoff before the next start
+    Restarting,
+    /// Returned normally, at shutdown
+    Stopped,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct ComponentStatus {
+    pub state: ComponentState,
+    pub since: DateTime<Utc>,
+    pub restarts: u64,
+    pub last_error: Option<String>,
+}
+
+/// Time of the last leader fill the grouper accepted.
+#[derive(Debug, Default)]
+pub struct Liveness {
+    /// Unix ms, 0 before the first fill
+    last_fill_ms: AtomicU64,
+}
+
+impl Liveness {
+    pub fn fill_processed(&self) {
+        self.last_fill_ms.store(Utc::now().timestamp_millis() as u64, Ordering::Relaxed);
+    }
+
+    pub fn last_fill_at(&self) -> Option<DateTime<Utc>> {
+        match self.last_fill_ms.load(Ordering::Relaxed) {
+            0 => None,
+            ms => DateTime::from_timestamp_millis(ms as i64),
+        }
+    }
+}
+
+/// Runs the engine's long-lived tasks, restarting any that fail or panic with
+/// exponential backoff, and keeps their status for `/health` and `/ready`.
+#[derive(Debug, Default)]
+pub struct Supervisor {
+    components: RwLock<BTreeMap<&'static str, ComponentStatus>>,
+    pub liveness: Arc<Liveness>,
+}
+
+impl Supervisor {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Run the future `start` makes until it returns `Ok` or shutdown, starting a
+    /// fresh one after every failure. The handle resolves when it stopped for good.
+    pub fn spawn<F, Fut, E>(self: &Arc<Self>, name: &'static str, shutdown: Shutdown, mut start: F) -> JoinHandle<()>
+    where
+        F: FnMut() -> Fut + Send + 'static,
+        Fut: Future<Output = Result<(), E>> + Send + 'static,
+        E: Display + Send + 'static,
+    {
+        let supervisor = self.clone();
+        tokio::spawn(async move {
+            let mut restarts = 0;
+            let mut delay = RESTART_BASE_DELAY;
+            loop {
+                supervisor.set(name, ComponentState::Running, restarts, None);
+                let started = Instant::now();
+                // a task of its own, so a panic is a failure like any other
+                let error = match tokio::spawn(start()).await {
+                    Ok(Ok(())) => {
+                        supervisor.set(name, ComponentState::Stopped, restarts, None);
+                        return;
+                    }
+                    Ok(Err(e)) => e.to_string(),
+                    Err(e) => format!("panicked: {e}"),
+                };
+
+                log::error!("{} failed: {}", name, error);
+                supervisor.set(name, ComponentState::Restarting, restarts, Some(error.clone()));
+                if started.elapsed() >= HEALTHY_RUN {
+                    delay = RESTART_BASE_DELAY;
+                }
+                tokio::select! {
+                    _ = sleep(delay) => {}
+                    _ = shutdown.wait() => {
+                        supervisor.set(name, ComponentState::Stopped, restarts, Some(error));
+                        return;
+                    }
+                }
+                delay = (delay * 2).min(RESTART_MAX_DELAY);
+                restarts += 1;
+                log::warn!("Restarting {} (restart {})", name, restarts);
+            }
+        })
+    }
+
+    pub fn statuses(&self) -> BTreeMap<&'static str, ComponentStatus> {
+        self.components.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+
+    /// Every supervised component is running.
+    pub fn is_live(&self) -> bool {
+        let components = self.components.read().unwrap_or_else(|e| e.into_inner());
+        !components.is_empty() && components.values().all(|c| c.state == ComponentState::Running)
+    }
+
+    fn set(&self, name: &'static str, state: ComponentState, restarts: u64, last_error: Option<String>) {
+        let mut components = self.components.write().unwrap_or_else(|e| e.into_inner());
+        let last_error = last_error.or_else(|| components.get(name).and_then(|c| c.last_error.clone()));
+        components.insert(name, ComponentStatus { state, since: Utc::now(), restarts, last_error });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::AtomicU32;
+
+    #[tokio::test(start_paused = true)]
+    async fn test_failed_components_restart_until_they_stop() {
+        let supervisor = Arc::new(Supervisor::new());
+        let (trigger, shutdown) = crate::shutdown::channel();
+        let attempts = Arc::new(AtomicU32::new(0));
+
+        let counter = attempts.clone();
+        let handle = supervisor.spawn("grouper", shutdown, move || {
+            let attempt = counter.fetch_add(1, Ordering::SeqCst);
+            async move {
+                match attempt {
+                    0 => Err("unparsable size".to_string()),
+                    1 => panic!("boom"),
+                    _ => std::future::pending().await,
+                }
+            }
+        });
+
+        // 1s, then 2s of backoff
+        sleep(Duration::from_millis(3_500)).await;
+        assert_eq!(attempts.load(Ordering::SeqCst), 3);
+        let status = &supervisor.statuses()["grouper"];
+        assert_eq!((status.state, status.restarts), (ComponentState::Running, 2));
+        assert!(status.last_error.as_deref().unwrap().contains("boom"));
+        assert!(supervisor.is_live());
+
+        // running components stop on their own once their input closes
+        trigger.trigger();
+        handle.abort();
+        assert!(handle.await.is_err());
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_no_restart_during_shutdown() {
+        let supervisor = Arc::new(Supervisor::new());
+        let (trigger, shutdown) = crate::shutdown::channel();
+        trigger.trigger();
+
+        let handle = supervisor.spawn("executor", shutdown, || async { Err::<(), _>("database gone") });
+        handle.await.unwrap();
+        let status = &supervisor.statuses()["executor"];
+        assert_eq!((status.state, status.restarts), (ComponentState::Stopped, 0));
+        assert!(!supervisor.is_live());
+    }
+}
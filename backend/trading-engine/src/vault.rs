@@ -0,0 +1,455 @@
+//! Follower agent key storage. Each secret is encrypted with its own data key and only
+//! the data key is wrapped with the configured master key, so master key rotation just
+//! rewraps data keys. Plaintext only leaves through `KeyVault::signing_key`.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use std::collections::{HashMap, HashSet};
+use std::env;
+use std::fmt;
+use std::sync::RwLock;
+use sqlx::{PgConnection, PgPool};
+use thiserror::Error;
+use zeroize::Zeroizing;
+
+/// Prefix of values produced by `AgentKeyCipher::encrypt`.
+const CIPHERTEXT_PREFIX: &str = "v1:";
+const NONCE_LEN: usize = 12;
+const DEFAULT_MASTER_KEY_ID: &str = "primary";
+
+#[derive(Error, Debug)]
+pub enum VaultError {
+    #[error("Encryption key must be 32 bytes of hex: {0}")]
+    InvalidMasterKey(String),
+    #[error("Unknown master key id: {0}")]
+    UnknownMasterKey(String),
+    #[error("Stored agent key is malformed")]
+    MalformedCiphertext,
+    #[error("Failed to encrypt agent key")]
+    Encryption,
+    #[error("Failed to decrypt agent key (wrong encryption key or corrupted value)")]
+    Decryption,
+    #[error("Agent key {0} not found or revoked")]
+    KeyUnavailable(i32),
+    #[error("Database error: {0}")]
+    SqlxError(#[from] sqlx::Error),
+}
+
+/// AES-256-GCM over a single key; used both for master keys and per-secret data keys.
+#[derive(Clone)]
+pub struct AgentKeyCipher {
+    cipher: Aes256Gcm,
+}
+
+impl fmt::Debug for AgentKeyCipher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("AgentKeyCipher(..)")
+    }
+}
+
+impl AgentKeyCipher {
+    pub fn from_hex(hex_key: &str) -> Result<Self, VaultError> {
+        let bytes = Zeroizing::new(
+            hex::decode(hex_key.trim().trim_start_matches("0x"))
+                .map_err(|e| VaultError::InvalidMasterKey(e.to_string()))?,
+        );
+        if bytes.len() != 32 {
+            return Err(VaultError::InvalidMasterKey(format!("got {} bytes", bytes.len())));
+        }
+        let key = Key::<Aes256Gcm>::from_slice(&bytes);
+        Ok(Self {
+            cipher: Aes256Gcm::new(key),
+        })
+    }
+
+    /// A fresh random key, returned with its hex encoding so it can be wrapped.
+    fn generate() -> (Self, Zeroizing<String>) {
+        let key = Aes256Gcm::generate_key(&mut OsRng);
+        let encoded = Zeroizing::new(hex::encode(key));
+        (Self { cipher: Aes256Gcm::new(&key) }, encoded)
+    }
+
+    pub fn is_encrypted(stored: &str) -> bool {
+        stored.starts_with(CIPHERTEXT_PREFIX)
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> Result<String, VaultError> {
+        let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+        let ciphertext = self
+            .cipher
+            .encrypt(&nonce, plaintext.as_bytes())
+            .map_err(|_| VaultError::Encryption)?;
+
+        let mut payload = nonce.to_vec();
+        payload.extend_from_slice(&ciphertext);
+        Ok(format!("{}{}", CIPHERTEXT_PREFIX, hex::encode(payload)))
+    }
+
+    pub fn decrypt(&self, stored: &str) -> Result<Zeroizing<String>, VaultError> {
+        let payload = stored
+            .strip_prefix(CIPHERTEXT_PREFIX)
+            .ok_or(VaultError::MalformedCiphertext)?;
+        let payload = hex::decode(payload).map_err(|_| VaultError::MalformedCiphertext)?;
+        if payload.len() <= NONCE_LEN {
+            return Err(VaultError::MalformedCiphertext);
+        }
+
+        let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+        let plaintext = Zeroizing::new(
+            self.cipher
+                .decrypt(Nonce::from_slice(nonce), ciphertext)
+                .map_err(|_| VaultError::Decryption)?,
+        );
+        let plaintext = String::from_utf8(plaintext.to_vec()).map_err(|_| VaultError::Decryption)?;
+        Ok(Zeroizing::new(plaintext))
+    }
+}
+
+/// The active master key plus any retired ones still needed to unwrap old data keys.
+#[derive(Clone, Debug)]
+pub struct MasterKeys {
+    active_id: String,
+    keys: HashMap<String, AgentKeyCipher>,
+}
+
+impl MasterKeys {
+    pub fn new(active_id: &str, active: AgentKeyCipher) -> Self {
+        let mut keys = HashMap::new();
+        keys.insert(active_id.to_string(), active);
+        Self {
+            active_id: active_id.to_string(),
+            keys,
+        }
+    }
+
+    pub fn with_retired(mut self, id: &str, key: AgentKeyCipher) -> Self {
+        self.keys.entry(id.to_string()).or_insert(key);
+        self
+    }
+
+    /// `AGENT_KEY_ENCRYPTION_KEY` (hex) is the active key, named by `AGENT_KEY_ENCRYPTION_KEY_ID`.
+    /// Retired keys go in `AGENT_KEY_RETIRED_KEYS` as comma separated `id:hex` pairs.
+    pub fn from_env() -> Result<Self, VaultError> {
+        let active = env::var("AGENT_KEY_ENCRYPTION_KEY")
+            .map_err(|_| VaultError::InvalidMasterKey("AGENT_KEY_ENCRYPTION_KEY must be set".to_string()))?;
+        let active_id = env::var("AGENT_KEY_ENCRYPTION_KEY_ID").unwrap_or_else(|_| DEFAULT_MASTER_KEY_ID.to_string());
+        let mut master = Self::new(&active_id, AgentKeyCipher::from_hex(&active)?);
+
+        if let Ok(retired) = env::var("AGENT_KEY_RETIRED_KEYS") {
+            for entry in retired.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+                let (id, key) = entry
+                    .split_once(':')
+                    .ok_or_else(|| VaultError::InvalidMasterKey("retired keys must be id:hex pairs".to_string()))?;
+                master = master.with_retired(id, AgentKeyCipher::from_hex(key)?);
+            }
+        }
+        Ok(master)
+    }
+
+    fn get(&self, id: &str) -> Result<&AgentKeyCipher, VaultError> {
+        self.keys.get(id).ok_or_else(|| VaultError::UnknownMasterKey(id.to_string()))
+    }
+
+    fn active(&self) -> &AgentKeyCipher {
+        &self.keys[&self.active_id]
+    }
+}
+
+/// An agent secret as persisted in `agent_keys`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct SealedKey {
+    pub master_key_id: String,
+    pub wrapped_data_key: String,
+    pub ciphertext: String,
+}
+
+impl SealedKey {
+    pub fn seal(master: &MasterKeys, secret: &str) -> Result<Self, VaultError> {
+        let (data_key, encoded) = AgentKeyCipher::generate();
+        Ok(Self {
+            master_key_id: master.active_id.clone(),
+            wrapped_data_key: master.active().encrypt(&encoded)?,
+            ciphertext: data_key.encrypt(secret)?,
+        })
+    }
+
+    pub fn open(&self, master: &MasterKeys) -> Result<Zeroizing<String>, VaultError> {
+        let data_key = self.data_key(master)?;
+        data_key.decrypt(&self.ciphertext)
+    }
+
+    /// Rewrap the data key under the active master key; the secret's ciphertext is untouched.
+    pub fn rewrap(&self, master: &MasterKeys) -> Result<Self, VaultError> {
+        let encoded = master.get(&self.master_key_id)?.decrypt(&self.wrapped_data_key)?;
+        Ok(Self {
+            master_key_id: master.active_id.clone(),
+            wrapped_data_key: master.active().encrypt(&encoded)?,
+            ciphertext: self.ciphertext.clone(),
+        })
+    }
+
+    fn data_key(&self, master: &MasterKeys) -> Result<AgentKeyCipher, VaultError> {
+        let encoded = master.get(&self.master_key_id)?.decrypt(&self.wrapped_data_key)?;
+        AgentKeyCipher::from_hex(&encoded).map_err(|_| VaultError::Decryption)
+    }
+}
+
+/// Follower agent key store backed by the `agent_keys` table.
+#[derive(Debug)]
+pub struct KeyVault {
+    pool: PgPool,
+    master: MasterKeys,
+    /// Keys revoked by this process, so cached signing clients stop immediately
+    revoked: RwLock<HashSet<i32>>,
+    /// Keys rotated by this process, to the key that replaced them
+    replaced_by: RwLock<HashMap<i32, i32>>,
+}
+
+impl KeyVault {
+    pub fn new(pool: PgPool, master: MasterKeys) -> Self {
+        Self {
+            pool,
+            master,
+            revoked: RwLock::new(HashSet::new()),
+            replaced_by: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Store a new agent key for a follower, revoking the one it replaces. Returns the key id.
+    pub async fn store(&self, follower_id: i32, secret: &str) -> Result<i32, VaultError> {
+        let mut tx = self.pool.begin().await?;
+        let (id, replaced) = self.insert_key(&mut tx, follower_id, secret).await?;
+        tx.commit().await?;
+
+        self.mark_rotated(replaced, id);
+        Ok(id)
+    }
+
+    /// Store the agent key of a follower created in the same transaction, so that
+    /// neither exists unless `tx` commits. Returns the key id.
+    pub async fn store_new(&self, tx: &mut PgConnection, follower_id: i32, secret: &str) -> Result<i32, VaultError> {
+        // a new follower has no key to replace
+        let (id, _) = self.insert_key(tx, follower_id, secret).await?;
+        Ok(id)
+    }
+
+    /// Revoke the follower's active key and insert `secret` as its new one; returns
+    /// the new key id and the revoked ones.
+    async fn insert_key(&self, tx: &mut PgConnection, follower_id: i32, secret: &str) -> Result<(i32, Vec<i32>), VaultError> {
+        let sealed = SealedKey::seal(&self.master, secret)?;
+
+        let replaced: Vec<i32> = sqlx::query_scalar(
+            "UPDATE agent_keys SET revoked_at = NOW()
+             WHERE follower_id = $1 AND revoked_at IS NULL
+             RETURNING id",
+        )
+        .bind(follower_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let id: i32 = sqlx::query_scalar(
+            "INSERT INTO agent_keys (follower_id, master_key_id, wrapped_data_key, ciphertext)
+             VALUES ($1, $2, $3, $4)
+             RETURNING id",
+        )
+        .bind(follower_id)
+        .bind(&sealed.master_key_id)
+        .bind(&sealed.wrapped_data_key)
+        .bind(&sealed.ciphertext)
+        .fetch_one(&mut *tx)
+        .await?;
+        Ok((id, replaced))
+    }
+
+    /// Revoke a follower's active agent key. Returns false if there was none.
+    pub async fn revoke(&self, follower_id: i32) -> Result<bool, VaultError> {
+        let revoked: Vec<i32> = sqlx::query_scalar(
+            "UPDATE agent_keys SET revoked_at = NOW()
+             WHERE follower_id = $1 AND revoked_at IS NULL
+             RETURNING id",
+        )
+        .bind(follower_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let any = !revoked.is_empty();
+        self.mark_revoked(revoked);
+        Ok(any)
+    }
+
+    pub fn is_revoked(&self, key_id: i32) -> bool {
+        self.revoked.read().map(|r| r.contains(&key_id)).unwrap_or(true)
+    }
+
+    /// The key to sign with for a follower last known to use `key_id`: the key that
+    /// replaced it if it was rotated, `None` if it was revoked without a replacement.
+    pub fn current_key(&self, key_id: i32) -> Option<i32> {
+        let mut current = key_id;
+        if let Ok(replaced_by) = self.replaced_by.read() {
+            // a new key always has a higher id, so this ends
+            while let Some(next) = replaced_by.get(&current) {
+                current = *next;
+            }
+        }
+        (!self.is_revoked(current)).then_some(current)
+    }
+
+    /// Decrypt an active agent key for signing. This is the only way plaintext leaves the vault.
+    pub async fn signing_key(&self, key_id: i32) -> Result<Zeroizing<String>, VaultError> {
+        let row = sqlx::query_as::<_, (String, String, String)>(
+            "SELECT master_key_id, wrapped_data_key, ciphertext FROM agent_keys
+             WHERE id = $1 AND revoked_at IS NULL",
+        )
+        .bind(key_id)
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(VaultError::KeyUnavailable(key_id))?;
+
+        let (master_key_id, wrapped_data_key, ciphertext) = row;
+        SealedKey { master_key_id, wrapped_data_key, ciphertext }.open(&self.master)
+    }
+
+    /// Rewrap every data key that is not under the active master key.
+    pub async fn rotate_master_key(&self) -> Result<usize, VaultError> {
+        let rows = sqlx::query_as::<_, (i32, String, String, String)>(
+            "SELECT id, master_key_id, wrapped_data_key, ciphertext FROM agent_keys WHERE master_key_id <> $1",
+        )
+        .bind(&self.master.active_id)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let count = rows.len();
+        for (id, master_key_id, wrapped_data_key, ciphertext) in rows {
+            let sealed = SealedKey { master_key_id, wrapped_data_key, ciphertext }.rewrap(&self.master)?;
+            sqlx::query("UPDATE agent_keys SET master_key_id = $2, wrapped_data_key = $3 WHERE id = $1")
+                .bind(id)
+                .bind(&sealed.master_key_id)
+                .bind(&sealed.wrapped_data_key)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(count)
+    }
+
+    /// Move keys still held in `followers.agent_signature` (plaintext, or encrypted directly
+    /// with a master key) into the vault and clear the column.
+    pub async fn import_legacy_keys(&self) -> Result<usize, VaultError> {
+        let rows = sqlx::query_as::<_, (i32, String)>(
+            "SELECT id, agent_signature FROM followers WHERE agent_signature IS NOT NULL",
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let count = rows.len();
+        for (follower_id, stored) in rows {
+            let stored = Zeroizing::new(stored);
+            let secret = if AgentKeyCipher::is_encrypted(&stored) {
+                self.decrypt_with_any_master(&stored)?
+            } else {
+                stored
+            };
+            self.store(follower_id, &secret).await?;
+            sqlx::query("UPDATE followers SET agent_signature = NULL WHERE id = $1")
+                .bind(follower_id)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(count)
+    }
+
+    fn decrypt_with_any_master(&self, stored: &str) -> Result<Zeroizing<String>, VaultError> {
+        self.master
+            .keys
+            .values()
+            .find_map(|key| key.decrypt(stored).ok())
+            .ok_or(VaultError::Decryption)
+    }
+
+    /// Record that `replaced` were revoked in favour of `id`.
+    pub(crate) fn mark_rotated(&self, replaced: Vec<i32>, id: i32) {
+        if let Ok(mut replaced_by) = self.replaced_by.write() {
+            replaced_by.extend(replaced.iter().map(|old| (*old, id)));
+        }
+        self.mark_revoked(replaced);
+    }
+
+    fn mark_revoked(&self, ids: Vec<i32>) {
+        if let Ok(mut revoked) = self.revoked.write() {
+            revoked.extend(ids);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MASTER: &str = "000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f";
+    const SECRET: &str = "0xe908f86dbb4d55ac876378565aafeabc187f6690f046459397b17d9b9a19688e";
+
+    #[test]
+    fn test_roundtrip() {
+        let cipher = AgentKeyCipher::from_hex(MASTER).unwrap();
+
+        let stored = cipher.encrypt(SECRET).unwrap();
+        assert!(AgentKeyCipher::is_encrypted(&stored));
+        assert!(!stored.contains(&SECRET[2..]));
+        // fresh nonce every time
+        assert_ne!(stored, cipher.encrypt(SECRET).unwrap());
+
+        assert_eq!(cipher.decrypt(&stored).unwrap().as_str(), SECRET);
+    }
+
+    #[test]
+    fn test_rejects_bad_input() {
+        assert!(matches!(AgentKeyCipher::from_hex("abcd"), Err(VaultError::InvalidMasterKey(_))));
+
+        let cipher = AgentKeyCipher::from_hex(MASTER).unwrap();
+        let other = AgentKeyCipher::from_hex(&MASTER.replace("00", "ff")).unwrap();
+        let stored = cipher.encrypt("secret").unwrap();
+
+        assert!(matches!(other.decrypt(&stored), Err(VaultError::Decryption)));
+        assert!(matches!(cipher.decrypt("plaintext"), Err(VaultError::MalformedCiphertext)));
+        assert!(matches!(cipher.decrypt("v1:00"), Err(VaultError::MalformedCiphertext)));
+    }
+
+    #[test]
+    fn test_envelope_seal_and_master_rotation() {
+        let old = MasterKeys::new("2025", AgentKeyCipher::from_hex(MASTER).unwrap());
+        let sealed = SealedKey::seal(&old, SECRET).unwrap();
+        assert_eq!(sealed.master_key_id, "2025");
+        assert!(!sealed.ciphertext.contains(&SECRET[2..]));
+        assert_eq!(sealed.open(&old).unwrap().as_str(), SECRET);
+
+        // new active key, old one retired
+        let new_key = AgentKeyCipher::from_hex(&MASTER.replace("00", "aa")).unwrap();
+        let rotated = MasterKeys::new("2026", new_key.clone())
+            .with_retired("2025", AgentKeyCipher::from_hex(MASTER).unwrap());
+        assert_eq!(sealed.open(&rotated).unwrap().as_str(), SECRET);
+
+        let rewrapped = sealed.rewrap(&rotated).unwrap();
+        assert_eq!(rewrapped.master_key_id, "2026");
+        assert_eq!(rewrapped.ciphertext, sealed.ciphertext);
+
+        // once rewrapped the retired key is no longer needed
+        let only_new = MasterKeys::new("2026", new_key);
+        assert_eq!(rewrapped.open(&only_new).unwrap().as_str(), SECRET);
+        assert!(matches!(sealed.open(&only_new), Err(VaultError::UnknownMasterKey(id)) if id == "2025"));
+    }
+
+    #[tokio::test]
+    async fn test_current_key_follows_rotations() {
+        let pool = PgPool::connect_lazy("postgres://localhost/unused").unwrap();
+        let vault = KeyVault::new(pool, MasterKeys::new("2025", AgentKeyCipher::from_hex(MASTER).unwrap()));
+        assert_eq!(vault.current_key(1), Some(1));
+
+        vault.mark_rotated(vec![1], 2);
+        vault.mark_rotated(vec![2], 3);
+        assert_eq!(vault.current_key(1), Some(3));
+        assert_eq!(vault.current_key(3), Some(3));
+
+        // revoked with nothing to replace it
+        vault.mark_revoked(vec![3]);
+        assert_eq!(vault.current_key(1), None);
+    }
+}